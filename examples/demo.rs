@@ -1,5 +1,5 @@
 use std::time::Duration;
-use wacohtech_force_torque_sensor::Wdf6m200;
+use wacohtech_force_torque_sensor::{Wdf6m200, WrenchSamplingScheduler};
 
 fn main() {
     println!("Demonstration started!");
@@ -11,13 +11,16 @@ fn main() {
     // 力覚センサに力がはたらいていなくても，力覚センサからの値は0とはならない．
     // そこで，力覚センサから何度か値を取得して，その平均をオフセットとしてとることでゼロ点を設定する．
     println!("Performing calibration. Do not touch the sensor...");
-    sensor.calibrate(period, 100);
+    sensor.calibrate(period, 100).unwrap();
     println!("Calibration done!");
 
     // 1000回力の測定を行う．
     let count = 1000;
 
-    for i in 0..count {
+    // 一定周期で測定を行う．
+    let scheduler = WrenchSamplingScheduler::new(1.0 / period.as_secs_f64());
+
+    for (i, _) in scheduler.take(count).enumerate() {
         // まずセンサからの情報を更新．
         // エラーが発生したらその内容を表示する．
         if let Err(err) = sensor.update() {
@@ -27,10 +30,7 @@ fn main() {
         // レンチを取得
         let wrench = sensor.last_measurement();
         // レンチを表示
-        println!("[{}/{}]: {:?}", i + 1, count, wrench);
-
-        // 次の観測時刻まで待機
-        std::thread::sleep(period);
+        println!("[{}/{}]: {}", i + 1, count, wrench);
     }
 
     println!("Demonstration finished!");
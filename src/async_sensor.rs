@@ -0,0 +1,308 @@
+//! tokioベースの非同期ドライバ．
+
+use crate::calibration::CalibrationAccumulator;
+use crate::protocol::{self, RawReading, NEWLINE, NEWLINE_BYTES, RESPONSE_BYTES, STOP_COMMAND};
+use crate::{
+    assert_valid_sensitivity, find_sensor_port, CalibrationReport, DynPick, PerNewton,
+    PerNewtonMeter, Sensitivity, SensorError, SensorSpec, Wdf6m200Spec, Wrench, BAUD_RATE,
+};
+use pair_macro::Triplet;
+use std::marker::PhantomData;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_serial::{SerialPortBuilderExt, SerialStream};
+
+/// Wacohtech DynPickシリーズの6軸力覚センサの非同期版ドライバ．
+/// 型式ごとの感度などの仕様は`S`によって与えられる．
+///
+/// `DynPick`と同じ操作を提供するが，シリアル通信の待機中に実行スレッドを占有しないため，
+/// tokioベースのロボット制御システムの中で利用できる．
+pub struct AsyncDynPick<S: SensorSpec> {
+    /// センサに接続されたシリアルポート．
+    serial_port: SerialStream,
+    /// 読み取り操作のタイムアウト時間．
+    read_timeout: Duration,
+    /// 現在のセンサ出力値．
+    raw_wrench: Wrench,
+    /// センサ出力値から減ずる補正値．
+    offset: Wrench,
+    /// デジタル出力値の換算に用いる，力とトルクの各軸の感度．
+    /// 型式が感度の既定値を持たず，まだ感度を設定していない場合は`None`．
+    sensitivity: Option<Sensitivity>,
+    /// 受信したが，まだフレームとして解釈していないバイト列．
+    frames: FrameBuffer,
+    /// 接続しているセンサの仕様．
+    spec: PhantomData<fn() -> S>,
+}
+
+/// WDF-6M200-3 Wacohtech 6-axis force/touque sensor の非同期版ドライバ．
+pub type AsyncWdf6m200 = AsyncDynPick<Wdf6m200Spec>;
+
+impl<S: SensorSpec> AsyncDynPick<S> {
+    /// コンピュータに接続されたセンサとの通信を確立する．
+    /// # Params
+    /// 1. `read_timeout_duration`: シリアル通信の読み取り操作がこの時間経過しても完了していない場合，タイムアウトとなる．
+    ///
+    /// # Returns
+    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub async fn open(read_timeout_duration: Duration) -> Result<AsyncDynPick<S>, SensorError> {
+        let sensor_port_path = find_sensor_port()?;
+
+        // 同期版のドライバと同じ通信設定を用いる
+        let serial_port = tokio_serial::new(sensor_port_path.to_string_lossy(), BAUD_RATE)
+            .data_bits(tokio_serial::DataBits::Eight)
+            .flow_control(tokio_serial::FlowControl::None)
+            .parity(tokio_serial::Parity::None)
+            .stop_bits(tokio_serial::StopBits::One)
+            .timeout(read_timeout_duration)
+            .open_native_async()
+            .map_err(std::io::Error::from)?;
+
+        let mut sensor = Self {
+            serial_port,
+            read_timeout: read_timeout_duration,
+            raw_wrench: Wrench::zeroed(),
+            offset: Wrench::zeroed(),
+            sensitivity: DynPick::<S>::default_sensitivity(),
+            frames: FrameBuffer::default(),
+            spec: PhantomData,
+        };
+
+        // 最初のupdate()に備えて，データを送信するようにセンサに要求する
+        sensor.send_request().await?;
+
+        Ok(sensor)
+    }
+
+    /// デジタル出力値の換算に用いる各軸の感度を設定する．
+    /// 型式が感度の既定値を持たない場合は，最初の`update`の前に設定する必要がある．
+    /// # Params
+    /// 1. `force`: 力の各軸について，1Nあたりデジタル出力値がいくつ変化するか．
+    /// 1. `torque`: トルクの各軸について，1Nmあたりデジタル出力値がいくつ変化するか．
+    ///
+    /// # Panics
+    /// いずれかの感度が0，または有限でない場合．
+    pub fn set_sensitivity(
+        &mut self,
+        force: Triplet<PerNewton<f64>>,
+        torque: Triplet<PerNewtonMeter<f64>>,
+    ) {
+        assert_valid_sensitivity(force, torque);
+        self.sensitivity = Some((force, torque));
+    }
+
+    /// 最後にこのセンサから取得した測定値を返す．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    /// センサと通信して観測値を更新するには`update`メソッドを利用する．
+    pub fn last_measurement(&self) -> Wrench {
+        self.raw_wrench - self.offset
+    }
+
+    /// センサと通信して，測定値情報を更新する．
+    /// 更新した測定値を得るには`last_measurement`メソッドを利用する．
+    ///
+    /// タイムアウトまでに届いたフレームの一部は次の呼び出しに持ち越し，フレームの区切りは改行コードをもとに再同期する．
+    /// 受信に失敗した場合も改めて送信を要求するため，次の呼び出しでは改めて出力値を受信できる．
+    /// 感度が定まっていない場合は，受信せずに`Err(SensorError::MissingSensitivity)`を返す．
+    pub async fn update(&mut self) -> Result<(), SensorError> {
+        let (force_sensitivity, torque_sensitivity) =
+            self.sensitivity.ok_or(SensorError::MissingSensitivity)?;
+
+        let received = tokio::time::timeout(self.read_timeout, self.receive_frame())
+            .await
+            .unwrap_or_else(|_| Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into()));
+        let reading = match received {
+            Ok(reading) => reading,
+            Err(e) => {
+                // 要求しない限りセンサは何も送信しないので，次の呼び出しに備えて改めて送信を要求する．
+                // 要求に失敗しても次の呼び出しで再び試みるので，受信に失敗した理由を優先して返す
+                let _ = self.send_request().await;
+                return Err(e);
+            }
+        };
+        self.raw_wrench = DynPick::<S>::convert_digitals_to_raw_wrench(
+            DynPick::<S>::center_digitals(reading.counts),
+            force_sensitivity,
+            torque_sensitivity,
+        );
+
+        // 次の観測に備えて，センサに力を送信するように命令しておく
+        self.send_request().await?;
+
+        Ok(())
+    }
+
+    /// 指定した期間センサからの出力を受信し，その平均をゼロ点とすることでキャリブレーションを行う．
+    /// 測定値の受信を待つ間や次の受信時刻までの間は`await`するため，実行スレッドを占有しない．
+    ///
+    /// # Returns
+    /// 受信に成功した測定値の個数と，その各軸の平均および標準偏差．
+    /// 受信に成功した測定値が試行回数の半分に満たない場合は`SensorError::InsufficientSamples`を返し，ゼロ点は変更しない．
+    ///
+    /// # Panics
+    /// `measurement_times`が0の場合．
+    pub async fn calibrate(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError> {
+        self.calibrate_with_progress(measurement_period, measurement_times, |_, _| {})
+            .await
+    }
+
+    /// `calibrate`と同様にキャリブレーションを行い，測定値を1つ受信しようとするたびに`progress`を呼び出す．
+    /// # Params
+    /// 1. `measurement_period`: 測定値を受信する間隔．
+    /// 1. `measurement_times`: 測定値の受信を試みる回数．
+    /// 1. `progress`: 進捗を受け取るコールバック．(試行済みの回数, `measurement_times`)が渡される．
+    ///
+    /// # Returns
+    /// `calibrate`と同じ．
+    ///
+    /// # Panics
+    /// `measurement_times`が0の場合．
+    pub async fn calibrate_with_progress(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<CalibrationReport, SensorError> {
+        assert!(measurement_times > 0);
+        if self.sensitivity.is_none() {
+            return Err(SensorError::MissingSensitivity);
+        }
+
+        let mut accumulator = CalibrationAccumulator::new();
+
+        // 指定回数，センサからの生データを集計する．受信に失敗した測定値は用いない
+        for i in 0..measurement_times {
+            match self.update().await {
+                Ok(()) => accumulator.push(self.raw_wrench),
+                Err(_) => accumulator.push_failure(),
+            }
+            progress(i + 1, measurement_times);
+            // 次の取得時刻まで待機．スレッドを止めずに他のタスクへ実行を譲る
+            tokio::time::sleep(measurement_period).await;
+        }
+        // 生データの平均をとり，補正後の値が0となるようにオフセットを定める．
+        let report = accumulator.finish()?;
+        self.offset = report.mean;
+        Ok(report)
+    }
+
+    /// センサにデータ送信の停止を指令する．
+    pub async fn request_stop(&mut self) -> Result<(), SensorError> {
+        self.send_command(STOP_COMMAND).await?;
+        self.serial_port.flush().await?;
+        Ok(())
+    }
+
+    /// センサに1回分の出力値の送信を要求する．
+    async fn send_request(&mut self) -> Result<(), SensorError> {
+        self.serial_port
+            .write_all(&protocol::encode_request())
+            .await?;
+        Ok(())
+    }
+
+    /// センサに1バイトの命令を送信する．
+    async fn send_command(&mut self, command: u8) -> Result<(), SensorError> {
+        self.serial_port.write_all(&[command]).await?;
+        Ok(())
+    }
+
+    /// 1フレーム分のデータが揃うまで受信し，解釈して返す．
+    /// 読み取りの途中で中断されても，それまでに受信したバイト列は失われない．
+    async fn receive_frame(&mut self) -> Result<RawReading, SensorError> {
+        loop {
+            if let Some(frame) = self.frames.take_frame() {
+                return Ok(S::parse_response(&frame)?);
+            }
+
+            let mut chunk = [0; RESPONSE_BYTES];
+            match self.serial_port.read(&mut chunk).await? {
+                0 => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+                count => self.frames.extend(&chunk[..count]),
+            }
+        }
+    }
+}
+
+/// 受信したが，まだフレームとして解釈していないバイト列．
+/// 改行コードをフレームの区切りとして，受信したバイト列を1フレームずつ取り出す．
+#[derive(Debug, Clone, Default)]
+struct FrameBuffer {
+    /// 受信したバイト列．改行コードが届くまで蓄える．
+    bytes: Vec<u8>,
+}
+
+impl FrameBuffer {
+    /// 受信したバイト列を末尾に追加する．
+    /// 改行コードが届かないまま1フレーム分を超えた場合は，次のフレームの一部ではありえない先頭部分を捨てる．
+    fn extend(&mut self, received: &[u8]) {
+        self.bytes.extend_from_slice(received);
+        if !self.bytes.windows(NEWLINE_BYTES).any(|w| w == NEWLINE) {
+            // 改行コードを含まない1フレームの途中までは，高々RESPONSE_BYTES - 1バイトである
+            let excess = self.bytes.len().saturating_sub(RESPONSE_BYTES - 1);
+            self.bytes.drain(..excess);
+        }
+    }
+
+    /// 最初の改行コードまでを1フレームとして取り出す．改行コードがまだ届いていなければ`None`を返す．
+    /// 改行コードより前に1フレーム分を超えるバイト列がある場合は，
+    /// 区切りのずれた前のフレームの残りとみなして捨て，改行コードの直前の1フレーム分を返す．
+    fn take_frame(&mut self) -> Option<Vec<u8>> {
+        let end = self
+            .bytes
+            .windows(NEWLINE_BYTES)
+            .position(|w| w == NEWLINE)?
+            + NEWLINE_BYTES;
+        let start = end.saturating_sub(RESPONSE_BYTES);
+        let frame = self.bytes[start..end].to_vec();
+        self.bytes.drain(..end);
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// すべての軸のデジタル出力値が中央値である，レコード番号0のフレーム．
+    const CENTERED_FRAME: &[u8; RESPONSE_BYTES] = b"0200020002000200020002000\r\n";
+
+    #[test]
+    fn partial_frame_is_kept_until_newline_arrives() {
+        let mut frames = FrameBuffer::default();
+        frames.extend(&CENTERED_FRAME[..10]);
+        assert_eq!(frames.take_frame(), None);
+
+        frames.extend(&CENTERED_FRAME[10..]);
+        assert_eq!(frames.take_frame().as_deref(), Some(&CENTERED_FRAME[..]));
+        assert_eq!(frames.take_frame(), None);
+    }
+
+    #[test]
+    fn leftover_of_broken_frame_is_dropped_on_newline() {
+        // 末尾を取りこぼしたフレームの後に，次のフレームが届く
+        let mut frames = FrameBuffer::default();
+        frames.extend(&CENTERED_FRAME[..20]);
+        frames.extend(CENTERED_FRAME);
+
+        assert_eq!(frames.take_frame().as_deref(), Some(&CENTERED_FRAME[..]));
+        assert!(frames.bytes.is_empty());
+    }
+
+    #[test]
+    fn noise_without_newline_does_not_grow_buffer() {
+        let mut frames = FrameBuffer::default();
+        for _ in 0..100 {
+            frames.extend(&[b'x'; RESPONSE_BYTES]);
+        }
+        assert_eq!(frames.bytes.len(), RESPONSE_BYTES - 1);
+
+        frames.extend(CENTERED_FRAME);
+        assert_eq!(frames.take_frame().as_deref(), Some(&CENTERED_FRAME[..]));
+    }
+}
@@ -0,0 +1,129 @@
+//! 力覚センサの測定値を，ROS 2のトピックに`geometry_msgs/msg/WrenchStamped`として配信するノード．
+//!
+//! ```text
+//! dynpick_ros2_publisher [--model MODEL] [--port PATH] [--topic TOPIC] [--frame-id FRAME]
+//!                        [--rate HZ] [--calibration-samples N] [--node-name NAME]
+//! ```
+//! 起動時にセンサに力がはたらいていない状態でキャリブレーションを行ってから，配信を始める．
+
+use r2r::geometry_msgs::msg::WrenchStamped;
+use std::error::Error;
+use std::time::Duration;
+use wacohtech_force_torque_sensor::{
+    DynPick, SensorModel, SensorSpec, Wdf6m200Spec, Wdf6m200_14Spec, Wef6a200Spec,
+    WrenchSamplingScheduler,
+};
+
+/// ノードの設定．
+struct Options {
+    /// 接続するセンサの型式．
+    model: SensorModel,
+    /// センサが接続されたシリアルポートへのパス．`None`の場合は自動で探す．
+    port: Option<String>,
+    /// 配信するトピック名．
+    topic: String,
+    /// メッセージのヘッダに設定する座標系の名前．
+    frame_id: String,
+    /// 配信の周波数[Hz]．
+    rate_hz: f64,
+    /// キャリブレーションに用いる測定値の個数．
+    calibration_samples: usize,
+    /// ノード名．
+    node_name: String,
+}
+
+impl Options {
+    /// コマンドライン引数から設定を読み込む．指定のない項目は既定値とする．
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            model: SensorModel::Wdf6m200_3,
+            port: None,
+            topic: "/wrench".to_string(),
+            frame_id: "ft_sensor".to_string(),
+            rate_hz: 100.0,
+            calibration_samples: 100,
+            node_name: "dynpick_publisher".to_string(),
+        };
+
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {}", flag));
+            match flag.as_str() {
+                "--model" => {
+                    let name = value()?;
+                    options.model = SensorModel::ALL
+                        .iter()
+                        .copied()
+                        .find(|model| model.name().eq_ignore_ascii_case(&name))
+                        .ok_or(format!("Unknown sensor model {}", name))?;
+                }
+                "--port" => options.port = Some(value()?),
+                "--topic" => options.topic = value()?,
+                "--frame-id" => options.frame_id = value()?,
+                "--rate" => {
+                    options.rate_hz = value()?
+                        .parse()
+                        .ok()
+                        .filter(|&hz: &f64| hz > 0.0)
+                        .ok_or("--rate must be a positive number")?;
+                }
+                "--calibration-samples" => {
+                    options.calibration_samples = value()?
+                        .parse()
+                        .ok()
+                        .filter(|&n: &usize| n > 0)
+                        .ok_or("--calibration-samples must be a positive integer")?;
+                }
+                "--node-name" => options.node_name = value()?,
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    match options.model {
+        SensorModel::Wdf6m200_3 => run::<Wdf6m200Spec>(&options),
+        SensorModel::Wdf6m200_14 => run::<Wdf6m200_14Spec>(&options),
+        SensorModel::Wef6a200_4 => run::<Wef6a200Spec>(&options),
+    }
+}
+
+/// センサと接続し，キャリブレーションを行ってから測定値を配信し続ける．
+fn run<S: SensorSpec>(options: &Options) -> Result<(), Box<dyn Error>> {
+    let period = Duration::from_secs_f64(1.0 / options.rate_hz);
+    // 受信の遅れで配信が止まらないよう，読み取りのタイムアウトは配信周期より長めにとる
+    let timeout = period.max(Duration::from_millis(10)) * 2;
+    let mut sensor = match &options.port {
+        Some(port) => DynPick::<S>::open_path(port, timeout)?,
+        None => DynPick::<S>::open(timeout)?,
+    };
+    // 感度の既定値を持たない型式では，センサに書き込まれた感度を用いる
+    if sensor.force_sensitivity().is_none() {
+        sensor.read_device_sensitivity()?;
+    }
+
+    eprintln!("Performing calibration. Do not touch the sensor...");
+    let report = sensor.calibrate(period, options.calibration_samples)?;
+    eprintln!("Calibration done with {} samples", report.sample_count);
+
+    let context = r2r::Context::create()?;
+    let mut node = r2r::Node::create(context, &options.node_name, "")?;
+    let publisher =
+        node.create_publisher::<WrenchStamped>(&options.topic, r2r::QosProfile::default())?;
+
+    for _ in WrenchSamplingScheduler::new(options.rate_hz) {
+        match sensor.update_stamped() {
+            Ok(measurement) => {
+                publisher.publish(&measurement.to_wrench_stamped(&options.frame_id))?;
+            }
+            // 受信に失敗してもドライバが次の出力値を要求しなおすので，一時的な失敗では配信を止めない
+            Err(err) => eprintln!("{}", err),
+        }
+        node.spin_once(Duration::from_secs(0));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,373 @@
+//! 測定値を小さなバイナリ形式で記録し，読み出すための機能．
+//!
+//! 記録ファイルは，ヘッダと固定長のレコードの列からなる．数値はすべてリトルエンディアンで格納する．
+//!
+//! ヘッダ:
+//! 1. マジックナンバー`DYNPKLOG`(8バイト)．
+//! 1. 形式のバージョン(`u16`)．
+//! 1. 1レコードのバイト数(`u16`)．
+//! 1. 型式名のバイト数(`u8`)と，UTF-8で表した型式名．
+//!
+//! レコード:
+//! 1. 最初のレコードの受信時刻からの経過時間[ns](`u64`)．
+//! 1. 受信時刻のUNIX時刻[ns](`u64`)．
+//! 1. 通し番号(`u64`)．
+//! 1. レコード番号(`u8`)．
+//! 1. 過負荷の有無(`u8`)．最下位ビットから順にfx, fy, fz, tx, ty, tzに対応する．
+//! 1. 換算前の各軸のデジタル出力値(`u16` × 6)．
+//! 1. 測定値`[fx, fy, fz, tx, ty, tz]`(`f64` × 6，SI単位)．
+
+use crate::logging::MeasurementSink;
+use crate::{Measurement, SensorModel, Wrench, AXIS_COUNT};
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 記録ファイルの先頭に置くマジックナンバー．
+const MAGIC: [u8; 8] = *b"DYNPKLOG";
+/// 記録ファイルの形式のバージョン．
+const FORMAT_VERSION: u16 = 1;
+/// 1レコードのバイト数．
+const RECORD_BYTES: usize = 8 + 8 + 8 + 1 + 1 + 2 * AXIS_COUNT + 8 * AXIS_COUNT;
+
+/// バイナリ形式の記録ファイルに格納された1件の測定値．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BinaryLogRecord {
+    /// 最初のレコードの受信時刻からの経過時間．
+    pub elapsed: Duration,
+    /// 測定値を受信した時刻のシステム時刻．
+    pub system_time: SystemTime,
+    /// ホスト側で付与した通し番号．
+    pub sequence: u64,
+    /// センサが付与したフレームのレコード番号．
+    pub record_number: u8,
+    /// 各軸が過負荷となっているかどうか．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub overload: [bool; AXIS_COUNT],
+    /// 換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub raw_counts: [u16; AXIS_COUNT],
+    /// 測定値．
+    pub wrench: Wrench,
+}
+
+/// 測定値をバイナリ形式で逐次記録する．
+/// 1件あたりのサイズが小さく，高い頻度での長時間の記録に適している．
+/// 測定値は`f64`のまま格納するため，`ReplaySensor::from_binary_log`で記録時と同じ値を再生できる．
+pub struct BinaryLogWriter<W: Write> {
+    /// 書き込み先．
+    writer: BufWriter<W>,
+    /// 最初に記録した測定値の受信時刻．まだ記録していなければ`None`．
+    start: Option<Instant>,
+}
+
+impl BinaryLogWriter<File> {
+    /// ファイルを作成し，ヘッダを書き込む．既にファイルが存在する場合は上書きする．
+    /// # Params
+    /// 1. `path`: 記録ファイルのパス．
+    /// 1. `model`: 記録するセンサの型式．
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        model: SensorModel,
+    ) -> io::Result<BinaryLogWriter<File>> {
+        let file = File::create(path)?;
+        Self::new(file, model)
+    }
+}
+
+impl<W: Write> BinaryLogWriter<W> {
+    /// 任意の書き込み先にヘッダを書き込み，記録を始める．
+    /// # Params
+    /// 1. `writer`: 書き込み先．
+    /// 1. `model`: 記録するセンサの型式．
+    pub fn new(writer: W, model: SensorModel) -> io::Result<BinaryLogWriter<W>> {
+        let mut writer = BufWriter::new(writer);
+        let name = model.name().as_bytes();
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&(RECORD_BYTES as u16).to_le_bytes())?;
+        writer.write_all(&[name.len() as u8])?;
+        writer.write_all(name)?;
+
+        Ok(BinaryLogWriter {
+            writer,
+            start: None,
+        })
+    }
+
+    /// 測定値を1件記録する．
+    /// # Params
+    /// 1. `measurement`: 記録する測定値．
+    /// 1. `raw_counts`: 測定値の換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub fn log(
+        &mut self,
+        measurement: &Measurement,
+        raw_counts: [u16; AXIS_COUNT],
+    ) -> io::Result<()> {
+        let start = *self.start.get_or_insert(measurement.timestamp);
+        let elapsed = measurement.timestamp.saturating_duration_since(start);
+        let unix_time = measurement
+            .system_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let overload = measurement
+            .overload
+            .iter()
+            .enumerate()
+            .filter(|(_, &o)| o)
+            .fold(0u8, |bits, (i, _)| bits | (1 << i));
+
+        let mut record = Vec::with_capacity(RECORD_BYTES);
+        record.extend_from_slice(&(elapsed.as_nanos() as u64).to_le_bytes());
+        record.extend_from_slice(&(unix_time.as_nanos() as u64).to_le_bytes());
+        record.extend_from_slice(&measurement.sequence.to_le_bytes());
+        record.push(measurement.record_number);
+        record.push(overload);
+        for count in raw_counts.iter() {
+            record.extend_from_slice(&count.to_le_bytes());
+        }
+        for e in measurement.wrench.to_array().iter() {
+            record.extend_from_slice(&e.to_le_bytes());
+        }
+        self.writer.write_all(&record)
+    }
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// バッファリングしている内容を書き出し，書き込み先を返す．
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl<W: Write> MeasurementSink for BinaryLogWriter<W> {
+    fn log(&mut self, measurement: &Measurement, raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        BinaryLogWriter::log(self, measurement, raw_counts)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        BinaryLogWriter::flush(self)
+    }
+}
+
+/// `BinaryLogWriter`で記録したファイルから，測定値を順に読み出すイテレータ．
+pub struct BinaryLogReader<R: Read> {
+    /// 読み出し元．
+    reader: R,
+    /// 記録したセンサの型式．
+    model: SensorModel,
+}
+
+impl BinaryLogReader<BufReader<File>> {
+    /// 記録ファイルを開き，ヘッダを読み込む．
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<BinaryLogReader<BufReader<File>>> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> BinaryLogReader<R> {
+    /// 任意の読み出し元からヘッダを読み込む．
+    ///
+    /// # Returns
+    /// マジックナンバーや形式のバージョン，型式名が不正な場合は`io::ErrorKind::InvalidData`のエラーを返す．
+    pub fn new(mut reader: R) -> io::Result<BinaryLogReader<R>> {
+        let invalid_data = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("Not a binary measurement log"));
+        }
+
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        let version = u16::from_le_bytes([header[0], header[1]]);
+        let record_bytes = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if version != FORMAT_VERSION || record_bytes != RECORD_BYTES {
+            return Err(invalid_data("Unsupported binary measurement log version"));
+        }
+
+        let mut name = vec![0; header[4] as usize];
+        reader.read_exact(&mut name)?;
+        let model = SensorModel::ALL
+            .iter()
+            .copied()
+            .find(|model| model.name().as_bytes() == name.as_slice())
+            .ok_or_else(|| invalid_data("Unknown sensor model in binary measurement log"))?;
+
+        Ok(BinaryLogReader { reader, model })
+    }
+
+    /// 記録したセンサの型式を返す．
+    pub fn model(&self) -> SensorModel {
+        self.model
+    }
+
+    /// 次のレコードを読み込む．ファイルの終端に達していれば`None`を返す．
+    fn read_record(&mut self) -> io::Result<Option<BinaryLogRecord>> {
+        let mut record = [0; RECORD_BYTES];
+        let mut filled = 0;
+        while filled < RECORD_BYTES {
+            match self.reader.read(&mut record[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let u64_at = |i: usize| u64::from_le_bytes(record[i..i + 8].try_into().unwrap());
+        let overload_bits = record[25];
+        let mut overload = [false; AXIS_COUNT];
+        let mut raw_counts = [0; AXIS_COUNT];
+        let mut wrench = [0.0; AXIS_COUNT];
+        for i in 0..AXIS_COUNT {
+            overload[i] = overload_bits & (1 << i) != 0;
+            let c = 26 + 2 * i;
+            raw_counts[i] = u16::from_le_bytes([record[c], record[c + 1]]);
+            wrench[i] = f64::from_bits(u64_at(26 + 2 * AXIS_COUNT + 8 * i));
+        }
+
+        Ok(Some(BinaryLogRecord {
+            elapsed: Duration::from_nanos(u64_at(0)),
+            system_time: UNIX_EPOCH + Duration::from_nanos(u64_at(8)),
+            sequence: u64_at(16),
+            record_number: record[24],
+            overload,
+            raw_counts,
+            wrench: Wrench::from_array(wrench),
+        }))
+    }
+}
+
+impl<R: Read> Iterator for BinaryLogReader<R> {
+    type Item = io::Result<BinaryLogRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 受信時刻を`start`から`offset`だけ進めた測定値を作る．
+    fn measurement(start: Instant, offset: Duration, sequence: u64) -> Measurement {
+        Measurement {
+            wrench: Wrench::from_array([1.5, -2.25, 3.0, 0.125, -0.5, 1e-3]),
+            timestamp: start + offset,
+            system_time: UNIX_EPOCH + Duration::from_secs(1_700_000_000) + offset,
+            sequence,
+            record_number: (sequence % 16) as u8,
+            overload: [true, false, false, true, false, true],
+        }
+    }
+
+    /// 測定値を2件記録したバイト列を返す．
+    fn write_log() -> Vec<u8> {
+        let start = Instant::now();
+        let mut writer = BinaryLogWriter::new(Vec::new(), SensorModel::Wdf6m200_3).unwrap();
+        writer
+            .log(&measurement(start, Duration::ZERO, 41), [1, 2, 3, 4, 5, 6])
+            .unwrap();
+        writer
+            .log(
+                &measurement(start, Duration::from_millis(1), 42),
+                [0x3FFF, 0, 0x2000, 0x1234, 0xABCD, 7],
+            )
+            .unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    /// ヘッダのバイト数．
+    fn header_bytes() -> usize {
+        MAGIC.len() + 2 + 2 + 1 + SensorModel::Wdf6m200_3.name().len()
+    }
+
+    fn assert_invalid_data<T: std::fmt::Debug>(result: io::Result<T>) {
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn records_round_trip_through_memory() {
+        let bytes = write_log();
+        assert_eq!(bytes.len(), header_bytes() + 2 * RECORD_BYTES);
+
+        let reader = BinaryLogReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.model(), SensorModel::Wdf6m200_3);
+        let records = reader.collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 2);
+
+        let second = records[1];
+        assert_eq!(second.elapsed, Duration::from_millis(1));
+        assert_eq!(
+            second.system_time,
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000) + Duration::from_millis(1)
+        );
+        assert_eq!(second.sequence, 42);
+        assert_eq!(second.record_number, 10);
+        assert_eq!(second.overload, [true, false, false, true, false, true]);
+        assert_eq!(second.raw_counts, [0x3FFF, 0, 0x2000, 0x1234, 0xABCD, 7]);
+        assert_eq!(
+            second.wrench.to_array(),
+            [1.5, -2.25, 3.0, 0.125, -0.5, 1e-3]
+        );
+        assert_eq!(records[0].elapsed, Duration::ZERO);
+        assert_eq!(records[0].raw_counts, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn record_layout_matches_documented_offsets() {
+        let bytes = write_log();
+        let header = &bytes[..header_bytes()];
+        assert_eq!(&header[..8], b"DYNPKLOG");
+        assert_eq!(&header[8..10], &FORMAT_VERSION.to_le_bytes());
+        assert_eq!(&header[10..12], &(RECORD_BYTES as u16).to_le_bytes());
+        assert_eq!(header[12] as usize, SensorModel::Wdf6m200_3.name().len());
+
+        let record = &bytes[header_bytes() + RECORD_BYTES..];
+        assert_eq!(record.len(), RECORD_BYTES);
+        assert_eq!(&record[0..8], &1_000_000u64.to_le_bytes());
+        assert_eq!(&record[16..24], &42u64.to_le_bytes());
+        assert_eq!(record[24], 10);
+        // fx, tx, tzの過負荷は，それぞれ0, 3, 5ビット目に対応する
+        assert_eq!(record[25], 0b10_1001);
+        assert_eq!(&record[26..28], &0x3FFFu16.to_le_bytes());
+        assert_eq!(&record[34..36], &0xABCDu16.to_le_bytes());
+        assert_eq!(&record[38..46], &1.5f64.to_le_bytes());
+        assert_eq!(&record[RECORD_BYTES - 8..], &1e-3f64.to_le_bytes());
+    }
+
+    #[test]
+    fn truncated_record_is_rejected() {
+        let bytes = write_log();
+        let truncated = &bytes[..bytes.len() - 1];
+        let mut reader = BinaryLogReader::new(truncated).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn log_with_wrong_magic_is_rejected() {
+        let mut bytes = write_log();
+        bytes[0] = b'X';
+        assert_invalid_data(BinaryLogReader::new(bytes.as_slice()).map(|r| r.model()));
+    }
+
+    #[test]
+    fn log_with_unknown_model_is_rejected() {
+        let mut bytes = write_log();
+        bytes[MAGIC.len() + 5] = b'X';
+        assert_invalid_data(BinaryLogReader::new(bytes.as_slice()).map(|r| r.model()));
+    }
+}
@@ -0,0 +1,105 @@
+//! レンチの時系列を保持するためのバッファ．
+
+use crate::Wrench;
+
+/// 直近`N`個のレンチを保持するリングバッファ．
+/// 容量を超えてレンチを追加すると，最も古いレンチから順に捨てられる．
+#[derive(Debug, Clone)]
+pub struct WrenchRingBuffer<const N: usize> {
+    /// レンチの格納領域．
+    buffer: [Wrench; N],
+    /// 最も古いレンチが格納されている位置．
+    start: usize,
+    /// 現在格納されているレンチの個数．
+    len: usize,
+}
+
+impl<const N: usize> WrenchRingBuffer<N> {
+    /// 空のバッファを作る．
+    /// # Panics
+    /// `N`が0の場合．
+    pub fn new() -> WrenchRingBuffer<N> {
+        assert!(N > 0);
+
+        WrenchRingBuffer {
+            buffer: [Wrench::zeroed(); N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// バッファに格納できるレンチの最大個数を返す．
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// 現在格納されているレンチの個数を返す．
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// バッファが空であれば`true`を返す．
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// バッファが容量いっぱいまで埋まっていれば`true`を返す．
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// バッファにレンチを追加する．
+    ///
+    /// # Returns
+    /// バッファが既に埋まっていた場合，押し出された最も古いレンチ`w`を`Some(w)`として返す．
+    /// そうでなければ`None`を返す．
+    pub fn push(&mut self, wrench: Wrench) -> Option<Wrench> {
+        if self.is_full() {
+            let evicted = self.buffer[self.start];
+            self.buffer[self.start] = wrench;
+            self.start = (self.start + 1) % N;
+            Some(evicted)
+        } else {
+            self.buffer[(self.start + self.len) % N] = wrench;
+            self.len += 1;
+            None
+        }
+    }
+
+    /// 古い方から数えて`index`番目のレンチを返す．
+    /// `index`が格納数以上の場合は`None`を返す．
+    pub fn get(&self, index: usize) -> Option<&Wrench> {
+        if index < self.len {
+            Some(&self.buffer[(self.start + index) % N])
+        } else {
+            None
+        }
+    }
+
+    /// 最も古いレンチを返す．
+    pub fn oldest(&self) -> Option<&Wrench> {
+        self.get(0)
+    }
+
+    /// 最も新しいレンチを返す．
+    pub fn latest(&self) -> Option<&Wrench> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// 格納されているレンチを古いものから順に返すイテレータを返す．
+    pub fn iter(&self) -> impl Iterator<Item = &Wrench> + '_ {
+        (0..self.len).map(move |i| &self.buffer[(self.start + i) % N])
+    }
+
+    /// 格納されているレンチをすべて捨てる．
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for WrenchRingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,120 @@
+//! センサとの通信設定を細かく指定するためのビルダ．
+
+use crate::{
+    find_device_port_matching, DynPick, SensorError, SensorSpec, Wdf6m200Spec, BAUD_RATE,
+    DEFAULT_VID_PID,
+};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 通信設定を指定して`DynPick`との通信を確立するためのビルダ．
+/// 指定しなかった設定には，`Wdf6m200::open`と同じ値が用いられる．
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use wacohtech_force_torque_sensor::Wdf6m200;
+///
+/// let sensor = Wdf6m200::builder()
+///     .port("/dev/ttyUSB0")
+///     .baud_rate(921600)
+///     .read_timeout(Duration::from_millis(100))
+///     .open()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynPickBuilder<S: SensorSpec> {
+    /// 接続するシリアルポートへのパス．`None`の場合はデバイスを探索する．
+    port: Option<PathBuf>,
+    /// シリアル通信のボーレート．
+    baud_rate: u32,
+    /// 読み取り操作のタイムアウト．
+    read_timeout: Duration,
+    /// 書き込み操作のタイムアウト．`None`の場合は読み取り操作と同じタイムアウトを用いる．
+    write_timeout: Option<Duration>,
+    /// デバイスの探索に用いるベンダIDとプロダクトIDの組の一覧．
+    vid_pids: Vec<(u16, u16)>,
+    /// 接続するセンサの仕様．
+    spec: PhantomData<fn() -> S>,
+}
+
+/// `Wdf6m200`との通信を確立するためのビルダ．
+pub type Wdf6m200Builder = DynPickBuilder<Wdf6m200Spec>;
+
+impl<S: SensorSpec> DynPickBuilder<S> {
+    /// 既定の通信設定を持つビルダを作る．
+    pub fn new() -> DynPickBuilder<S> {
+        DynPickBuilder {
+            port: None,
+            baud_rate: BAUD_RATE,
+            read_timeout: Duration::from_millis(100),
+            write_timeout: None,
+            vid_pids: vec![DEFAULT_VID_PID],
+            spec: PhantomData,
+        }
+    }
+
+    /// 接続するシリアルポートを指定する．
+    /// 指定した場合，デバイスの探索は行わない．
+    pub fn port<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.port = Some(path.into());
+        self
+    }
+
+    /// シリアル通信のボーレートを指定する．
+    pub fn baud_rate(mut self, baud_rate: u32) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// 読み取り操作のタイムアウトを指定する．
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// 書き込み操作のタイムアウトを指定する．
+    /// 指定しない場合は，読み取り操作と同じタイムアウトを用いる．
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// デバイスの探索に用いるベンダIDとプロダクトIDを指定する．
+    /// `port`を指定した場合は用いられない．
+    pub fn vid_pid(mut self, vendor_id: u16, product_id: u16) -> Self {
+        self.vid_pids = vec![(vendor_id, product_id)];
+        self
+    }
+
+    /// デバイスの探索に用いるベンダIDとプロダクトIDの組を複数指定する．
+    /// いずれかの組に一致するデバイスをセンサとみなす．
+    /// `port`を指定した場合は用いられない．
+    pub fn vid_pids<I>(mut self, vid_pids: I) -> Self
+    where
+        I: IntoIterator<Item = (u16, u16)>,
+    {
+        self.vid_pids = vid_pids.into_iter().collect();
+        self
+    }
+
+    /// 指定した設定でセンサとの通信を確立する．
+    ///
+    /// # Returns
+    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub fn open(self) -> Result<DynPick<S>, SensorError> {
+        let path = match self.port {
+            Some(path) => path,
+            None => find_device_port_matching(&self.vid_pids, |_| true)?,
+        };
+
+        DynPick::connect(&path, self.baud_rate, self.read_timeout, self.write_timeout)
+    }
+}
+
+impl<S: SensorSpec> Default for DynPickBuilder<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
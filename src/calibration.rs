@@ -0,0 +1,151 @@
+//! センサのゼロ点を推定するためのユーティリティ．
+
+use crate::{DynPick, SensorError, SensorSpec, Wrench, AXIS_COUNT};
+
+/// 無負荷時の測定値を与えるたびに，指数移動平均によってゼロ点の推定値を更新する．
+///
+/// `Wdf6m200::calibrate`と異なり測定ループを止める必要がないため，
+/// ロボットの待機姿勢など，負荷がかかっていないことがわかっている期間に断続的にゼロ点を補正し続けられる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchEwmaCalibrator {
+    /// 新しい測定値に対する重み．
+    alpha: f64,
+    /// ゼロ点の推定値．まだ測定値が与えられていない場合は`None`．
+    offset: Option<Wrench>,
+}
+
+impl WrenchEwmaCalibrator {
+    /// ゼロ点の推定値を持たない状態で作る．
+    /// # Params
+    /// 1. `alpha`: 新しい測定値に対する重み．大きいほど新しい測定値にすばやく追従する．
+    ///
+    /// # Panics
+    /// `alpha`が`(0, 1]`の範囲にない場合．
+    pub fn new(alpha: f64) -> WrenchEwmaCalibrator {
+        assert!(alpha > 0.0 && alpha <= 1.0);
+
+        WrenchEwmaCalibrator {
+            alpha,
+            offset: None,
+        }
+    }
+
+    /// 負荷がかかっていないときのセンサの生の出力値を与えて，ゼロ点の推定値を更新する．
+    /// 最初に与えた測定値は，そのままゼロ点の推定値となる．
+    pub fn feed_zero_measurement(&mut self, raw: Wrench) {
+        let offset = match self.offset {
+            Some(offset) => {
+                let current = offset.to_array();
+                let raw = raw.to_array();
+                let mut updated = [0.0; AXIS_COUNT];
+                for (i, e) in updated.iter_mut().enumerate() {
+                    *e = current[i] + self.alpha * (raw[i] - current[i]);
+                }
+                Wrench::from_array(updated)
+            }
+            None => raw,
+        };
+        self.offset = Some(offset);
+    }
+
+    /// 現在のゼロ点の推定値を返す．
+    /// まだ測定値が与えられていない場合は，力とトルクが0であるレンチを返す．
+    pub fn current_offset(&self) -> Wrench {
+        self.offset.unwrap_or_else(Wrench::zeroed)
+    }
+
+    /// 現在のゼロ点の推定値を，センサの補正値として設定する．
+    pub fn apply_to_sensor<S: SensorSpec>(&self, sensor: &mut DynPick<S>) {
+        sensor.set_offset(self.current_offset());
+    }
+
+    /// ゼロ点の推定値を捨てる．
+    pub fn reset(&mut self) {
+        self.offset = None;
+    }
+}
+
+/// `calibrate`によるキャリブレーションの結果．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationReport {
+    /// 受信に成功した測定値の個数．
+    pub sample_count: usize,
+    /// 受信に失敗した測定値の個数．
+    pub failed_count: usize,
+    /// 受信した生の出力値の各軸の平均．これが新たなゼロ点となる．
+    pub mean: Wrench,
+    /// 受信した生の出力値の各軸の標準偏差を`[fx, fy, fz, tx, ty, tz]`の順に並べたもの．
+    pub std_dev: [f64; AXIS_COUNT],
+}
+
+/// キャリブレーション中に受信した生の出力値を，保持せずに逐次集計する．
+/// 平均と分散はWelfordのアルゴリズムで計算するため，試行回数によらずメモリ使用量は一定である．
+#[derive(Debug, Clone)]
+pub(crate) struct CalibrationAccumulator {
+    /// 受信に成功した測定値の個数．
+    sample_count: usize,
+    /// 受信に失敗した測定値の個数．
+    failed_count: usize,
+    /// 各軸の平均．
+    mean: [f64; AXIS_COUNT],
+    /// 各軸の平均からの偏差の2乗和．
+    m2: [f64; AXIS_COUNT],
+}
+
+impl CalibrationAccumulator {
+    /// 測定値を1つも持たない状態で作る．
+    pub(crate) fn new() -> CalibrationAccumulator {
+        CalibrationAccumulator {
+            sample_count: 0,
+            failed_count: 0,
+            mean: [0.0; AXIS_COUNT],
+            m2: [0.0; AXIS_COUNT],
+        }
+    }
+
+    /// 受信に成功した生の出力値を集計に加える．
+    pub(crate) fn push(&mut self, raw: Wrench) {
+        self.sample_count += 1;
+        let n = self.sample_count as f64;
+        let stats = self.mean.iter_mut().zip(self.m2.iter_mut());
+        for ((mean, m2), x) in stats.zip(raw.to_array().iter()) {
+            let delta = x - *mean;
+            *mean += delta / n;
+            *m2 += delta * (x - *mean);
+        }
+    }
+
+    /// 受信に失敗したことを記録する．
+    pub(crate) fn push_failure(&mut self) {
+        self.failed_count += 1;
+    }
+
+    /// 集計結果からキャリブレーションの結果を作る．
+    ///
+    /// # Returns
+    /// 受信に成功した測定値が試行回数の半分に満たない場合は`SensorError::InsufficientSamples`を返す．
+    pub(crate) fn finish(&self) -> Result<CalibrationReport, SensorError> {
+        let required = required_sample_count(self.sample_count + self.failed_count);
+        if self.sample_count < required {
+            return Err(SensorError::InsufficientSamples(
+                required,
+                self.sample_count,
+            ));
+        }
+
+        let n = self.sample_count as f64;
+        Ok(CalibrationReport {
+            sample_count: self.sample_count,
+            failed_count: self.failed_count,
+            mean: Wrench::from_array(self.mean),
+            // 丸め誤差でわずかに負になることがあるので0で打ち切る
+            std_dev: self.m2.map(|m2| (m2 / n).max(0.0).sqrt()),
+        })
+    }
+}
+
+/// `measurement_times`回の受信を試みるキャリブレーションで，最低限必要となる受信成功回数を返す．
+/// 試行回数の半分(端数は切り上げ)とする．
+pub(crate) fn required_sample_count(measurement_times: usize) -> usize {
+    measurement_times.div_ceil(2)
+}
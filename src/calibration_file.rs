@@ -0,0 +1,249 @@
+//! キャリブレーション結果をファイルに保存し，再利用するための機能．
+
+use crate::{Wrench, AXIS_COUNT};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// ファイルに保存したキャリブレーション結果．
+/// 前回求めたゼロ点を起動時に読み込むことで，キャリブレーションをやり直さずに測定を始められる．
+///
+/// ファイルは次のようなTOML形式で記述する．`serial_number`は省略できる．
+/// ```toml
+/// timestamp = 1700000000.25
+/// sample_count = 100
+/// serial_number = "ABC1234"
+/// offset = [0.1, -0.2, 0.3, 0.001, -0.002, 0.003]
+/// ```
+/// `timestamp`はUNIX時刻(秒)，`offset`は`[fx, fy, fz, tx, ty, tz]`の順に並べたゼロ点(SI単位)である．
+/// `serial_number`に含まれる`\`，`"`，改行は，TOMLと同様に`\\`，`\"`，`\n`，`\r`とエスケープする．
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedCalibration {
+    /// ゼロ点．
+    pub offset: Wrench,
+    /// キャリブレーションを行った時刻．
+    pub timestamp: SystemTime,
+    /// キャリブレーションに用いた測定値の個数．手動で設定したゼロ点の場合は0．
+    pub sample_count: usize,
+    /// キャリブレーションを行ったセンサのシリアル番号．不明な場合は`None`．
+    pub serial_number: Option<String>,
+}
+
+impl SavedCalibration {
+    /// キャリブレーション結果をファイルに書き込む．既にファイルが存在する場合は上書きする．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.to_writer(&mut writer)?;
+        writer.flush()
+    }
+
+    /// `save`と同じ形式でキャリブレーション結果を書き込む．
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        writeln!(writer, "timestamp = {}", timestamp)?;
+        writeln!(writer, "sample_count = {}", self.sample_count)?;
+        if let Some(serial_number) = &self.serial_number {
+            writeln!(writer, "serial_number = \"{}\"", escape(serial_number))?;
+        }
+        let offset = self
+            .offset
+            .to_array()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        writeln!(writer, "offset = [{}]", offset.join(", "))
+    }
+
+    /// キャリブレーション結果をファイルから読み込む．
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<SavedCalibration> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// `save`と同じ形式のキャリブレーション結果を読み込む．
+    /// 空行と`#`で始まる行，未知のキーは無視する．
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<SavedCalibration> {
+        let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut timestamp = None;
+        let mut sample_count = None;
+        let mut serial_number = None;
+        let mut offset = None;
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || invalid_data(format!("Invalid entry at line {}", i + 1));
+            let (key, value) = line.split_once('=').ok_or_else(invalid_line)?;
+            let value = value.trim();
+            match key.trim() {
+                "timestamp" => {
+                    let secs = value.parse::<f64>().map_err(|_| invalid_line())?;
+                    let since_epoch =
+                        Duration::try_from_secs_f64(secs).map_err(|_| invalid_line())?;
+                    timestamp = Some(UNIX_EPOCH + since_epoch);
+                }
+                "sample_count" => {
+                    sample_count = Some(value.parse::<usize>().map_err(|_| invalid_line())?);
+                }
+                "serial_number" => {
+                    let quoted = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.strip_suffix('"'))
+                        .ok_or_else(invalid_line)?;
+                    serial_number = Some(unescape(quoted).ok_or_else(invalid_line)?);
+                }
+                "offset" => {
+                    let elements = value
+                        .strip_prefix('[')
+                        .and_then(|v| v.strip_suffix(']'))
+                        .ok_or_else(invalid_line)?
+                        .split(',')
+                        .map(|e| e.trim().parse::<f64>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| invalid_line())?;
+                    if elements.len() != AXIS_COUNT || elements.iter().any(|e| !e.is_finite()) {
+                        return Err(invalid_line());
+                    }
+                    let mut array = [0.0; AXIS_COUNT];
+                    array.copy_from_slice(&elements);
+                    offset = Some(Wrench::from_array(array));
+                }
+                _ => {}
+            }
+        }
+
+        let missing = |key: &str| invalid_data(format!("Missing key '{}'", key));
+        Ok(SavedCalibration {
+            offset: offset.ok_or_else(|| missing("offset"))?,
+            timestamp: timestamp.ok_or_else(|| missing("timestamp"))?,
+            sample_count: sample_count.ok_or_else(|| missing("sample_count"))?,
+            serial_number,
+        })
+    }
+}
+
+/// 文字列を引用符の内側に書けるように，`\\`，`\"`，`\n`，`\r`でエスケープする．
+/// 改行を含むシリアル番号も1行に収まるため，読み込み時に次の行と混ざらない．
+fn escape(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 引用符の内側の，`escape`でエスケープされた文字列を元に戻す．
+/// 不正なエスケープや，エスケープされていない引用符を含む場合は`None`を返す．
+fn unescape(quoted: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                e @ ('\\' | '"') => unescaped.push(e),
+                'n' => unescaped.push('\n'),
+                'r' => unescaped.push('\r'),
+                _ => return None,
+            },
+            '"' => return None,
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(serial_number: Option<&str>) -> SavedCalibration {
+        SavedCalibration {
+            offset: Wrench::from_array([0.1, -0.2, 0.3, 0.001, -0.002, 0.003]),
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_250),
+            sample_count: 100,
+            serial_number: serial_number.map(str::to_string),
+        }
+    }
+
+    fn round_trip(calibration: &SavedCalibration) -> SavedCalibration {
+        let mut bytes = Vec::new();
+        calibration.to_writer(&mut bytes).unwrap();
+        SavedCalibration::from_reader(bytes.as_slice()).unwrap()
+    }
+
+    fn assert_invalid_data(text: &str) {
+        let error = SavedCalibration::from_reader(text.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn calibration_round_trips_through_writer_and_reader() {
+        let saved = calibration(Some("ABC1234"));
+        assert_eq!(round_trip(&saved), saved);
+
+        let saved = calibration(None);
+        assert_eq!(round_trip(&saved), saved);
+    }
+
+    #[test]
+    fn serial_number_with_special_characters_round_trips() {
+        let saved = calibration(Some("A\\B \"C\"\nD\r"));
+        let mut bytes = Vec::new();
+        saved.to_writer(&mut bytes).unwrap();
+
+        // 改行を含むシリアル番号も1行に収まる
+        let text = String::from_utf8(bytes).unwrap();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.contains(r#"serial_number = "A\\B \"C\"\nD\r""#));
+        assert_eq!(round_trip(&saved), saved);
+    }
+
+    #[test]
+    fn invalid_escape_is_rejected() {
+        assert_eq!(unescape(r#"A\\B\"C\nD"#).as_deref(), Some("A\\B\"C\nD"));
+        assert_eq!(unescape(r"A\tB"), None);
+        assert_eq!(unescape(r"A\"), None);
+        assert_eq!(unescape(r#"A"B"#), None);
+
+        assert_invalid_data(
+            "timestamp = 0\nsample_count = 1\nserial_number = \"A\\tB\"\noffset = [0, 0, 0, 0, 0, 0]\n",
+        );
+    }
+
+    #[test]
+    fn missing_keys_are_rejected() {
+        let complete = [
+            "timestamp = 1700000000.25",
+            "sample_count = 100",
+            "offset = [0.1, -0.2, 0.3, 0.001, -0.002, 0.003]",
+        ];
+        for skipped in 0..complete.len() {
+            let text = complete
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != skipped)
+                .map(|(_, line)| format!("{}\n", line))
+                .collect::<String>();
+            assert_invalid_data(&text);
+        }
+
+        let text = format!("# comment\n\n{}\nunknown = 1\n", complete.join("\n"));
+        assert!(SavedCalibration::from_reader(text.as_bytes()).is_ok());
+    }
+}
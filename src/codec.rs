@@ -0,0 +1,100 @@
+//! `tokio_util::codec`によるフレームの符号化と復号．
+
+use crate::protocol::{self, Command, RawReading, NEWLINE, RESPONSE_BYTES};
+use crate::SensorError;
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// DynPickシリーズのセンサとの通信で用いるフレームの符号化と復号を行うコーデック．
+/// `tokio_util::codec::Framed`と組み合わせることで，
+/// シリアルポートに限らず任意の`AsyncRead + AsyncWrite`を通じてセンサと通信できる．
+///
+/// 解釈できないフレームを受信した場合は，そのフレームを読み捨てて次のフレームから復号を続ける．
+/// 改行コードを含まないまま1フレーム分を超えて届いたバイト列も，フレームの一部ではありえない先頭部分を読み捨てる．
+/// 復号できるのは出力値を表すフレームのみであり，
+/// 感度や機器情報の要求に対する応答は扱えないことに注意すること．
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DynPickCodec {
+    /// 解釈できずに読み捨てたバイト数．
+    discarded_bytes: u64,
+}
+
+impl DynPickCodec {
+    /// コーデックを作る．
+    pub fn new() -> DynPickCodec {
+        DynPickCodec { discarded_bytes: 0 }
+    }
+
+    /// これまでに解釈できずに読み捨てたバイト数を返す．
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded_bytes
+    }
+}
+
+impl Decoder for DynPickCodec {
+    type Item = RawReading;
+    type Error = SensorError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<RawReading>, SensorError> {
+        loop {
+            // 改行コードが届くまでは1フレーム分のデータが揃っていない
+            let end = match src.windows(NEWLINE.len()).position(|w| w == NEWLINE) {
+                Some(position) => position + NEWLINE.len(),
+                None => {
+                    // 改行コードを含まない1フレームの途中までは，高々RESPONSE_BYTES - 1バイトである．
+                    // それを超える先頭部分は雑音とみなして捨て，バッファが際限なく伸びるのを防ぐ
+                    let excess = src.len().saturating_sub(RESPONSE_BYTES - 1);
+                    src.advance(excess);
+                    self.discarded_bytes += excess as u64;
+                    return Ok(None);
+                }
+            };
+
+            // 改行コードまでを1フレームとして切り出すため，解釈に失敗しても次のフレームの区切りはずれない
+            let frame = src.split_to(end);
+            match protocol::parse_frame(&frame) {
+                Ok(reading) => return Ok(Some(reading)),
+                Err(_) => self.discarded_bytes += frame.len() as u64,
+            }
+        }
+    }
+}
+
+impl Encoder<Command> for DynPickCodec {
+    type Error = SensorError;
+
+    fn encode(&mut self, command: Command, dst: &mut BytesMut) -> Result<(), SensorError> {
+        dst.put_slice(command.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// すべての軸のデジタル出力値が中央値である，レコード番号0のフレーム．
+    const CENTERED_FRAME: &[u8; RESPONSE_BYTES] = b"0200020002000200020002000\r\n";
+
+    #[test]
+    fn bytes_without_newline_are_capped_and_counted() {
+        let mut codec = DynPickCodec::new();
+        let mut src = BytesMut::new();
+        for _ in 0..100 {
+            src.put_slice(&[b'x'; RESPONSE_BYTES]);
+            assert_eq!(codec.decode(&mut src).unwrap(), None);
+            assert!(src.len() < RESPONSE_BYTES);
+        }
+        assert_eq!(
+            codec.discarded_bytes(),
+            (100 * RESPONSE_BYTES - (RESPONSE_BYTES - 1)) as u64
+        );
+
+        // 雑音の残りは次の改行コードで読み捨てられ，その後のフレームは復号できる
+        src.put_slice(CENTERED_FRAME);
+        src.put_slice(CENTERED_FRAME);
+        let reading = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(reading.counts, [0x2000; 6]);
+        assert!(src.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! 力覚センサの測定値を利用したロボットの制御器．
+
+use crate::{Wrench, AXIS_COUNT};
+
+/// アドミタンス制御によって，目標のレンチと測定したレンチの差から速度指令を生成する．
+///
+/// 各軸について，仮想的な慣性`M`と粘性`D`を持つ系の運動方程式
+/// `M * dv/dt + D * v = F_desired - F_measured`
+/// を前進オイラー法で積分した速度を出力する．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchAdmittanceController {
+    /// 各軸の仮想慣性．
+    inertia: [f64; AXIS_COUNT],
+    /// 各軸の仮想粘性．
+    damping: [f64; AXIS_COUNT],
+    /// 各軸の現在の速度指令．
+    velocity: [f64; AXIS_COUNT],
+    /// 各軸の速度指令の大きさの上限．
+    velocity_limits: Option<[f64; AXIS_COUNT]>,
+}
+
+impl WrenchAdmittanceController {
+    /// 速度指令が0の状態で制御器を作る．
+    /// # Params
+    /// 1. `inertia`: 各軸の仮想慣性．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    /// 1. `damping`: 各軸の仮想粘性．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// `inertia`のいずれかの要素が正でない場合．
+    pub fn new(
+        inertia: [f64; AXIS_COUNT],
+        damping: [f64; AXIS_COUNT],
+    ) -> WrenchAdmittanceController {
+        assert!(inertia.iter().all(|&m| m > 0.0));
+
+        WrenchAdmittanceController {
+            inertia,
+            damping,
+            velocity: [0.0; AXIS_COUNT],
+            velocity_limits: None,
+        }
+    }
+
+    /// 目標のレンチと測定したレンチから，次の速度指令を計算して返す．
+    /// # Params
+    /// 1. `desired`: 目標のレンチ．
+    /// 1. `measured`: センサで測定したレンチ．
+    /// 1. `dt`: 前回の呼び出しからの経過時間[s]．
+    ///
+    /// # Returns
+    /// 各軸の速度指令を`[vx, vy, vz, ωx, ωy, ωz]`の順に返す．
+    pub fn compute_velocity(
+        &mut self,
+        desired: Wrench,
+        measured: Wrench,
+        dt: f64,
+    ) -> [f64; AXIS_COUNT] {
+        let error = (desired - measured).to_array();
+
+        for (i, v) in self.velocity.iter_mut().enumerate() {
+            let acceleration = (error[i] - self.damping[i] * *v) / self.inertia[i];
+            *v += acceleration * dt;
+            if let Some(limits) = &self.velocity_limits {
+                *v = v.max(-limits[i]).min(limits[i]);
+            }
+        }
+
+        self.velocity
+    }
+
+    /// 現在の速度指令を返す．
+    pub fn velocity(&self) -> [f64; AXIS_COUNT] {
+        self.velocity
+    }
+
+    /// 速度指令を0に戻す．
+    pub fn reset_velocity(&mut self) {
+        self.velocity = [0.0; AXIS_COUNT];
+    }
+
+    /// 各軸の速度指令の大きさの上限を設定する．
+    /// # Panics
+    /// `limits`のいずれかの要素が負の場合．
+    pub fn set_velocity_limits(&mut self, limits: [f64; AXIS_COUNT]) {
+        assert!(limits.iter().all(|&l| l >= 0.0));
+
+        self.velocity_limits = Some(limits);
+    }
+
+    /// 速度指令の大きさの上限を解除する．
+    pub fn clear_velocity_limits(&mut self) {
+        self.velocity_limits = None;
+    }
+}
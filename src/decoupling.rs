@@ -0,0 +1,91 @@
+//! 軸間の干渉を補正するための校正行列．
+
+use crate::{Wrench, AXIS_COUNT};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// デジタル出力値をレンチに換算するための6×6行列．
+/// 実際のセンサには軸間の干渉があり，各軸独立の感度では表しきれない．
+/// メーカーや独自の校正装置から得た行列を用いることで，この干渉を補正できる．
+///
+/// 行列は`[fx, fy, fz, tx, ty, tz]`の各成分(SI単位)を行，
+/// 同じ順に並べた各軸のデジタル出力値を列とし，
+/// `wrench[i] = Σ_j matrix[i][j] * counts[j]`として換算する．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationMatrix {
+    /// 校正行列．
+    matrix: [[f64; AXIS_COUNT]; AXIS_COUNT],
+}
+
+impl CalibrationMatrix {
+    /// 与えた行列から作る．
+    /// # Panics
+    /// 行列のいずれかの要素が有限でない場合．
+    pub fn new(matrix: [[f64; AXIS_COUNT]; AXIS_COUNT]) -> CalibrationMatrix {
+        assert!(matrix.iter().flatten().all(|e| e.is_finite()));
+
+        CalibrationMatrix { matrix }
+    }
+
+    /// 校正ファイルから読み込む．
+    ///
+    /// 校正ファイルは，行列の各行を1行ずつ，6つの要素をカンマまたは空白で区切って記述したものとする．
+    /// 空行と`#`で始まる行は無視する．
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<CalibrationMatrix> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// `from_file`と同じ形式の校正行列を読み込む．
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<CalibrationMatrix> {
+        let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut rows = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let row = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|e| !e.is_empty())
+                .map(|e| e.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid_data(format!("Invalid number at line {}", i + 1)))?;
+            if row.len() != AXIS_COUNT || row.iter().any(|e| !e.is_finite()) {
+                return Err(invalid_data(format!("Invalid row at line {}", i + 1)));
+            }
+            rows.push(row);
+        }
+
+        if rows.len() != AXIS_COUNT {
+            return Err(invalid_data(format!(
+                "A calibration matrix should have {} rows, but actually {} rows",
+                AXIS_COUNT,
+                rows.len()
+            )));
+        }
+
+        let mut matrix = [[0.0; AXIS_COUNT]; AXIS_COUNT];
+        for (dst, src) in matrix.iter_mut().zip(rows.iter()) {
+            dst.copy_from_slice(src);
+        }
+        Ok(CalibrationMatrix { matrix })
+    }
+
+    /// 校正行列を返す．
+    pub fn matrix(&self) -> [[f64; AXIS_COUNT]; AXIS_COUNT] {
+        self.matrix
+    }
+
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べた各軸のデジタル出力値をレンチに換算する．
+    pub fn apply(&self, counts: [f64; AXIS_COUNT]) -> Wrench {
+        let mut wrench = [0.0; AXIS_COUNT];
+        for (w, row) in wrench.iter_mut().zip(self.matrix.iter()) {
+            *w = row.iter().zip(counts.iter()).map(|(m, c)| m * c).sum();
+        }
+        Wrench::from_array(wrench)
+    }
+}
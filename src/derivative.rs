@@ -0,0 +1,83 @@
+//! レンチの時間微分を推定するためのユーティリティ．
+
+use crate::{Measurement, Wrench, AXIS_COUNT};
+use std::time::Instant;
+
+/// 時刻付きのレンチから，力とトルクの時間変化率を推定する．
+/// 衝突の検出や，レンチの微分を必要とする制御器に利用できる．
+///
+/// 差分をそのままとるとノイズが増幅されるため，差分から求めた変化率を指数平滑化して出力する．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchDifferentiator {
+    /// 新しい変化率に対する重み．
+    alpha: f64,
+    /// 直前に与えたレンチとその時刻．
+    previous: Option<(Instant, Wrench)>,
+    /// 平滑化した変化率．まだ求められていなければ`None`．
+    rate: Option<[f64; AXIS_COUNT]>,
+}
+
+impl WrenchDifferentiator {
+    /// # Params
+    /// 1. `alpha`: 新しい変化率に対する重み．1とすると平滑化を行わず，小さいほどノイズを抑える代わりに応答が遅れる．
+    ///
+    /// # Panics
+    /// `alpha`が`(0, 1]`の範囲にない場合．
+    pub fn new(alpha: f64) -> WrenchDifferentiator {
+        assert!(alpha > 0.0 && alpha <= 1.0);
+
+        WrenchDifferentiator {
+            alpha,
+            previous: None,
+            rate: None,
+        }
+    }
+
+    /// 新しいレンチとその取得時刻を与えて，変化率の推定値を更新する．
+    ///
+    /// # Returns
+    /// 更新後の変化率[N/s, N·m/s]を`[fx, fy, fz, tx, ty, tz]`の順に並べたもの`r`を`Some(r)`として返す．
+    /// 最初のレンチを与えた場合など，まだ変化率を求められない場合は`None`を返す．
+    /// 直前のレンチと時刻が同じか古いレンチは，時刻の記録だけを更新して変化率の計算には用いない．
+    pub fn update(&mut self, wrench: Wrench, timestamp: Instant) -> Option<[f64; AXIS_COUNT]> {
+        let previous = self.previous.replace((timestamp, wrench));
+
+        if let Some((previous_time, previous_wrench)) = previous {
+            let dt = timestamp
+                .saturating_duration_since(previous_time)
+                .as_secs_f64();
+            if dt > 0.0 {
+                let difference = (wrench - previous_wrench).to_array();
+                let rate = self.rate.get_or_insert_with(|| {
+                    let mut rate = [0.0; AXIS_COUNT];
+                    for (r, d) in rate.iter_mut().zip(difference.iter()) {
+                        *r = d / dt;
+                    }
+                    rate
+                });
+                for (r, d) in rate.iter_mut().zip(difference.iter()) {
+                    *r += self.alpha * (d / dt - *r);
+                }
+            }
+        }
+        self.rate
+    }
+
+    /// 時刻付きの測定値を与えて，変化率の推定値を更新する．
+    /// 戻り値は`update`と同じ．
+    pub fn update_measurement(&mut self, measurement: &Measurement) -> Option<[f64; AXIS_COUNT]> {
+        self.update(measurement.wrench, measurement.timestamp)
+    }
+
+    /// 現在の変化率の推定値[N/s, N·m/s]を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// まだ変化率を求められていなければ`None`を返す．
+    pub fn rate(&self) -> Option<[f64; AXIS_COUNT]> {
+        self.rate
+    }
+
+    /// これまでに与えたレンチと変化率の推定値を捨てる．
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.rate = None;
+    }
+}
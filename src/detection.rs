@@ -0,0 +1,641 @@
+//! レンチの変化からイベントを検出するためのユーティリティ．
+
+use crate::{Axis, Measurement, Newton, NewtonMeter, Wrench, WrenchBandPassFilter, AXIS_COUNT};
+use std::time::{Duration, Instant};
+
+/// 各軸について，レンチの大きさがしきい値を超えているかどうかをヒステリシス付きで判定する．
+///
+/// 単一のしきい値で接触判定を行うと，信号がしきい値付近にあるときに判定が激しく切り替わってしまう．
+/// この判定器では，非アクティブな軸は大きさが上側しきい値を超えたときにアクティブとなり，
+/// アクティブな軸は大きさが下側しきい値を下回ったときに非アクティブとなる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchHysteresis {
+    /// 各軸の下側しきい値．
+    low: [f64; AXIS_COUNT],
+    /// 各軸の上側しきい値．
+    high: [f64; AXIS_COUNT],
+    /// 各軸が現在アクティブかどうか．
+    active: [bool; AXIS_COUNT],
+}
+
+impl WrenchHysteresis {
+    /// すべての軸が非アクティブな状態で判定器を作る．
+    /// # Params
+    /// 1. `low`: 各軸の下側しきい値．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    /// 1. `high`: 各軸の上側しきい値．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// いずれかの軸について`low`が`high`より大きい場合．
+    pub fn new(low: [f64; AXIS_COUNT], high: [f64; AXIS_COUNT]) -> WrenchHysteresis {
+        assert!(low.iter().zip(high.iter()).all(|(l, h)| l <= h));
+
+        WrenchHysteresis {
+            low,
+            high,
+            active: [false; AXIS_COUNT],
+        }
+    }
+
+    /// 新しいレンチを与えて各軸の状態を更新し，更新後の状態を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// 各軸の大きさは絶対値で評価する．
+    pub fn is_active(&mut self, wrench: Wrench) -> [bool; AXIS_COUNT] {
+        let values = wrench.to_array();
+
+        for (i, active) in self.active.iter_mut().enumerate() {
+            let magnitude = values[i].abs();
+            if *active {
+                if magnitude < self.low[i] {
+                    *active = false;
+                }
+            } else if magnitude > self.high[i] {
+                *active = true;
+            }
+        }
+
+        self.active
+    }
+
+    /// 最後に更新した時点での各軸の状態を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn state(&self) -> [bool; AXIS_COUNT] {
+        self.active
+    }
+
+    /// すべての軸を非アクティブな状態に戻す．
+    pub fn reset(&mut self) {
+        self.active = [false; AXIS_COUNT];
+    }
+}
+
+/// `WrenchMagnitudeAlarm`が発した警報．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlarmEvent {
+    /// 警報の原因となった軸．
+    pub axis: Axis,
+    /// 前回のサンプルにおけるその軸の値(SI単位)．
+    pub previous_value: f64,
+    /// 今回のサンプルにおけるその軸の値(SI単位)．
+    pub current_value: f64,
+}
+
+/// 前回のサンプルからレンチのいずれかの成分が急変したときに警報を発する．
+/// ロボットの関節を傷めかねない急な負荷の変動の検出に利用できる．
+///
+/// 警報はエッジトリガであり，1回の急変につき1度だけ発せられる．
+/// 警報を発した軸は，変化量がしきい値以下に戻るまで再び警報を発しない．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchMagnitudeAlarm {
+    /// 力の各成分の変化量のしきい値．
+    delta_force: Newton<f64>,
+    /// トルクの各成分の変化量のしきい値．
+    delta_torque: NewtonMeter<f64>,
+    /// 前回のサンプル．
+    previous: Option<Wrench>,
+    /// 各軸について，警報を発したまま変化量がしきい値以下に戻っていなければ`true`．
+    triggered: [bool; AXIS_COUNT],
+}
+
+impl WrenchMagnitudeAlarm {
+    /// # Params
+    /// 1. `delta_force`: 力の各成分の，1サンプルあたりの変化量のしきい値．
+    /// 1. `delta_torque`: トルクの各成分の，1サンプルあたりの変化量のしきい値．
+    pub fn new(delta_force: Newton<f64>, delta_torque: NewtonMeter<f64>) -> WrenchMagnitudeAlarm {
+        WrenchMagnitudeAlarm {
+            delta_force,
+            delta_torque,
+            previous: None,
+            triggered: [false; AXIS_COUNT],
+        }
+    }
+
+    /// 新しいサンプルを与えて，急変があったかどうかを調べる．
+    ///
+    /// # Returns
+    /// 新たに警報を発すべき軸についての警報を，`Axis::ALL`の順に並べて返す．
+    /// 複数の軸で同時に急変した場合は，そのすべての軸についての警報を返す．
+    /// 新たに警報を発すべき軸がなければ空の`Vec`を返す．
+    pub fn check(&mut self, current: Wrench) -> Vec<AlarmEvent> {
+        let previous = match self.previous.replace(current) {
+            Some(previous) => previous.to_array(),
+            None => return Vec::new(),
+        };
+        let current = current.to_array();
+
+        let mut events = Vec::new();
+        for (&axis, triggered) in Axis::ALL.iter().zip(self.triggered.iter_mut()) {
+            let i = axis.index();
+            let threshold = if axis.is_force() {
+                self.delta_force.value_unsafe
+            } else {
+                self.delta_torque.value_unsafe
+            };
+
+            let exceeded = (current[i] - previous[i]).abs() > threshold;
+            if exceeded && !*triggered {
+                events.push(AlarmEvent {
+                    axis,
+                    previous_value: previous[i],
+                    current_value: current[i],
+                });
+            }
+            *triggered = exceeded;
+        }
+
+        events
+    }
+
+    /// 前回のサンプルと警報の状態を捨てて，最初の状態に戻す．
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.triggered = [false; AXIS_COUNT];
+    }
+}
+
+/// `WrenchPeakDetector`が記録した，大きさが最大となった瞬間の測定値．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrenchPeak {
+    /// 大きさが最大となった瞬間のレンチ．
+    pub wrench: Wrench,
+    /// `wrench`を取得した時刻．
+    pub timestamp: Instant,
+}
+
+/// 前回のリセット以降に与えたレンチのうち，力とトルクそれぞれの大きさが最大となったものを記録する．
+/// 破壊試験や，挿入作業で加わった最大の力の測定に利用できる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchPeakDetector {
+    /// 力の大きさが最大となった瞬間の測定値．
+    force_peak: Option<WrenchPeak>,
+    /// トルクの大きさが最大となった瞬間の測定値．
+    torque_peak: Option<WrenchPeak>,
+}
+
+impl WrenchPeakDetector {
+    /// 何も記録していない状態で作る．
+    pub fn new() -> WrenchPeakDetector {
+        WrenchPeakDetector {
+            force_peak: None,
+            torque_peak: None,
+        }
+    }
+
+    /// 新しいレンチとその取得時刻を与えて，最大値の記録を更新する．
+    ///
+    /// # Returns
+    /// 力とトルクのいずれかの最大値を更新した場合は`true`を返す．
+    pub fn update(&mut self, wrench: Wrench, timestamp: Instant) -> bool {
+        let peak = WrenchPeak { wrench, timestamp };
+
+        let force_updated = match &self.force_peak {
+            Some(p) => wrench.force_norm() > p.wrench.force_norm(),
+            None => true,
+        };
+        if force_updated {
+            self.force_peak = Some(peak);
+        }
+
+        let torque_updated = match &self.torque_peak {
+            Some(p) => wrench.torque_norm() > p.wrench.torque_norm(),
+            None => true,
+        };
+        if torque_updated {
+            self.torque_peak = Some(peak);
+        }
+
+        force_updated || torque_updated
+    }
+
+    /// 時刻付きの測定値を与えて，最大値の記録を更新する．
+    /// 戻り値は`update`と同じ．
+    pub fn update_measurement(&mut self, measurement: &Measurement) -> bool {
+        self.update(measurement.wrench, measurement.timestamp)
+    }
+
+    /// 力の大きさが最大となった瞬間の測定値を返す．まだレンチを与えていなければ`None`を返す．
+    pub fn force_peak(&self) -> Option<WrenchPeak> {
+        self.force_peak
+    }
+
+    /// トルクの大きさが最大となった瞬間の測定値を返す．まだレンチを与えていなければ`None`を返す．
+    pub fn torque_peak(&self) -> Option<WrenchPeak> {
+        self.torque_peak
+    }
+
+    /// 記録した最大値を捨てる．
+    pub fn reset(&mut self) {
+        self.force_peak = None;
+        self.torque_peak = None;
+    }
+}
+
+impl Default for WrenchPeakDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 接触の検出器が測定値の時系列から検出した事象．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactEvent {
+    /// 対象との接触が始まった．
+    Contact {
+        /// 接触と判定する条件を最初に満たした測定値の取得時刻．
+        timestamp: Instant,
+    },
+    /// 対象との接触が終わった．
+    Release {
+        /// 離脱と判定する条件を最初に満たした測定値の取得時刻．
+        timestamp: Instant,
+    },
+    /// 把持した対象がすべり始めた．
+    Slip {
+        /// すべりと判定した測定値の取得時刻．
+        timestamp: Instant,
+    },
+}
+
+/// 測定値の時系列から`ContactEvent`を検出する検出器に共通の操作．
+/// 検出器の種類を呼び出し側を変えずに差し替えたり，複数の検出器をまとめて扱ったりするために用いる．
+pub trait ContactEventDetector {
+    /// 新しい測定値を与えて，事象が発生したかどうかを調べる．
+    ///
+    /// # Returns
+    /// 新たに事象が発生した場合，その事象`e`を`Some(e)`として返す．
+    fn detect(&mut self, measurement: &Measurement) -> Option<ContactEvent>;
+
+    /// 検出器の内部状態を捨てて，最初の状態に戻す．
+    fn reset(&mut self);
+}
+
+/// 力の大きさから，対象との接触と離脱を検出する．
+///
+/// 非接触の状態では力の大きさが接触しきい値を超えたときに，接触の状態では離脱しきい値を下回ったときに状態が切り替わる．
+/// ノイズによる判定のばたつきを防ぐため，切り替わりの条件が`debounce`の間続いた場合にだけ事象を発する．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchContactDetector {
+    /// 接触と判定する力の大きさのしきい値．
+    engage_force: Newton<f64>,
+    /// 離脱と判定する力の大きさのしきい値．
+    release_force: Newton<f64>,
+    /// 状態を切り替えるまでに，切り替わりの条件が続く必要のある時間．
+    debounce: Duration,
+    /// 現在接触の状態にあるかどうか．
+    in_contact: bool,
+    /// 状態の切り替わりの条件を満たし始めた時刻．条件を満たしていなければ`None`．
+    pending_since: Option<Instant>,
+}
+
+impl WrenchContactDetector {
+    /// 非接触の状態で検出器を作る．
+    /// # Params
+    /// 1. `engage_force`: 力の大きさがこの値を超えたときに接触と判定する．
+    /// 1. `release_force`: 力の大きさがこの値を下回ったときに離脱と判定する．
+    /// 1. `debounce`: 状態を切り替えるまでに，切り替わりの条件が続く必要のある時間．
+    ///
+    /// # Panics
+    /// `release_force`が`engage_force`より大きい場合．
+    pub fn new(
+        engage_force: Newton<f64>,
+        release_force: Newton<f64>,
+        debounce: Duration,
+    ) -> WrenchContactDetector {
+        assert!(release_force <= engage_force);
+
+        WrenchContactDetector {
+            engage_force,
+            release_force,
+            debounce,
+            in_contact: false,
+            pending_since: None,
+        }
+    }
+
+    /// 現在接触の状態にあれば`true`を返す．
+    pub fn in_contact(&self) -> bool {
+        self.in_contact
+    }
+
+    /// 新しいレンチとその取得時刻を与えて，接触と離脱を調べる．
+    ///
+    /// # Returns
+    /// 状態が切り替わった場合，その事象`e`を`Some(e)`として返す．
+    pub fn update(&mut self, wrench: Wrench, timestamp: Instant) -> Option<ContactEvent> {
+        let force = wrench.force_norm();
+        let switching = if self.in_contact {
+            force < self.release_force
+        } else {
+            force > self.engage_force
+        };
+
+        if !switching {
+            self.pending_since = None;
+            return None;
+        }
+
+        let since = *self.pending_since.get_or_insert(timestamp);
+        if timestamp.saturating_duration_since(since) < self.debounce {
+            return None;
+        }
+
+        self.pending_since = None;
+        self.in_contact = !self.in_contact;
+        let event = if self.in_contact {
+            ContactEvent::Contact { timestamp: since }
+        } else {
+            ContactEvent::Release { timestamp: since }
+        };
+        Some(event)
+    }
+
+    /// 非接触の状態に戻す．
+    pub fn reset(&mut self) {
+        self.in_contact = false;
+        self.pending_since = None;
+    }
+}
+
+impl ContactEventDetector for WrenchContactDetector {
+    fn detect(&mut self, measurement: &Measurement) -> Option<ContactEvent> {
+        self.update(measurement.wrench, measurement.timestamp)
+    }
+
+    fn reset(&mut self) {
+        WrenchContactDetector::reset(self)
+    }
+}
+
+/// 接線方向の力の高周波成分から，把持した対象のすべり始めを検出する．
+///
+/// すべりが始まる直前には，接触面の微小な固着と滑りの繰り返しによって接線方向の力に振動的な成分が現れる．
+/// センサ座標系のz軸を接触面の法線とみなし，x軸とy軸の力を帯域通過フィルタに通した成分の二乗和を平滑化して，
+/// その平方根がしきい値を超えたときにすべりと判定する．
+/// 一度すべりと判定した後は，値がしきい値を下回るまで次の事象を発しない．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchSlipDetector {
+    /// 接線方向の力から振動的な成分を取り出すフィルタ．
+    band_pass: WrenchBandPassFilter,
+    /// すべりと判定する，振動的な成分の大きさのしきい値．
+    threshold: Newton<f64>,
+    /// 二乗和の平滑化係数．
+    alpha: f64,
+    /// 平滑化した二乗和[N^2]．まだレンチを与えていなければ`None`．
+    energy: Option<f64>,
+    /// 直前にすべりと判定していれば`true`．
+    slipping: bool,
+}
+
+impl WrenchSlipDetector {
+    /// # Params
+    /// 1. `low_hz`: 振動的な成分とみなす帯域の下端の周波数[Hz]．
+    /// 1. `high_hz`: 振動的な成分とみなす帯域の上端の周波数[Hz]．
+    /// 1. `sampling_hz`: レンチを与える周期の逆数であるサンプリング周波数[Hz]．
+    /// 1. `threshold`: 振動的な成分の大きさがこの値を超えたときにすべりと判定する．
+    /// 1. `alpha`: 二乗和の平滑化係数．1に近いほど新しい値を重視する．
+    ///
+    /// # Panics
+    /// `0 < low_hz < high_hz < sampling_hz / 2`を満たさない場合，または`alpha`が`(0, 1]`の範囲にない場合．
+    pub fn new(
+        low_hz: f64,
+        high_hz: f64,
+        sampling_hz: f64,
+        threshold: Newton<f64>,
+        alpha: f64,
+    ) -> WrenchSlipDetector {
+        assert!(alpha > 0.0 && alpha <= 1.0);
+
+        WrenchSlipDetector {
+            band_pass: WrenchBandPassFilter::new(low_hz, high_hz, sampling_hz),
+            threshold,
+            alpha,
+            energy: None,
+            slipping: false,
+        }
+    }
+
+    /// 現在すべりと判定していれば`true`を返す．
+    pub fn is_slipping(&self) -> bool {
+        self.slipping
+    }
+
+    /// 接線方向の力の振動的な成分の大きさを返す．
+    /// まだレンチを与えていなければ`None`を返す．
+    pub fn vibration(&self) -> Option<Newton<f64>> {
+        self.energy.map(|e| Newton::new(e.sqrt()))
+    }
+
+    /// 新しいレンチとその取得時刻を与えて，すべりを調べる．
+    ///
+    /// # Returns
+    /// 新たにすべりと判定した場合，その事象`e`を`Some(e)`として返す．
+    pub fn update(&mut self, wrench: Wrench, timestamp: Instant) -> Option<ContactEvent> {
+        let filtered = self.band_pass.filter(wrench).to_array();
+        let power = filtered[0] * filtered[0] + filtered[1] * filtered[1];
+        let energy = match self.energy {
+            Some(e) => e + self.alpha * (power - e),
+            None => power,
+        };
+        self.energy = Some(energy);
+
+        let was_slipping = self.slipping;
+        self.slipping = energy.sqrt() > self.threshold.value_unsafe;
+        if self.slipping && !was_slipping {
+            Some(ContactEvent::Slip { timestamp })
+        } else {
+            None
+        }
+    }
+
+    /// フィルタの内部状態とすべりの判定を捨てる．
+    pub fn reset(&mut self) {
+        self.band_pass.reset();
+        self.energy = None;
+        self.slipping = false;
+    }
+}
+
+impl ContactEventDetector for WrenchSlipDetector {
+    fn detect(&mut self, measurement: &Measurement) -> Option<ContactEvent> {
+        self.update(measurement.wrench, measurement.timestamp)
+    }
+
+    fn reset(&mut self) {
+        WrenchSlipDetector::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 力が`force`となるレンチを作る．
+    fn force(force: [f64; 3]) -> Wrench {
+        Wrench::from_array([force[0], force[1], force[2], 0.0, 0.0, 0.0])
+    }
+
+    /// 時刻`start`から`millis`ミリ秒後の時刻を返す．
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn hysteresis_switches_only_outside_thresholds() {
+        let mut hysteresis = WrenchHysteresis::new([1.0; AXIS_COUNT], [2.0; AXIS_COUNT]);
+        let uniform = |value: f64| Wrench::from_array([value; AXIS_COUNT]);
+
+        // 上側しきい値を超えるまではアクティブにならない
+        assert_eq!(hysteresis.is_active(uniform(1.5)), [false; AXIS_COUNT]);
+        assert_eq!(hysteresis.is_active(uniform(2.5)), [true; AXIS_COUNT]);
+        // 下側しきい値を下回るまではアクティブのまま
+        assert_eq!(hysteresis.is_active(uniform(1.5)), [true; AXIS_COUNT]);
+        assert_eq!(hysteresis.is_active(uniform(0.5)), [false; AXIS_COUNT]);
+        // 大きさは絶対値で評価する
+        assert_eq!(hysteresis.is_active(uniform(-2.5)), [true; AXIS_COUNT]);
+        assert_eq!(hysteresis.state(), [true; AXIS_COUNT]);
+
+        assert_eq!(
+            hysteresis.is_active(Wrench::from_array([0.5, 1.5, 0.5, 1.5, 0.5, 1.5])),
+            [false, true, false, true, false, true]
+        );
+        hysteresis.reset();
+        assert_eq!(hysteresis.state(), [false; AXIS_COUNT]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn hysteresis_with_inverted_thresholds_panics() {
+        let mut low = [1.0; AXIS_COUNT];
+        low[3] = 3.0;
+        WrenchHysteresis::new(low, [2.0; AXIS_COUNT]);
+    }
+
+    #[test]
+    fn peak_detector_tracks_force_and_torque_separately() {
+        let start = Instant::now();
+        let mut detector = WrenchPeakDetector::new();
+        assert_eq!(detector.force_peak(), None);
+
+        let first = Wrench::from_array([3.0, 4.0, 0.0, 0.1, 0.0, 0.0]);
+        assert!(detector.update(first, at(start, 0)));
+
+        // 力の大きさは5Nのままで，トルクだけが大きくなる
+        let second = Wrench::from_array([0.0, 0.0, 5.0, 0.0, 0.5, 0.0]);
+        assert!(detector.update(second, at(start, 1)));
+        assert_eq!(detector.force_peak().unwrap().wrench, first);
+        assert_eq!(detector.torque_peak().unwrap().wrench, second);
+        assert_eq!(detector.torque_peak().unwrap().timestamp, at(start, 1));
+
+        // どちらも最大値を超えなければ記録は変わらない
+        assert!(!detector.update(Wrench::zeroed(), at(start, 2)));
+
+        let third = Wrench::from_array([-6.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(detector.update(third, at(start, 3)));
+        assert_eq!(detector.force_peak().unwrap().timestamp, at(start, 3));
+        assert_eq!(detector.torque_peak().unwrap().wrench, second);
+
+        detector.reset();
+        assert_eq!(detector.force_peak(), None);
+        assert_eq!(detector.torque_peak(), None);
+    }
+
+    #[test]
+    fn contact_is_reported_after_debounce() {
+        let start = Instant::now();
+        let mut detector = WrenchContactDetector::new(
+            Newton::new(5.0),
+            Newton::new(2.0),
+            Duration::from_millis(10),
+        );
+
+        // 短い接触は，条件が途切れた時点で捨てられる
+        assert_eq!(detector.update(force([0.0, 0.0, 6.0]), at(start, 0)), None);
+        assert_eq!(detector.update(force([0.0, 0.0, 6.0]), at(start, 5)), None);
+        assert_eq!(detector.update(force([0.0, 0.0, 1.0]), at(start, 6)), None);
+        assert_eq!(detector.update(force([0.0, 0.0, 6.0]), at(start, 15)), None);
+        assert!(!detector.in_contact());
+
+        // 条件が続いた場合は，条件を満たし始めた時刻の接触として報告する
+        assert_eq!(
+            detector.update(force([3.0, 0.0, 6.0]), at(start, 25)),
+            Some(ContactEvent::Contact {
+                timestamp: at(start, 15)
+            })
+        );
+        assert!(detector.in_contact());
+
+        // 離脱しきい値と接触しきい値の間では接触のまま
+        assert_eq!(detector.update(force([0.0, 0.0, 3.0]), at(start, 30)), None);
+        assert_eq!(detector.update(force([0.0, 0.0, 3.0]), at(start, 50)), None);
+        assert!(detector.in_contact());
+
+        assert_eq!(detector.update(force([0.0, 0.0, 1.0]), at(start, 60)), None);
+        assert_eq!(
+            detector.update(force([0.0, 0.0, 1.0]), at(start, 70)),
+            Some(ContactEvent::Release {
+                timestamp: at(start, 60)
+            })
+        );
+        assert!(!detector.in_contact());
+    }
+
+    #[test]
+    fn slip_is_reported_once_per_vibration_burst() {
+        let start = Instant::now();
+        let mut detector = WrenchSlipDetector::new(20.0, 80.0, 1000.0, Newton::new(0.5), 0.1);
+        let mut events = vec![];
+        let mut feed =
+            |detector: &mut WrenchSlipDetector, range: std::ops::Range<u64>, amplitude: f64| {
+                for i in range {
+                    // 法線方向の一定の力に，接線方向の50Hzの振動を重ねる
+                    let phase = 2.0 * std::f64::consts::PI * 50.0 * i as f64 / 1000.0;
+                    let wrench = force([amplitude * phase.sin(), 0.0, 10.0]);
+                    if let Some(event) = detector.update(wrench, at(start, i)) {
+                        events.push(event);
+                    }
+                }
+            };
+
+        // 振動がなければすべりとは判定しない
+        feed(&mut detector, 0..200, 0.0);
+        assert!(!detector.is_slipping());
+        assert!(detector.vibration().unwrap().value_unsafe < 0.5);
+
+        feed(&mut detector, 200..400, 2.0);
+        assert!(detector.is_slipping());
+        feed(&mut detector, 400..800, 0.0);
+        assert!(!detector.is_slipping());
+        feed(&mut detector, 800..1000, 2.0);
+
+        assert_eq!(events.len(), 2);
+        for (event, burst_start) in events.iter().zip([200, 800].iter()) {
+            match *event {
+                ContactEvent::Slip { timestamp } => assert!(timestamp > at(start, *burst_start)),
+                _ => panic!("Only slip events should be reported: {:?}", event),
+            }
+        }
+
+        detector.reset();
+        assert!(!detector.is_slipping());
+        assert_eq!(detector.vibration(), None);
+    }
+
+    #[test]
+    fn magnitude_alarm_reports_every_axis_that_spikes_together() {
+        let mut alarm = WrenchMagnitudeAlarm::new(Newton::new(5.0), NewtonMeter::new(0.5));
+        assert!(alarm.check(Wrench::zeroed()).is_empty());
+
+        // fxとtzが同時に急変する
+        let spike = Wrench::from_array([10.0, 1.0, 0.0, 0.0, 0.0, -1.0]);
+        let events = alarm.check(spike);
+        let axes: Vec<_> = events.iter().map(|e| e.axis).collect();
+        assert_eq!(axes, [Axis::Fx, Axis::Tz]);
+        assert_eq!(events[1].previous_value, 0.0);
+        assert_eq!(events[1].current_value, -1.0);
+
+        // 値が留まっている間は，いずれの軸も再び警報を発しない
+        assert!(alarm.check(spike).is_empty());
+
+        // 変化量がしきい値以下に戻った後は，再び警報を発する
+        let events = alarm.check(Wrench::zeroed());
+        let axes: Vec<_> = events.iter().map(|e| e.axis).collect();
+        assert_eq!(axes, [Axis::Fx, Axis::Tz]);
+    }
+}
@@ -0,0 +1,50 @@
+//! センサ本体の設定に関する型．
+
+/// センサ本体に内蔵された移動平均フィルタの強さ．
+/// 平均するサンプル数が多いほどノイズは小さくなるが，測定値の遅れは大きくなる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FilterLevel {
+    /// フィルタを用いない．
+    #[default]
+    None,
+    /// 直近2サンプルの移動平均．
+    Average2,
+    /// 直近4サンプルの移動平均．
+    Average4,
+    /// 直近8サンプルの移動平均．
+    Average8,
+}
+
+impl FilterLevel {
+    /// 移動平均をとるサンプル数を返す．フィルタを用いない場合は1となる．
+    pub const fn sample_count(self) -> usize {
+        match self {
+            FilterLevel::None => 1,
+            FilterLevel::Average2 => 2,
+            FilterLevel::Average4 => 4,
+            FilterLevel::Average8 => 8,
+        }
+    }
+
+    /// フィルタの設定命令においてこのフィルタを表す数字を返す．
+    pub(crate) const fn command_digit(self) -> u8 {
+        match self {
+            FilterLevel::None => b'0',
+            FilterLevel::Average2 => b'1',
+            FilterLevel::Average4 => b'2',
+            FilterLevel::Average8 => b'3',
+        }
+    }
+}
+
+/// センサ本体から読み出した機器情報．
+/// 実験条件の記録や，未対応のファームウェアの検出に利用できる．
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// センサの型式名．
+    pub model: String,
+    /// ファームウェアのバージョン．
+    pub firmware_version: String,
+    /// センサのシリアル番号．
+    pub serial_number: String,
+}
@@ -0,0 +1,79 @@
+//! センサとの通信状態の診断．
+
+use std::time::Duration;
+
+/// センサとの通信状態に関する統計情報．
+/// センサや配線の劣化の兆候を現場で把握するために利用できる．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagnosticsReport {
+    /// 測定値の更新を試みた回数．
+    pub total_updates: u64,
+    /// 測定値の更新に失敗した回数．
+    pub failed_updates: u64,
+    /// 1回の更新にかかった時間の平均[μs]．
+    pub average_latency_us: f64,
+    /// 1回の更新にかかった時間の最大値[μs]．
+    pub max_latency_us: f64,
+    /// いずれかの軸のデジタル出力値が出力範囲の端に達していたフレームの数．
+    pub saturated_frames: u64,
+    /// フレームの区切りを再同期するために読み捨てたバイト数．
+    pub discarded_bytes: u64,
+    /// レコード番号の飛びから検出した，取りこぼしたフレームの数．
+    pub dropped_frames: u64,
+}
+
+/// `DiagnosticsReport`を作るために，センサが更新のたびに記録する値．
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DiagnosticsCounter {
+    total_updates: u64,
+    failed_updates: u64,
+    latency_sum: Duration,
+    max_latency: Duration,
+    saturated_frames: u64,
+    discarded_bytes: u64,
+    dropped_frames: u64,
+}
+
+impl DiagnosticsCounter {
+    /// 1回の更新の結果を記録する．
+    pub(crate) fn record_update(&mut self, latency: Duration, succeeded: bool) {
+        self.total_updates += 1;
+        if !succeeded {
+            self.failed_updates += 1;
+        }
+        self.latency_sum += latency;
+        self.max_latency = self.max_latency.max(latency);
+    }
+
+    /// 出力範囲の端に達したフレームを受信したことを記録する．
+    pub(crate) fn record_saturated_frame(&mut self) {
+        self.saturated_frames += 1;
+    }
+
+    /// フレームの区切りの再同期のために，受信データを読み捨てたことを記録する．
+    pub(crate) fn record_discarded_bytes(&mut self, count: usize) {
+        self.discarded_bytes += count as u64;
+    }
+
+    /// フレームを取りこぼしたことを記録する．
+    pub(crate) fn record_dropped_frames(&mut self, count: u8) {
+        self.dropped_frames += count as u64;
+    }
+
+    pub(crate) fn report(&self) -> DiagnosticsReport {
+        let average_latency_us = match self.total_updates {
+            0 => 0.0,
+            n => self.latency_sum.as_secs_f64() * 1e6 / n as f64,
+        };
+
+        DiagnosticsReport {
+            total_updates: self.total_updates,
+            failed_updates: self.failed_updates,
+            average_latency_us,
+            max_latency_us: self.max_latency.as_secs_f64() * 1e6,
+            saturated_frames: self.saturated_frames,
+            discarded_bytes: self.discarded_bytes,
+            dropped_frames: self.dropped_frames,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! レンチを読みやすい文字列として表示するための機能．
+
+use crate::Wrench;
+use std::fmt::{self, Display, Formatter};
+
+/// `Wrench`を表示する際の書式．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrenchDisplayOptions {
+    /// 各成分の小数点以下の桁数．
+    pub precision: usize,
+    /// 力とトルクの後ろに単位を表示するかどうか．
+    pub show_units: bool,
+}
+
+impl Default for WrenchDisplayOptions {
+    /// 小数点以下2桁で単位付きの書式を返す．
+    fn default() -> Self {
+        WrenchDisplayOptions {
+            precision: 2,
+            show_units: true,
+        }
+    }
+}
+
+/// 指定した書式で`Wrench`を表示する．`Wrench::display_with`で作る．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrenchDisplay {
+    /// 表示するレンチ．
+    wrench: Wrench,
+    /// 表示の書式．
+    options: WrenchDisplayOptions,
+}
+
+impl WrenchDisplay {
+    pub(crate) fn new(wrench: Wrench, options: WrenchDisplayOptions) -> WrenchDisplay {
+        WrenchDisplay { wrench, options }
+    }
+}
+
+impl Display for WrenchDisplay {
+    /// `F=( 1.23, -0.45,  9.81) N  T=( 0.01,  0.00, -0.02) N·m`の形式で表示する．
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let [fx, fy, fz, tx, ty, tz] = self.wrench.to_array();
+        let precision = self.options.precision;
+        // 符号と整数部1桁分の幅をとり，正負の値の桁をそろえる
+        let width = precision + 3;
+        let (force_unit, torque_unit) = match self.options.show_units {
+            true => (" N", " N·m"),
+            false => ("", ""),
+        };
+
+        write!(
+            f,
+            "F=({:>w$.p$}, {:>w$.p$}, {:>w$.p$}){}  T=({:>w$.p$}, {:>w$.p$}, {:>w$.p$}){}",
+            fx,
+            fy,
+            fz,
+            force_unit,
+            tx,
+            ty,
+            tz,
+            torque_unit,
+            w = width,
+            p = precision
+        )
+    }
+}
+
+impl Display for Wrench {
+    /// `WrenchDisplayOptions`の既定の書式で表示する．
+    /// `{:.3}`のように精度を指定した場合は，その桁数で表示する．
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut options = WrenchDisplayOptions::default();
+        if let Some(precision) = f.precision() {
+            options.precision = precision;
+        }
+        WrenchDisplay::new(*self, options).fmt(f)
+    }
+}
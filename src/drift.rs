@@ -0,0 +1,101 @@
+//! 温度変化などによるゼロ点のドリフトを補償するためのユーティリティ．
+
+use crate::{Axis, Newton, NewtonMeter, Wrench, AXIS_COUNT};
+use std::time::{Duration, Instant};
+
+/// 無負荷とみなせる状態が続いている間，ゼロ点をゆっくりと現在の出力値に近づける．
+///
+/// 力覚センサの出力は温度変化などで徐々にドリフトするため，長時間の運用ではゼロ点がずれていく．
+/// 補正後の測定値の各軸の大きさがしきい値を下回る状態が一定時間続いた場合にだけゼロ点を更新するため，
+/// 短い接触の間にゼロ点が接触力に引きずられることはない．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchDriftCompensator {
+    /// 無負荷とみなす力の各軸の大きさの上限．
+    idle_force: Newton<f64>,
+    /// 無負荷とみなすトルクの各軸の大きさの上限．
+    idle_torque: NewtonMeter<f64>,
+    /// ゼロ点の更新を始めるまでに，無負荷の状態が続く必要のある時間．
+    idle_duration: Duration,
+    /// 1回の更新でゼロ点を現在の出力値に近づける割合．
+    gain: f64,
+    /// 無負荷の状態が始まった時刻．無負荷でない場合は`None`．
+    idle_since: Option<Instant>,
+}
+
+impl WrenchDriftCompensator {
+    /// 無負荷の状態にない状態で作る．
+    /// # Params
+    /// 1. `idle_force`: 補正後の力の各軸の大きさがすべてこの値未満であれば，無負荷とみなす．
+    /// 1. `idle_torque`: 補正後のトルクの各軸の大きさがすべてこの値未満であれば，無負荷とみなす．
+    /// 1. `idle_duration`: 無負荷の状態がこの時間続いてから，ゼロ点の更新を始める．
+    /// 1. `gain`: 1回の更新でゼロ点を現在の出力値に近づける割合．小さいほどゆっくりと追従する．
+    ///
+    /// # Panics
+    /// `gain`が`(0, 1]`の範囲にない場合．
+    pub fn new(
+        idle_force: Newton<f64>,
+        idle_torque: NewtonMeter<f64>,
+        idle_duration: Duration,
+        gain: f64,
+    ) -> WrenchDriftCompensator {
+        assert!(gain > 0.0 && gain <= 1.0);
+
+        WrenchDriftCompensator {
+            idle_force,
+            idle_torque,
+            idle_duration,
+            gain,
+            idle_since: None,
+        }
+    }
+
+    /// 新しい出力値を与えて，更新後のゼロ点を返す．
+    /// # Params
+    /// 1. `offset`: 現在のゼロ点．
+    /// 1. `raw`: センサの生の出力値．
+    /// 1. `now`: `raw`を受信した時刻．
+    ///
+    /// # Returns
+    /// 無負荷の状態が`idle_duration`以上続いていれば，現在の出力値に近づけたゼロ点を返す．
+    /// そうでなければ`offset`をそのまま返す．
+    pub fn compensate(&mut self, offset: Wrench, raw: Wrench, now: Instant) -> Wrench {
+        let measurement = (raw - offset).to_array();
+        let idle = Axis::ALL.iter().zip(measurement.iter()).all(|(axis, e)| {
+            let limit = if axis.is_force() {
+                self.idle_force.value_unsafe
+            } else {
+                self.idle_torque.value_unsafe
+            };
+            e.abs() < limit
+        });
+
+        if !idle {
+            self.idle_since = None;
+            return offset;
+        }
+
+        let idle_since = *self.idle_since.get_or_insert(now);
+        if now.saturating_duration_since(idle_since) < self.idle_duration {
+            return offset;
+        }
+
+        let current = offset.to_array();
+        let raw = raw.to_array();
+        let mut updated = [0.0; AXIS_COUNT];
+        for (i, e) in updated.iter_mut().enumerate() {
+            *e = current[i] + self.gain * (raw[i] - current[i]);
+        }
+        Wrench::from_array(updated)
+    }
+
+    /// 現在，ゼロ点を更新する状態にあれば`true`を返す．
+    pub fn is_compensating(&self, now: Instant) -> bool {
+        self.idle_since
+            .is_some_and(|since| now.saturating_duration_since(since) >= self.idle_duration)
+    }
+
+    /// 無負荷の状態の記録を捨てる．
+    pub fn reset(&mut self) {
+        self.idle_since = None;
+    }
+}
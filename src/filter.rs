@@ -0,0 +1,1004 @@
+//! レンチの時系列に適用するディジタルフィルタ．
+
+use crate::{Wrench, WrenchRingBuffer, AXIS_COUNT};
+use std::f64::consts::{PI, SQRT_2};
+
+/// レンチの時系列に逐次適用するフィルタに共通の操作．
+/// フィルタの種類を呼び出し側を変えずに差し替えたり，複数のフィルタを組み合わせたりするために用いる．
+pub trait WrenchFilter {
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    fn filter(&mut self, new_sample: Wrench) -> Wrench;
+
+    /// フィルタの内部状態を捨て，まだサンプルを与えていない状態に戻す．
+    fn reset(&mut self);
+}
+
+/// 有限インパルス応答(FIR)フィルタ．
+/// 直近`N`個のレンチと，利用者が与えた長さ`N`の係数列との畳み込みを出力する．
+///
+/// FIRフィルタは常に安定であり，係数列が対称であれば位相特性は線形となる．
+#[derive(Debug, Clone)]
+pub struct WrenchFIRFilter<const N: usize> {
+    /// フィルタ係数．先頭の係数が最も新しいサンプルに掛けられる．
+    kernel: Vec<f64>,
+    /// 直近のサンプル．
+    history: WrenchRingBuffer<N>,
+}
+
+impl<const N: usize> WrenchFIRFilter<N> {
+    /// 与えた係数をそのまま用いるフィルタを作る．
+    /// # Params
+    /// 1. `kernel`: フィルタ係数．先頭の係数が最も新しいサンプルに掛けられる．
+    ///
+    /// # Panics
+    /// `kernel`の長さが`N`と一致しない場合．
+    pub fn new(kernel: Vec<f64>) -> WrenchFIRFilter<N> {
+        assert_eq!(kernel.len(), N);
+
+        WrenchFIRFilter {
+            kernel,
+            history: WrenchRingBuffer::new(),
+        }
+    }
+
+    /// 係数の総和が1となるように正規化した係数を用いるフィルタを作る．
+    /// 正規化したフィルタの直流ゲインは1となる．
+    /// # Panics
+    /// `kernel`の長さが`N`と一致しない場合，または`kernel`の総和が0の場合．
+    pub fn new_normalized(kernel: Vec<f64>) -> WrenchFIRFilter<N> {
+        let sum: f64 = kernel.iter().sum();
+        assert!(sum != 0.0);
+
+        Self::new(kernel.into_iter().map(|k| k / sum).collect())
+    }
+
+    /// フィルタ係数を返す．
+    pub fn kernel(&self) -> &[f64] {
+        &self.kernel
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// サンプルが`N`個たまるまでは，まだ与えられていない過去のサンプルは最初に与えたサンプルと等しいものとみなす．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.history.push(new_sample);
+
+        let len = self.history.len();
+        let mut output = [0.0; AXIS_COUNT];
+        for (k, coefficient) in self.kernel.iter().enumerate() {
+            // k個前のサンプル．まだ与えられていなければ最も古いサンプルで代用する
+            let sample = self.history.get(len.saturating_sub(k + 1)).unwrap();
+            for (o, x) in output.iter_mut().zip(sample.to_array().iter()) {
+                *o += coefficient * x;
+            }
+        }
+
+        Wrench::from_array(output)
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl<const N: usize> WrenchFilter for WrenchFIRFilter<N> {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchFIRFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchFIRFilter::reset(self)
+    }
+}
+
+/// 直近`N`個のレンチの単純移動平均を出力する．
+/// 合計値を逐次更新するため，1回の更新にかかる計算量は`N`によらず一定である．
+#[derive(Debug, Clone)]
+pub struct WrenchMovingAverage<const N: usize> {
+    /// 直近のサンプル．
+    history: WrenchRingBuffer<N>,
+    /// `history`内のサンプルの各軸の合計．
+    sum: [f64; AXIS_COUNT],
+}
+
+impl<const N: usize> WrenchMovingAverage<N> {
+    /// サンプルを1つも持たない状態で作る．
+    /// # Panics
+    /// `N`が0の場合．
+    pub fn new() -> WrenchMovingAverage<N> {
+        WrenchMovingAverage {
+            history: WrenchRingBuffer::new(),
+            sum: [0.0; AXIS_COUNT],
+        }
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// サンプルが`N`個たまるまでは，それまでに与えたサンプルの平均を返す．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        for (s, x) in self.sum.iter_mut().zip(new_sample.to_array().iter()) {
+            *s += x;
+        }
+        if let Some(evicted) = self.history.push(new_sample) {
+            for (s, x) in self.sum.iter_mut().zip(evicted.to_array().iter()) {
+                *s -= x;
+            }
+        }
+
+        Wrench::from_array(self.sum) / self.history.len() as f64
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.sum = [0.0; AXIS_COUNT];
+    }
+}
+
+impl<const N: usize> Default for WrenchMovingAverage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> WrenchFilter for WrenchMovingAverage<N> {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchMovingAverage::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchMovingAverage::reset(self)
+    }
+}
+
+/// 指数平滑化(1次のIIR低域通過フィルタ)によってレンチを平滑化する．
+/// `y[n] = y[n-1] + alpha * (x[n] - y[n-1])`として出力を求める．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchExponentialSmoothing {
+    /// 新しいサンプルに対する重み．
+    alpha: f64,
+    /// 直前の出力．まだサンプルを与えていなければ`None`．
+    output: Option<Wrench>,
+}
+
+impl WrenchExponentialSmoothing {
+    /// # Params
+    /// 1. `alpha`: 新しいサンプルに対する重み．大きいほど新しいサンプルにすばやく追従する．
+    ///
+    /// # Panics
+    /// `alpha`が`(0, 1]`の範囲にない場合．
+    pub fn new(alpha: f64) -> WrenchExponentialSmoothing {
+        assert!(alpha > 0.0 && alpha <= 1.0);
+
+        WrenchExponentialSmoothing {
+            alpha,
+            output: None,
+        }
+    }
+
+    /// 新しいサンプルに対する重みを返す．
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// 最初に与えたサンプルは，そのまま出力となる．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        let output = match self.output {
+            Some(previous) => previous + (new_sample - previous) * self.alpha,
+            None => new_sample,
+        };
+        self.output = Some(output);
+        output
+    }
+
+    /// 直前の出力を捨てる．
+    pub fn reset(&mut self) {
+        self.output = None;
+    }
+}
+
+impl WrenchFilter for WrenchExponentialSmoothing {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchExponentialSmoothing::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchExponentialSmoothing::reset(self)
+    }
+}
+
+/// 直近`N`個のレンチの，各軸の中央値を出力する．
+/// 通信路で時折生じる単発のスパイクを取り除くのに適しており，移動平均と異なり階段状の変化をなまらせない．
+///
+/// 1回の更新で各軸`N`個の値を並べ替えるため，`N`は小さい値(3から9程度)とすることを想定している．
+#[derive(Debug, Clone)]
+pub struct WrenchMedianFilter<const N: usize> {
+    /// 直近のサンプル．
+    history: WrenchRingBuffer<N>,
+}
+
+impl<const N: usize> WrenchMedianFilter<N> {
+    /// サンプルを1つも持たない状態で作る．
+    /// # Panics
+    /// `N`が0の場合．
+    pub fn new() -> WrenchMedianFilter<N> {
+        WrenchMedianFilter {
+            history: WrenchRingBuffer::new(),
+        }
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// サンプルが`N`個たまるまでは，それまでに与えたサンプルの中央値を返す．
+    /// サンプル数が偶数の場合は，中央の2つの値の平均を中央値とする．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.history.push(new_sample);
+        Wrench::from_array(window_medians(&self.history))
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl<const N: usize> Default for WrenchMedianFilter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> WrenchFilter for WrenchMedianFilter<N> {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchMedianFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchMedianFilter::reset(self)
+    }
+}
+
+/// Hampelフィルタによって，レンチの各軸の外れ値を取り除く．
+///
+/// 直近`N`個のサンプルの中央値`m`と中央絶対偏差`MAD`を求め，
+/// 新しいサンプルが`m`から`threshold * 1.4826 * MAD`より離れていれば外れ値とみなして`m`で置き換える．
+/// 外れ値でないサンプルはそのまま出力するため，中央値フィルタよりも信号をそのまま保つ．
+#[derive(Debug, Clone)]
+pub struct WrenchHampelFilter<const N: usize> {
+    /// 外れ値とみなす，中央値からの距離の尺度．正規分布の標準偏差を単位とする．
+    threshold: f64,
+    /// 直近のサンプル．
+    history: WrenchRingBuffer<N>,
+}
+
+impl<const N: usize> WrenchHampelFilter<N> {
+    /// # Params
+    /// 1. `threshold`: 外れ値とみなす，中央値からの距離．正規分布の標準偏差を単位とし，3程度がよく用いられる．
+    ///
+    /// # Panics
+    /// `N`が0の場合，または`threshold`が正でない場合．
+    pub fn new(threshold: f64) -> WrenchHampelFilter<N> {
+        assert!(threshold > 0.0);
+
+        WrenchHampelFilter {
+            threshold,
+            history: WrenchRingBuffer::new(),
+        }
+    }
+
+    /// 外れ値とみなす，中央値からの距離の尺度を返す．
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// 外れ値とみなした軸は直近`N`個のサンプルの中央値で置き換え，そうでない軸はそのまま返す．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        /// 中央絶対偏差を，正規分布の標準偏差の推定値に換算する係数．
+        const MAD_TO_STD_DEV: f64 = 1.4826;
+
+        self.history.push(new_sample);
+        let medians = window_medians(&self.history);
+
+        let mut output = new_sample.to_array();
+        for (i, x) in output.iter_mut().enumerate() {
+            let mut deviations = self
+                .history
+                .iter()
+                .map(|w| (w.to_array()[i] - medians[i]).abs())
+                .collect::<Vec<_>>();
+            let mad = median(&mut deviations);
+            if (*x - medians[i]).abs() > self.threshold * MAD_TO_STD_DEV * mad {
+                *x = medians[i];
+            }
+        }
+        Wrench::from_array(output)
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl<const N: usize> WrenchFilter for WrenchHampelFilter<N> {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchHampelFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchHampelFilter::reset(self)
+    }
+}
+
+/// Butterworthフィルタの次数．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ButterworthOrder {
+    /// 1次．遮断周波数より高い周波数成分を-20dB/decで減衰させる．
+    First,
+    /// 2次．遮断周波数より高い周波数成分を-40dB/decで減衰させる．
+    Second,
+}
+
+/// レンチの各軸に独立に適用するButterworth低域通過フィルタ．
+/// 通過域の特性が平坦であり，力制御ループでのノイズ除去に適している．
+///
+/// 係数は，遮断周波数を補正した双一次変換によって求める．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchButterworthFilter {
+    /// フィルタの次数．
+    order: ButterworthOrder,
+    /// 遮断周波数[Hz]．
+    cutoff_hz: f64,
+    /// サンプリング周波数[Hz]．
+    sampling_hz: f64,
+    /// 各軸に共通のフィルタ．
+    biquad: Biquad,
+}
+
+impl WrenchButterworthFilter {
+    /// # Params
+    /// 1. `order`: フィルタの次数．
+    /// 1. `cutoff_hz`: 遮断周波数[Hz]．
+    /// 1. `sampling_hz`: レンチを与える周期の逆数であるサンプリング周波数[Hz]．
+    ///
+    /// # Panics
+    /// `cutoff_hz`が`(0, sampling_hz / 2)`の範囲にない場合．
+    pub fn new(
+        order: ButterworthOrder,
+        cutoff_hz: f64,
+        sampling_hz: f64,
+    ) -> WrenchButterworthFilter {
+        assert!(cutoff_hz > 0.0 && cutoff_hz < sampling_hz / 2.0);
+
+        let k = (PI * cutoff_hz / sampling_hz).tan();
+        let coefficients = match order {
+            ButterworthOrder::First => {
+                let norm = 1.0 / (1.0 + k);
+                BiquadCoefficients {
+                    b: [k * norm, k * norm, 0.0],
+                    a: [(k - 1.0) * norm, 0.0],
+                }
+            }
+            ButterworthOrder::Second => {
+                let norm = 1.0 / (1.0 + SQRT_2 * k + k * k);
+                let b0 = k * k * norm;
+                BiquadCoefficients {
+                    b: [b0, 2.0 * b0, b0],
+                    a: [
+                        2.0 * (k * k - 1.0) * norm,
+                        (1.0 - SQRT_2 * k + k * k) * norm,
+                    ],
+                }
+            }
+        };
+
+        WrenchButterworthFilter {
+            order,
+            cutoff_hz,
+            sampling_hz,
+            biquad: Biquad::new(coefficients),
+        }
+    }
+
+    /// フィルタの次数を返す．
+    pub fn order(&self) -> ButterworthOrder {
+        self.order
+    }
+
+    /// 遮断周波数[Hz]を返す．
+    pub fn cutoff_hz(&self) -> f64 {
+        self.cutoff_hz
+    }
+
+    /// サンプリング周波数[Hz]を返す．
+    pub fn sampling_hz(&self) -> f64 {
+        self.sampling_hz
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// 最初のサンプルを与えた時点で，それ以前も同じ値が続いていたものとしてフィルタの内部状態を初期化する．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.biquad.filter(new_sample)
+    }
+
+    /// フィルタの内部状態を捨てる．次に与えたサンプルで改めて初期化される．
+    pub fn reset(&mut self) {
+        self.biquad.reset();
+    }
+}
+
+impl WrenchFilter for WrenchButterworthFilter {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchButterworthFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchButterworthFilter::reset(self)
+    }
+}
+
+/// レンチの各軸に独立に適用するIIRノッチフィルタ．
+/// ロボットアームの構造振動など，特定の周波数の狭帯域な成分だけを取り除く．
+///
+/// 係数はRobert Bristow-Johnson氏のAudio EQ Cookbookの式によって求める．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchNotchFilter {
+    /// 取り除く中心周波数[Hz]．
+    center_hz: f64,
+    /// Q値．
+    q: f64,
+    /// サンプリング周波数[Hz]．
+    sampling_hz: f64,
+    /// 各軸に共通のフィルタ．
+    biquad: Biquad,
+}
+
+impl WrenchNotchFilter {
+    /// # Params
+    /// 1. `center_hz`: 取り除く中心周波数[Hz]．
+    /// 1. `q`: Q値．大きいほど取り除く帯域が狭くなる．帯域幅はおよそ`center_hz / q`となる．
+    /// 1. `sampling_hz`: レンチを与える周期の逆数であるサンプリング周波数[Hz]．
+    ///
+    /// # Panics
+    /// `center_hz`が`(0, sampling_hz / 2)`の範囲にない場合，または`q`が正でない場合．
+    pub fn new(center_hz: f64, q: f64, sampling_hz: f64) -> WrenchNotchFilter {
+        assert!(center_hz > 0.0 && center_hz < sampling_hz / 2.0);
+        assert!(q > 0.0);
+
+        let omega = 2.0 * PI * center_hz / sampling_hz;
+        let alpha = omega.sin() / (2.0 * q);
+        let norm = 1.0 / (1.0 + alpha);
+        let cos = omega.cos();
+        let coefficients = BiquadCoefficients {
+            b: [norm, -2.0 * cos * norm, norm],
+            a: [-2.0 * cos * norm, (1.0 - alpha) * norm],
+        };
+
+        WrenchNotchFilter {
+            center_hz,
+            q,
+            sampling_hz,
+            biquad: Biquad::new(coefficients),
+        }
+    }
+
+    /// 取り除く中心周波数[Hz]を返す．
+    pub fn center_hz(&self) -> f64 {
+        self.center_hz
+    }
+
+    /// Q値を返す．
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+
+    /// サンプリング周波数[Hz]を返す．
+    pub fn sampling_hz(&self) -> f64 {
+        self.sampling_hz
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// 最初のサンプルを与えた時点で，それ以前も同じ値が続いていたものとしてフィルタの内部状態を初期化する．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.biquad.filter(new_sample)
+    }
+
+    /// フィルタの内部状態を捨てる．次に与えたサンプルで改めて初期化される．
+    pub fn reset(&mut self) {
+        self.biquad.reset();
+    }
+}
+
+impl WrenchFilter for WrenchNotchFilter {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchNotchFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchNotchFilter::reset(self)
+    }
+}
+
+/// レンチの各軸に独立に適用するIIR帯域通過フィルタ．
+/// 準静的な負荷や高周波ノイズを取り除き，すべりや振動など特定の帯域の成分だけを取り出す．
+///
+/// 係数はRobert Bristow-Johnson氏のAudio EQ Cookbookの式によって求める．中心周波数でのゲインは1となる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchBandPassFilter {
+    /// 通過域の下端の周波数[Hz]．
+    low_hz: f64,
+    /// 通過域の上端の周波数[Hz]．
+    high_hz: f64,
+    /// サンプリング周波数[Hz]．
+    sampling_hz: f64,
+    /// 各軸に共通のフィルタ．
+    biquad: Biquad,
+}
+
+impl WrenchBandPassFilter {
+    /// # Params
+    /// 1. `low_hz`: 通過域の下端の周波数[Hz]．
+    /// 1. `high_hz`: 通過域の上端の周波数[Hz]．
+    /// 1. `sampling_hz`: レンチを与える周期の逆数であるサンプリング周波数[Hz]．
+    ///
+    /// # Panics
+    /// `0 < low_hz < high_hz < sampling_hz / 2`を満たさない場合．
+    pub fn new(low_hz: f64, high_hz: f64, sampling_hz: f64) -> WrenchBandPassFilter {
+        assert!(low_hz > 0.0 && low_hz < high_hz && high_hz < sampling_hz / 2.0);
+
+        let center_hz = (low_hz * high_hz).sqrt();
+        let q = center_hz / (high_hz - low_hz);
+        let omega = 2.0 * PI * center_hz / sampling_hz;
+        let alpha = omega.sin() / (2.0 * q);
+        let norm = 1.0 / (1.0 + alpha);
+        let coefficients = BiquadCoefficients {
+            b: [alpha * norm, 0.0, -alpha * norm],
+            a: [-2.0 * omega.cos() * norm, (1.0 - alpha) * norm],
+        };
+
+        WrenchBandPassFilter {
+            low_hz,
+            high_hz,
+            sampling_hz,
+            biquad: Biquad::new(coefficients),
+        }
+    }
+
+    /// 通過域の下端の周波数[Hz]を返す．
+    pub fn low_hz(&self) -> f64 {
+        self.low_hz
+    }
+
+    /// 通過域の上端の周波数[Hz]を返す．
+    pub fn high_hz(&self) -> f64 {
+        self.high_hz
+    }
+
+    /// サンプリング周波数[Hz]を返す．
+    pub fn sampling_hz(&self) -> f64 {
+        self.sampling_hz
+    }
+
+    /// 新しいサンプルを与えて，フィルタの出力を返す．
+    /// 最初のサンプルを与えた時点で，それ以前も同じ値が続いていたものとしてフィルタの内部状態を初期化する．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.biquad.filter(new_sample)
+    }
+
+    /// フィルタの内部状態を捨てる．次に与えたサンプルで改めて初期化される．
+    pub fn reset(&mut self) {
+        self.biquad.reset();
+    }
+}
+
+impl WrenchFilter for WrenchBandPassFilter {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchBandPassFilter::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchBandPassFilter::reset(self)
+    }
+}
+
+/// 各軸の大きさが不感帯の幅以下の値を0とする不感帯．
+/// わずかに残ったゼロ点のずれが，アドミタンス制御などでロボットをゆっくりとドリフトさせるのを防ぐ．
+///
+/// 不感帯の幅を超える値は，そのまま出力する．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchDeadband {
+    /// 各軸の不感帯の幅．
+    widths: [f64; AXIS_COUNT],
+}
+
+impl WrenchDeadband {
+    /// # Params
+    /// 1. `widths`: 各軸の不感帯の幅．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// `widths`のいずれかの要素が負の場合．
+    pub fn new(widths: [f64; AXIS_COUNT]) -> WrenchDeadband {
+        assert!(widths.iter().all(|&w| w >= 0.0));
+
+        WrenchDeadband { widths }
+    }
+
+    /// 各軸の不感帯の幅を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn widths(&self) -> [f64; AXIS_COUNT] {
+        self.widths
+    }
+
+    /// 不感帯を適用したレンチを返す．
+    pub fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        let mut output = new_sample.to_array();
+        for (x, &w) in output.iter_mut().zip(self.widths.iter()) {
+            if x.abs() <= w {
+                *x = 0.0;
+            }
+        }
+        Wrench::from_array(output)
+    }
+}
+
+impl WrenchFilter for WrenchDeadband {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        WrenchDeadband::filter(self, new_sample)
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// 複数のフィルタを順に適用する信号処理の流れ．
+/// ゼロ点の除去，低域通過フィルタ，制限などの構成を，`WrenchPipelineBuilder`で1か所にまとめて記述できる．
+///
+/// ```
+/// use wacohtech_force_torque_sensor::{ButterworthOrder, Wrench, WrenchButterworthFilter, WrenchPipeline};
+///
+/// let mut pipeline = WrenchPipeline::builder()
+///     .remove_offset(Wrench::zeroed())
+///     .stage(WrenchButterworthFilter::new(ButterworthOrder::Second, 10.0, 1000.0))
+///     .limit([100.0, 100.0, 100.0, 2.0, 2.0, 2.0])
+///     .build();
+/// let output = pipeline.process(Wrench::zeroed());
+/// ```
+pub struct WrenchPipeline {
+    /// 追加した順に並んだフィルタ．
+    stages: Vec<Box<dyn WrenchFilter + Send>>,
+}
+
+impl WrenchPipeline {
+    /// フィルタを1つも持たない状態のビルダを返す．
+    pub fn builder() -> WrenchPipelineBuilder {
+        WrenchPipelineBuilder::new()
+    }
+
+    /// 新しいサンプルを与えて，すべてのフィルタを順に適用した出力を返す．
+    /// フィルタを1つも持たない場合は，サンプルをそのまま返す．
+    pub fn process(&mut self, new_sample: Wrench) -> Wrench {
+        self.stages
+            .iter_mut()
+            .fold(new_sample, |sample, stage| stage.filter(sample))
+    }
+
+    /// すべてのフィルタの内部状態を捨てる．
+    pub fn reset(&mut self) {
+        for stage in self.stages.iter_mut() {
+            stage.reset();
+        }
+    }
+
+    /// フィルタの個数を返す．
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// フィルタを1つも持たなければ`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl WrenchFilter for WrenchPipeline {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        self.process(new_sample)
+    }
+
+    fn reset(&mut self) {
+        WrenchPipeline::reset(self)
+    }
+}
+
+/// `WrenchPipeline`を組み立てるためのビルダ．
+/// フィルタは追加した順に適用される．
+pub struct WrenchPipelineBuilder {
+    /// 追加した順に並んだフィルタ．
+    stages: Vec<Box<dyn WrenchFilter + Send>>,
+}
+
+impl WrenchPipelineBuilder {
+    /// フィルタを1つも持たない状態で作る．
+    pub fn new() -> WrenchPipelineBuilder {
+        WrenchPipelineBuilder { stages: vec![] }
+    }
+
+    /// 任意のフィルタを追加する．
+    pub fn stage<F: WrenchFilter + Send + 'static>(mut self, filter: F) -> Self {
+        self.stages.push(Box::new(filter));
+        self
+    }
+
+    /// 入力から一定のゼロ点を減じる段を追加する．
+    pub fn remove_offset(self, offset: Wrench) -> Self {
+        self.stage(OffsetRemoval { offset })
+    }
+
+    /// 各軸の大きさを上限までに制限する段を追加する．
+    /// # Params
+    /// 1. `limits`: 各軸の大きさの上限．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// `limits`のいずれかの要素が負の場合．
+    pub fn limit(self, limits: [f64; AXIS_COUNT]) -> Self {
+        assert!(limits.iter().all(|&l| l >= 0.0));
+
+        self.stage(Limiter { limits })
+    }
+
+    /// 各軸の大きさが不感帯の幅以下の値を0とする段を追加する．
+    /// # Params
+    /// 1. `widths`: 各軸の不感帯の幅．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// `widths`のいずれかの要素が負の場合．
+    pub fn deadband(self, widths: [f64; AXIS_COUNT]) -> Self {
+        self.stage(WrenchDeadband::new(widths))
+    }
+
+    /// 追加したフィルタを順に適用する`WrenchPipeline`を作る．
+    pub fn build(self) -> WrenchPipeline {
+        WrenchPipeline {
+            stages: self.stages,
+        }
+    }
+}
+
+impl Default for WrenchPipelineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 入力から一定のゼロ点を減じる段．
+#[derive(Debug, Clone, PartialEq)]
+struct OffsetRemoval {
+    /// 減じるゼロ点．
+    offset: Wrench,
+}
+
+impl WrenchFilter for OffsetRemoval {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        new_sample - self.offset
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// 各軸の大きさを上限までに制限する段．
+#[derive(Debug, Clone, PartialEq)]
+struct Limiter {
+    /// 各軸の大きさの上限．
+    limits: [f64; AXIS_COUNT],
+}
+
+impl WrenchFilter for Limiter {
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        let mut output = new_sample.to_array();
+        for (x, &l) in output.iter_mut().zip(self.limits.iter()) {
+            *x = x.clamp(-l, l);
+        }
+        Wrench::from_array(output)
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// 2次のIIRフィルタ(双2次フィルタ)の係数．
+/// `y[n] = b0 x[n] + b1 x[n-1] + b2 x[n-2] - a1 y[n-1] - a2 y[n-2]`として出力を求める．
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BiquadCoefficients {
+    /// 入力側の係数`[b0, b1, b2]`．
+    b: [f64; 3],
+    /// 出力側の係数`[a1, a2]`．
+    a: [f64; 2],
+}
+
+/// レンチの各軸に独立に適用する双2次フィルタ．
+#[derive(Debug, Clone, PartialEq)]
+struct Biquad {
+    /// フィルタ係数．
+    coefficients: BiquadCoefficients,
+    /// 各軸の直近2つの入力`[x[n-1], x[n-2]]`と出力`[y[n-1], y[n-2]]`．まだサンプルを与えていなければ`None`．
+    state: Option<[([f64; 2], [f64; 2]); AXIS_COUNT]>,
+}
+
+impl Biquad {
+    fn new(coefficients: BiquadCoefficients) -> Biquad {
+        Biquad {
+            coefficients,
+            state: None,
+        }
+    }
+
+    fn filter(&mut self, new_sample: Wrench) -> Wrench {
+        let x = new_sample.to_array();
+        let BiquadCoefficients { b, a } = self.coefficients;
+
+        // 最初のサンプルでは，同じ値が続いていた場合の定常状態から始める
+        let state = self.state.get_or_insert_with(|| {
+            let dc_gain = (b[0] + b[1] + b[2]) / (1.0 + a[0] + a[1]);
+            let mut state = [([0.0; 2], [0.0; 2]); AXIS_COUNT];
+            for (s, &x) in state.iter_mut().zip(x.iter()) {
+                *s = ([x; 2], [dc_gain * x; 2]);
+            }
+            state
+        });
+
+        let mut output = [0.0; AXIS_COUNT];
+        for ((y, (xs, ys)), &x) in output.iter_mut().zip(state.iter_mut()).zip(x.iter()) {
+            *y = b[0] * x + b[1] * xs[0] + b[2] * xs[1] - a[0] * ys[0] - a[1] * ys[1];
+            *xs = [x, xs[0]];
+            *ys = [*y, ys[0]];
+        }
+        Wrench::from_array(output)
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// バッファ内のレンチの各軸の中央値を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+/// # Panics
+/// バッファが空の場合．
+fn window_medians<const N: usize>(history: &WrenchRingBuffer<N>) -> [f64; AXIS_COUNT] {
+    let mut medians = [0.0; AXIS_COUNT];
+    for (i, m) in medians.iter_mut().enumerate() {
+        let mut values = history.iter().map(|w| w.to_array()[i]).collect::<Vec<_>>();
+        *m = median(&mut values);
+    }
+    medians
+}
+
+/// 値の中央値を返す．要素数が偶数の場合は，中央の2つの値の平均を返す．
+/// `values`は並べ替えられる．
+/// # Panics
+/// `values`が空の場合．
+fn median(values: &mut [f64]) -> f64 {
+    assert!(!values.is_empty());
+
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_wrench_close(actual: Wrench, expected: Wrench) {
+        let (a, e) = (actual.to_array(), expected.to_array());
+        for (x, y) in a.iter().zip(e.iter()) {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", a, e);
+        }
+    }
+
+    #[test]
+    fn normalized_uniform_fir_equals_moving_average() {
+        let mut fir = WrenchFIRFilter::<5>::new_normalized(vec![1.0; 5]);
+        let mut average = WrenchMovingAverage::<5>::new();
+        assert!(fir.kernel().iter().all(|k| (k - 0.2).abs() < 1e-12));
+
+        for i in 0..20 {
+            let x = (i * i % 7) as f64;
+            let sample = Wrench::from_array([x, -x, 2.0 * x, 0.5 * x, x + 1.0, 3.0]);
+            let (fir_output, average_output) = (fir.filter(sample), average.filter(sample));
+            // ウィンドウが埋まるまでは，まだ与えていないサンプルの扱いが異なる
+            if i >= 4 {
+                assert_wrench_close(fir_output, average_output);
+            }
+        }
+    }
+
+    /// 各軸の値が`[x, 2x, 3x, -x, -2x, -3x]`となるレンチを作る．
+    fn scaled(x: f64) -> Wrench {
+        Wrench::from_array([x, 2.0 * x, 3.0 * x, -x, -2.0 * x, -3.0 * x])
+    }
+
+    /// 0から`value`への階段状の入力を十分な回数与えた後の出力を返す．
+    fn settle<F: WrenchFilter>(filter: &mut F, value: f64) -> Wrench {
+        filter.filter(Wrench::zeroed());
+        let mut output = Wrench::zeroed();
+        for _ in 0..5000 {
+            output = filter.filter(scaled(value));
+        }
+        output
+    }
+
+    #[test]
+    fn low_pass_and_notch_filters_have_unit_dc_gain() {
+        let mut first = WrenchButterworthFilter::new(ButterworthOrder::First, 10.0, 1000.0);
+        assert_wrench_close(settle(&mut first, 2.0), scaled(2.0));
+
+        let mut second = WrenchButterworthFilter::new(ButterworthOrder::Second, 10.0, 1000.0);
+        assert_wrench_close(settle(&mut second, 2.0), scaled(2.0));
+
+        let mut notch = WrenchNotchFilter::new(50.0, 5.0, 1000.0);
+        assert_wrench_close(settle(&mut notch, 2.0), scaled(2.0));
+    }
+
+    #[test]
+    fn band_pass_filter_rejects_dc() {
+        let mut band_pass = WrenchBandPassFilter::new(20.0, 80.0, 1000.0);
+        assert_wrench_close(settle(&mut band_pass, 2.0), Wrench::zeroed());
+    }
+
+    #[test]
+    fn first_sample_initializes_biquad_state() {
+        // 最初のサンプルと同じ値が続いていたものとみなすため，一定の入力はそのまま出力される
+        let mut butterworth = WrenchButterworthFilter::new(ButterworthOrder::Second, 10.0, 1000.0);
+        let mut notch = WrenchNotchFilter::new(50.0, 5.0, 1000.0);
+        for _ in 0..10 {
+            assert_wrench_close(butterworth.filter(scaled(3.0)), scaled(3.0));
+            assert_wrench_close(notch.filter(scaled(3.0)), scaled(3.0));
+        }
+    }
+
+    #[test]
+    fn notch_filter_removes_center_frequency() {
+        let mut notch = WrenchNotchFilter::new(50.0, 5.0, 1000.0);
+        let mut peak: f64 = 0.0;
+        for i in 0..5000 {
+            let x = (2.0 * PI * 50.0 * i as f64 / 1000.0).sin();
+            let output = notch.filter(scaled(x)).to_array()[0];
+            if i >= 4000 {
+                peak = peak.max(output.abs());
+            }
+        }
+        assert!(peak < 1e-3, "{}", peak);
+    }
+
+    #[test]
+    fn median_filter_removes_single_spike_and_keeps_step() {
+        let mut median = WrenchMedianFilter::<5>::new();
+        for _ in 0..5 {
+            median.filter(scaled(1.0));
+        }
+        assert_wrench_close(median.filter(scaled(100.0)), scaled(1.0));
+        for _ in 0..4 {
+            assert_wrench_close(median.filter(scaled(1.0)), scaled(1.0));
+        }
+
+        // 階段状の変化は，ウィンドウの過半数を占めた時点でそのまま現れる
+        assert_wrench_close(median.filter(scaled(5.0)), scaled(1.0));
+        assert_wrench_close(median.filter(scaled(5.0)), scaled(1.0));
+        assert_wrench_close(median.filter(scaled(5.0)), scaled(5.0));
+    }
+
+    #[test]
+    fn hampel_filter_replaces_only_outliers() {
+        let mut hampel = WrenchHampelFilter::<5>::new(3.0);
+        for i in 0..20 {
+            let x = if i % 2 == 0 { 1.0 } else { 1.2 };
+            if i == 10 {
+                // 外れ値は直近のサンプルの中央値で置き換える
+                assert_wrench_close(hampel.filter(scaled(50.0)), scaled(1.2));
+            } else {
+                assert_wrench_close(hampel.filter(scaled(x)), scaled(x));
+            }
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! レンチの幾何的な解析を行うためのユーティリティ．
+
+use crate::{Meter, Newton, NewtonMeter, Triplet, Wrench, AXIS_COUNT};
+
+/// 単位ベクトルとみなす際の，ノルムと1との差の許容値．
+const UNIT_VECTOR_TOLERANCE: f64 = 1e-6;
+/// 標準重力加速度[m/s²]．
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// 接触面の法線方向を基準にして，力を法線方向成分とせん断方向成分とに分解する．
+/// 把持物体の滑りの予測などに利用できる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchForceDecomposer {
+    /// センサ座標系で表した接触面の単位法線ベクトル．
+    contact_normal: [f64; 3],
+}
+
+impl WrenchForceDecomposer {
+    /// # Params
+    /// 1. `contact_normal`: センサ座標系で表した接触面の単位法線ベクトル．
+    ///
+    /// # Panics
+    /// `contact_normal`が単位ベクトルでない場合．
+    pub fn new(contact_normal: [f64; 3]) -> WrenchForceDecomposer {
+        assert!((norm(contact_normal) - 1.0).abs() < UNIT_VECTOR_TOLERANCE);
+
+        WrenchForceDecomposer { contact_normal }
+    }
+
+    /// 接触面の単位法線ベクトルを返す．
+    pub fn contact_normal(&self) -> [f64; 3] {
+        self.contact_normal
+    }
+
+    /// レンチの力を分解する．
+    ///
+    /// # Returns
+    /// 法線方向の力(法線ベクトルの向きを正とする)と，せん断方向の力ベクトルの組を返す．
+    pub fn decompose(&self, wrench: &Wrench) -> (Newton<f64>, Triplet<Newton<f64>>) {
+        let force = force_array(wrench);
+        let n = self.contact_normal;
+
+        let normal = force[0] * n[0] + force[1] * n[1] + force[2] * n[2];
+        let shear = Triplet::new(
+            force[0] - normal * n[0],
+            force[1] - normal * n[1],
+            force[2] - normal * n[2],
+        )
+        .map(Newton::new);
+
+        (Newton::new(normal), shear)
+    }
+
+    /// せん断方向の力の大きさと法線方向の力の大きさの比を返す．
+    /// 法線方向の力が0の場合，結果は無限大または`NaN`となる．
+    pub fn friction_ratio(&self, wrench: &Wrench) -> f64 {
+        let (normal, shear) = self.decompose(wrench);
+        let shear = [
+            shear.x.value_unsafe,
+            shear.y.value_unsafe,
+            shear.z.value_unsafe,
+        ];
+        norm(shear) / normal.value_unsafe.abs()
+    }
+}
+
+/// 平面の接触面上で推定した接触点．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    /// センサ座標系で表した接触点(圧力中心)の位置[m]．
+    pub position: [f64; 3],
+    /// 接触面に垂直な力．センサのz軸の向きを正とする．
+    pub normal_force: Newton<f64>,
+    /// 接触点まわりの，接触面の法線まわりのトルク．
+    pub torsional_torque: NewtonMeter<f64>,
+}
+
+/// センサのz軸に垂直な平面の接触面について，測定したレンチから接触点(圧力中心)を推定する．
+/// 押し付けや研磨などの作業で，工具先端のどこで対象に触れているかを求めるために用いる．
+///
+/// 接触面はセンサ座標系で`z = surface_offset`の平面とし，接触面から受ける力はすべて接触点にはたらくものとみなす．
+/// このとき接触点`(px, py, surface_offset)`は，センサ原点まわりのトルク`τ = p × f + τn ez`から
+/// `px = (surface_offset * fx - ty) / fz`，`py = (surface_offset * fy + tx) / fz`として求まる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchContactPointEstimator {
+    /// センサ原点から接触面までのz軸方向の距離．
+    surface_offset: Meter<f64>,
+    /// 接触点を推定するのに必要な，接触面に垂直な力の大きさの下限．
+    min_normal_force: Newton<f64>,
+}
+
+impl WrenchContactPointEstimator {
+    /// # Params
+    /// 1. `surface_offset`: センサ原点から接触面までのz軸方向の距離．
+    /// 1. `min_normal_force`: 接触面に垂直な力の大きさがこの値未満の場合は，接触していないものとみなす．
+    ///
+    /// # Panics
+    /// `min_normal_force`が正でない場合．
+    pub fn new(
+        surface_offset: Meter<f64>,
+        min_normal_force: Newton<f64>,
+    ) -> WrenchContactPointEstimator {
+        assert!(min_normal_force.value_unsafe > 0.0);
+
+        WrenchContactPointEstimator {
+            surface_offset,
+            min_normal_force,
+        }
+    }
+
+    /// センサ原点から接触面までのz軸方向の距離を返す．
+    pub fn surface_offset(&self) -> Meter<f64> {
+        self.surface_offset
+    }
+
+    /// 接触点を推定するのに必要な，接触面に垂直な力の大きさの下限を返す．
+    pub fn min_normal_force(&self) -> Newton<f64> {
+        self.min_normal_force
+    }
+
+    /// レンチから接触点を推定する．
+    ///
+    /// # Returns
+    /// 接触面に垂直な力の大きさが下限以上であれば，推定した接触点`p`を`Some(p)`として返す．
+    /// そうでなければ`None`を返す．
+    pub fn estimate(&self, wrench: &Wrench) -> Option<ContactPoint> {
+        let [fx, fy, fz, tx, ty, tz] = wrench.to_array();
+        if fz.abs() < self.min_normal_force.value_unsafe {
+            return None;
+        }
+
+        let d = self.surface_offset.value_unsafe;
+        let px = (d * fx - ty) / fz;
+        let py = (d * fy + tx) / fz;
+        // センサ原点まわりのz軸トルクから，接触点にはたらく力によるモーメントを除く
+        let torsional = tz - (px * fy - py * fx);
+
+        Some(ContactPoint {
+            position: [px, py, d],
+            normal_force: Newton::new(fz),
+            torsional_torque: NewtonMeter::<f64>::new(torsional),
+        })
+    }
+}
+
+/// レンチが楕円体で近似した許容領域の内側にあるかどうかを判定する．
+/// 把持の力閉包性の判定などで用いられる，レンチ空間での楕円体近似による実行可能性の判定を行う．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchSafetyZone {
+    /// 楕円体の中心．
+    center: Wrench,
+    /// 各軸方向の楕円体の半径．
+    radii: [f64; AXIS_COUNT],
+}
+
+impl WrenchSafetyZone {
+    /// # Params
+    /// 1. `center`: 楕円体の中心．
+    /// 1. `radii`: 各軸方向の楕円体の半径．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    ///
+    /// # Panics
+    /// `radii`のいずれかの要素が正でない場合．
+    pub fn new(center: Wrench, radii: [f64; AXIS_COUNT]) -> WrenchSafetyZone {
+        assert!(radii.iter().all(|&r| r > 0.0));
+
+        WrenchSafetyZone { center, radii }
+    }
+
+    /// 楕円体の中心を返す．
+    pub fn center(&self) -> Wrench {
+        self.center
+    }
+
+    /// 各軸方向の楕円体の半径を返す．
+    pub fn radii(&self) -> [f64; AXIS_COUNT] {
+        self.radii
+    }
+
+    /// レンチが楕円体の内側(境界を含む)にあれば`true`を返す．
+    pub fn contains(&self, wrench: &Wrench) -> bool {
+        self.normalized_squared_distance(wrench) <= 1.0
+    }
+
+    /// 各軸を半径で正規化した空間における，レンチと楕円体の境界との符号付き距離を返す．
+    /// レンチが楕円体の内側にある場合は負，外側にある場合は正となる．
+    pub fn distance_to_boundary(&self, wrench: &Wrench) -> f64 {
+        self.normalized_squared_distance(wrench).sqrt() - 1.0
+    }
+
+    /// 各軸を半径で正規化した空間における，レンチと楕円体の中心との距離の2乗を返す．
+    fn normalized_squared_distance(&self, wrench: &Wrench) -> f64 {
+        let w = wrench.to_array();
+        let c = self.center.to_array();
+        (0..AXIS_COUNT)
+            .map(|i| ((w[i] - c[i]) / self.radii[i]).powi(2))
+            .sum()
+    }
+}
+
+/// センサに取り付けた工具にはたらく重力の影響を，測定値から取り除く．
+///
+/// 手首に取り付けたセンサの測定値には工具の重さによる力とトルクが含まれ，その大きさはロボットの姿勢によって変わる．
+/// センサ座標系で表した重力加速度を与えると，工具の質量`m`と重心位置`r`から
+/// 重力による力`m * g`とトルク`r × m * g`を求めて測定値から減じる．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchGravityCompensator {
+    /// 工具の質量[kg]．
+    tool_mass_kg: f64,
+    /// センサ座標系で表した工具の重心位置[m]．
+    tool_com_m: [f64; 3],
+}
+
+impl WrenchGravityCompensator {
+    /// # Params
+    /// 1. `tool_mass_kg`: 工具の質量[kg]．
+    /// 1. `tool_com_m`: センサ座標系で表した工具の重心位置[m]．
+    ///
+    /// # Panics
+    /// `tool_mass_kg`が負の場合．
+    pub fn new(tool_mass_kg: f64, tool_com_m: [f64; 3]) -> WrenchGravityCompensator {
+        assert!(tool_mass_kg >= 0.0);
+
+        WrenchGravityCompensator {
+            tool_mass_kg,
+            tool_com_m,
+        }
+    }
+
+    /// 工具の質量[kg]を返す．
+    pub fn tool_mass_kg(&self) -> f64 {
+        self.tool_mass_kg
+    }
+
+    /// センサ座標系で表した工具の重心位置[m]を返す．
+    pub fn tool_com_m(&self) -> [f64; 3] {
+        self.tool_com_m
+    }
+
+    /// 工具にはたらく重力がセンサに及ぼすレンチを返す．
+    /// # Params
+    /// 1. `gravity_in_sensor_frame`: センサ座標系で表した重力加速度[m/s²]．
+    pub fn gravity_wrench(&self, gravity_in_sensor_frame: [f64; 3]) -> Wrench {
+        let g = gravity_in_sensor_frame;
+        let m = self.tool_mass_kg;
+        let force = [m * g[0], m * g[1], m * g[2]];
+        let torque = cross(self.tool_com_m, force);
+        Wrench::from_array([
+            force[0], force[1], force[2], torque[0], torque[1], torque[2],
+        ])
+    }
+
+    /// 測定したレンチから，工具にはたらく重力の影響を取り除いたレンチを返す．
+    /// # Params
+    /// 1. `wrench`: センサで測定したレンチ．
+    /// 1. `gravity_in_sensor_frame`: センサ座標系で表した重力加速度[m/s²]．
+    pub fn compensate(&self, wrench: Wrench, gravity_in_sensor_frame: [f64; 3]) -> Wrench {
+        wrench - self.gravity_wrench(gravity_in_sensor_frame)
+    }
+
+    /// センサの姿勢から，センサ座標系で表した重力加速度[m/s²]を返す．
+    /// # Params
+    /// 1. `rotation`: センサ座標系からワールド座標系への回転行列．ワールド座標系のz軸は鉛直上向きとする．
+    pub fn gravity_in_sensor_frame(rotation: &[[f64; 3]; 3]) -> [f64; 3] {
+        // ワールド座標系の重力加速度(0, 0, -g)を，回転行列の転置でセンサ座標系に戻す
+        let r = rotation;
+        [
+            -STANDARD_GRAVITY * r[2][0],
+            -STANDARD_GRAVITY * r[2][1],
+            -STANDARD_GRAVITY * r[2][2],
+        ]
+    }
+
+    /// 測定したレンチから，センサの姿勢に応じた工具の重力の影響を取り除いたレンチを返す．
+    /// # Params
+    /// 1. `wrench`: センサで測定したレンチ．
+    /// 1. `rotation`: センサ座標系からワールド座標系への回転行列．ワールド座標系のz軸は鉛直上向きとする．
+    pub fn compensate_with_rotation(&self, wrench: Wrench, rotation: &[[f64; 3]; 3]) -> Wrench {
+        self.compensate(wrench, Self::gravity_in_sensor_frame(rotation))
+    }
+
+    /// 測定したレンチから，センサの姿勢に応じた工具の重力の影響を取り除いたレンチを返す．
+    /// # Params
+    /// 1. `wrench`: センサで測定したレンチ．
+    /// 1. `quaternion`: センサ座標系からワールド座標系への回転を表す単位四元数`[w, x, y, z]`．
+    ///    ワールド座標系のz軸は鉛直上向きとする．
+    ///
+    /// # Panics
+    /// `quaternion`が単位四元数でない場合．
+    pub fn compensate_with_quaternion(&self, wrench: Wrench, quaternion: [f64; 4]) -> Wrench {
+        self.compensate_with_rotation(wrench, &quaternion_to_rotation(quaternion))
+    }
+}
+
+/// ある座標系から別の座標系への剛体変換．
+/// センサ座標系で測定したレンチを，工具先端やロボットのフランジなど，別の点を原点とする座標系で表し直すために用いる．
+///
+/// 変換は，変換元の座標系で表した点`p`を変換先の座標系で`rotation * p + translation`と表すものとする．
+/// つまり`rotation`と`translation`は，変換先の座標系から見た変換元の座標系の姿勢と原点の位置である．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTransform {
+    /// 変換元の座標系から変換先の座標系への回転行列．
+    rotation: [[f64; 3]; 3],
+    /// 変換先の座標系で表した，変換元の座標系の原点の位置[m]．
+    translation: [f64; 3],
+}
+
+impl FrameTransform {
+    /// # Params
+    /// 1. `rotation`: 変換元の座標系から変換先の座標系への回転行列．
+    /// 1. `translation`: 変換先の座標系で表した，変換元の座標系の原点の位置[m]．
+    pub fn new(rotation: [[f64; 3]; 3], translation: [f64; 3]) -> FrameTransform {
+        FrameTransform {
+            rotation,
+            translation,
+        }
+    }
+
+    /// 回転を単位四元数`[w, x, y, z]`で与えて作る．
+    /// # Panics
+    /// `quaternion`が単位四元数でない場合．
+    pub fn from_quaternion(quaternion: [f64; 4], translation: [f64; 3]) -> FrameTransform {
+        Self::new(quaternion_to_rotation(quaternion), translation)
+    }
+
+    /// 回転を伴わない平行移動のみの変換を作る．
+    pub fn from_translation(translation: [f64; 3]) -> FrameTransform {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        Self::new(identity, translation)
+    }
+
+    /// 回転行列を返す．
+    pub fn rotation(&self) -> [[f64; 3]; 3] {
+        self.rotation
+    }
+
+    /// 変換先の座標系で表した，変換元の座標系の原点の位置[m]を返す．
+    pub fn translation(&self) -> [f64; 3] {
+        self.translation
+    }
+
+    /// 変換元の座標系の原点にはたらくレンチを，変換先の座標系の原点にはたらくレンチとして表し直す．
+    /// 力とトルクを回転させたうえで，原点の移動によって生じるモーメント`translation × force`をトルクに加える．
+    pub fn apply(&self, wrench: &Wrench) -> Wrench {
+        let rotated = rotate(wrench, &self.rotation);
+        let w = rotated.to_array();
+        let moment = cross(self.translation, [w[0], w[1], w[2]]);
+        Wrench::from_array([
+            w[0],
+            w[1],
+            w[2],
+            w[3] + moment[0],
+            w[4] + moment[1],
+            w[5] + moment[2],
+        ])
+    }
+}
+
+/// センサ座標系と利用者の座標系との関係を表す，センサの取り付け方．
+/// センサをロボットに対して90°や180°回転させて取り付けた場合に，測定値を利用者の座標系で表すために用いる．
+///
+/// トルクは軸性ベクトルであるため，取り付け方は右手系どうしの回転に限る．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorMounting {
+    /// センサ座標系から利用者の座標系への回転行列．
+    rotation: [[f64; 3]; 3],
+}
+
+impl SensorMounting {
+    /// センサ座標系をそのまま利用者の座標系とする取り付け方を作る．
+    pub fn identity() -> SensorMounting {
+        SensorMounting {
+            rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    /// 軸の入れ替えと符号の反転によって取り付け方を作る．
+    /// 利用者の座標系の`i`番目の軸は，センサ座標系の`axes[i]`番目の軸に`signs[i]`を掛けたものとなる．
+    /// 例えば，センサをz軸まわりに90°回転させて取り付けた場合は`axes = [1, 0, 2]`，`signs = [-1.0, 1.0, 1.0]`とする．
+    /// # Params
+    /// 1. `axes`: 利用者の座標系のx, y, z軸に対応するセンサ座標系の軸の番号(0: x, 1: y, 2: z)．
+    /// 1. `signs`: 利用者の座標系の各軸の，対応するセンサ座標系の軸に対する符号．`1.0`または`-1.0`．
+    ///
+    /// # Panics
+    /// `axes`が0, 1, 2の並べ替えでない場合，`signs`の要素が`1.0`または`-1.0`でない場合，
+    /// または利用者の座標系が右手系とならない場合．
+    pub fn from_axes(axes: [usize; 3], signs: [f64; 3]) -> SensorMounting {
+        assert!(axes.iter().all(|&a| a < 3));
+        assert!(axes[0] != axes[1] && axes[1] != axes[2] && axes[2] != axes[0]);
+        assert!(signs.iter().all(|&s| s == 1.0 || s == -1.0));
+
+        let mut rotation = [[0.0; 3]; 3];
+        for (row, (&axis, &sign)) in rotation.iter_mut().zip(axes.iter().zip(signs.iter())) {
+            row[axis] = sign;
+        }
+        Self::from_rotation(rotation)
+    }
+
+    /// センサ座標系から利用者の座標系への回転行列によって取り付け方を作る．
+    /// # Panics
+    /// `rotation`が回転行列でない場合．
+    pub fn from_rotation(rotation: [[f64; 3]; 3]) -> SensorMounting {
+        assert!(is_rotation(&rotation));
+
+        SensorMounting { rotation }
+    }
+
+    /// センサ座標系から利用者の座標系への回転を単位四元数`[w, x, y, z]`で与えて作る．
+    /// # Panics
+    /// `quaternion`が単位四元数でない場合．
+    pub fn from_quaternion(quaternion: [f64; 4]) -> SensorMounting {
+        Self::from_rotation(quaternion_to_rotation(quaternion))
+    }
+
+    /// センサ座標系から利用者の座標系への回転行列を返す．
+    pub fn rotation(&self) -> [[f64; 3]; 3] {
+        self.rotation
+    }
+
+    /// センサ座標系で表したレンチを，利用者の座標系で表し直す．
+    pub fn apply(&self, wrench: &Wrench) -> Wrench {
+        rotate(wrench, &self.rotation)
+    }
+}
+
+impl Default for SensorMounting {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// 回転行列`rotation`によってレンチの力とトルクを回転させたレンチを返す．
+pub(crate) fn rotate(wrench: &Wrench, rotation: &[[f64; 3]; 3]) -> Wrench {
+    let w = wrench.to_array();
+    let mut rotated = [0.0; AXIS_COUNT];
+    for (row, r) in rotation.iter().enumerate() {
+        rotated[row] = r[0] * w[0] + r[1] * w[1] + r[2] * w[2];
+        rotated[row + 3] = r[0] * w[3] + r[1] * w[4] + r[2] * w[5];
+    }
+    Wrench::from_array(rotated)
+}
+
+/// 単位四元数`[w, x, y, z]`が表す回転の回転行列を返す．
+/// # Panics
+/// `quaternion`が単位四元数でない場合．
+pub(crate) fn quaternion_to_rotation(quaternion: [f64; 4]) -> [[f64; 3]; 3] {
+    let [w, x, y, z] = quaternion;
+    let norm = (w * w + x * x + y * y + z * z).sqrt();
+    assert!((norm - 1.0).abs() < UNIT_VECTOR_TOLERANCE);
+
+    [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+        ],
+        [
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+        ],
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ]
+}
+
+/// 行列が，正規直交かつ行列式が1である回転行列であれば`true`を返す．
+fn is_rotation(matrix: &[[f64; 3]; 3]) -> bool {
+    let orthonormal = (0..3).all(|i| {
+        (0..3).all(|j| {
+            let dot = (0..3).map(|k| matrix[i][k] * matrix[j][k]).sum::<f64>();
+            let expected = if i == j { 1.0 } else { 0.0 };
+            (dot - expected).abs() < UNIT_VECTOR_TOLERANCE
+        })
+    });
+    let determinant = {
+        let [a, b, c] = *matrix;
+        let [x, y, z] = cross(b, c);
+        a[0] * x + a[1] * y + a[2] * z
+    };
+    orthonormal && (determinant - 1.0).abs() < UNIT_VECTOR_TOLERANCE
+}
+
+/// レンチの力を`[fx, fy, fz]`の配列として返す．
+fn force_array(wrench: &Wrench) -> [f64; 3] {
+    [
+        wrench.force.x.value_unsafe,
+        wrench.force.y.value_unsafe,
+        wrench.force.z.value_unsafe,
+    ]
+}
+
+/// 3次元ベクトルの外積`a × b`を返す．
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// 3次元ベクトルのユークリッドノルムを返す．
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_array_close(actual: [f64; AXIS_COUNT], expected: [f64; AXIS_COUNT]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn force_is_decomposed_along_normal() {
+        // 法線はxz平面内で，x軸とz軸からそれぞれ45°傾いている
+        let n = std::f64::consts::FRAC_1_SQRT_2;
+        let decomposer = WrenchForceDecomposer::new([n, 0.0, n]);
+        let wrench = Wrench::from_array([3.0, 4.0, 1.0, 0.5, 0.5, 0.5]);
+
+        let (normal, shear) = decomposer.decompose(&wrench);
+        // 法線方向の成分は(3 + 1) / √2，せん断方向は残りの(1, 4, -1)
+        assert!((normal.value_unsafe - 4.0 * n).abs() < 1e-9);
+        let shear = [
+            shear.x.value_unsafe,
+            shear.y.value_unsafe,
+            shear.z.value_unsafe,
+        ];
+        for (s, e) in shear.iter().zip([1.0, 4.0, -1.0].iter()) {
+            assert!((s - e).abs() < 1e-9, "{:?}", shear);
+        }
+        assert!((decomposer.friction_ratio(&wrench) - 18f64.sqrt() / (4.0 * n)).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_unit_normal_is_rejected() {
+        WrenchForceDecomposer::new([0.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn gravity_torque_is_com_cross_weight() {
+        // x軸上0.1mに重心をもつ1kgの工具が，センサ座標系の-z方向の重力を受ける
+        let compensator = WrenchGravityCompensator::new(1.0, [0.1, 0.0, 0.0]);
+        let gravity = [0.0, 0.0, -9.81];
+
+        // τ = r × mg = (0.1, 0, 0) × (0, 0, -9.81) = (0, 0.981, 0)
+        let weight = compensator.gravity_wrench(gravity);
+        assert_array_close(weight.to_array(), [0.0, 0.0, -9.81, 0.0, 0.981, 0.0]);
+
+        let measured = Wrench::from_array([1.0, 2.0, -6.81, 0.5, 1.981, -0.5]);
+        let compensated = compensator.compensate(measured, gravity);
+        assert_array_close(compensated.to_array(), [1.0, 2.0, 3.0, 0.5, 1.0, -0.5]);
+    }
+}
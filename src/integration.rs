@@ -0,0 +1,118 @@
+//! レンチの時間積分を求めるためのユーティリティ．
+
+use crate::{Wrench, AXIS_COUNT};
+use std::time::Instant;
+
+/// `start`から`stop`までの間に与えたレンチを時間積分し，力積と仕事を求める．
+/// 打撃試験や，挿入作業の力の品質評価に利用できる．
+///
+/// 積分は台形則によって行う．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchIntegrator {
+    /// 積分中かどうか．
+    running: bool,
+    /// 直前に与えたサンプルの時刻，レンチ，仕事率．
+    previous: Option<(Instant, [f64; AXIS_COUNT], f64)>,
+    /// 力積[N·s]と角力積[N·m·s]．
+    impulse: [f64; AXIS_COUNT],
+    /// 仕事[J]．
+    work: f64,
+}
+
+impl WrenchIntegrator {
+    /// 積分を行っていない状態で作る．
+    pub fn new() -> WrenchIntegrator {
+        WrenchIntegrator {
+            running: false,
+            previous: None,
+            impulse: [0.0; AXIS_COUNT],
+            work: 0.0,
+        }
+    }
+
+    /// これまでの積分値を捨てて，積分を始める．
+    pub fn start(&mut self) {
+        self.reset();
+        self.running = true;
+    }
+
+    /// 積分を止める．積分値はそのまま保たれる．
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.previous = None;
+    }
+
+    /// 積分中であれば`true`を返す．
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// 新しいレンチとその取得時刻を与えて，力積を積分する．仕事は積分しない．
+    /// 積分中でなければ何もしない．
+    pub fn update(&mut self, wrench: Wrench, timestamp: Instant) {
+        self.integrate(wrench.to_array(), 0.0, timestamp);
+    }
+
+    /// 新しいレンチとその取得時刻に加えて，センサの速度を与えて力積と仕事を積分する．
+    /// 積分中でなければ何もしない．
+    /// # Params
+    /// 1. `wrench`: センサで測定したレンチ．
+    /// 1. `twist`: センサ座標系で表したセンサの並進速度[m/s]と角速度[rad/s]を`[vx, vy, vz, wx, wy, wz]`の順に並べたもの．
+    /// 1. `timestamp`: `wrench`を取得した時刻．
+    pub fn update_with_twist(
+        &mut self,
+        wrench: Wrench,
+        twist: [f64; AXIS_COUNT],
+        timestamp: Instant,
+    ) {
+        let wrench = wrench.to_array();
+        let power = wrench.iter().zip(twist.iter()).map(|(w, t)| w * t).sum();
+        self.integrate(wrench, power, timestamp);
+    }
+
+    /// 力積[N·s]と角力積[N·m·s]を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn impulse(&self) -> [f64; AXIS_COUNT] {
+        self.impulse
+    }
+
+    /// 仕事[J]を返す．`update_with_twist`で速度を与えた区間のみが積分される．
+    pub fn work(&self) -> f64 {
+        self.work
+    }
+
+    /// 積分値を捨てる．積分中であれば，次に与えたサンプルから改めて積分を始める．
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.impulse = [0.0; AXIS_COUNT];
+        self.work = 0.0;
+    }
+
+    /// 積分中であれば，直前のサンプルとの間を台形則で積分する．
+    fn integrate(&mut self, wrench: [f64; AXIS_COUNT], power: f64, timestamp: Instant) {
+        if !self.running {
+            return;
+        }
+
+        if let Some((previous_time, previous_wrench, previous_power)) = self.previous {
+            let dt = timestamp
+                .saturating_duration_since(previous_time)
+                .as_secs_f64();
+            for ((i, w), p) in self
+                .impulse
+                .iter_mut()
+                .zip(wrench.iter())
+                .zip(previous_wrench.iter())
+            {
+                *i += (w + p) / 2.0 * dt;
+            }
+            self.work += (power + previous_power) / 2.0 * dt;
+        }
+        self.previous = Some((timestamp, wrench, power));
+    }
+}
+
+impl Default for WrenchIntegrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
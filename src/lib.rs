@@ -1,15 +1,179 @@
 //! ワコーテック製6軸力覚センサと通信するためのライブラリ．
 
+use calibration::CalibrationAccumulator;
+use diagnostics::DiagnosticsCounter;
 use dimensioned::si::Unitless;
 pub use dimensioned::si::{Meter, Newton};
 use dimensioned::typenum::{Prod, Quot};
 pub use pair_macro::Triplet;
+use protocol::{
+    FrameError, RawReading, DIGITAL_OUTPUT_MAX, NEWLINE, NEWLINE_BYTES, RESPONSE_BYTES,
+    SENSITIVITY_COMMAND, STREAM_COMMAND, VERSION_COMMAND, ZERO_COMMAND,
+};
+use std::collections::VecDeque;
 use std::fmt::{self, Display, Formatter};
-use std::ops::{Add, Sub};
-use std::time::Duration;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(feature = "approx")]
+mod approx_support;
+#[cfg(feature = "tokio")]
+mod async_sensor;
+mod binary_log;
+mod buffer;
+mod builder;
+mod calibration;
+mod calibration_file;
+#[cfg(feature = "tokio")]
+mod codec;
+mod control;
+mod decoupling;
+mod derivative;
+mod detection;
+mod device;
+mod diagnostics;
+mod display;
+mod drift;
+pub mod filter;
+mod geometry;
+mod integration;
+mod logging;
+mod mcap;
+mod measurement;
+mod mixer;
+#[cfg(feature = "mock")]
+mod mock;
+mod model;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_support;
+mod plain_wrench;
+pub mod protocol;
+mod replay;
+#[cfg(feature = "ros")]
+mod ros_support;
+mod rotation;
+mod safety;
+mod sampling;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod serialization;
+#[cfg(feature = "fft")]
+mod spectrum;
+mod statistics;
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(test)]
+mod test_port;
+mod timing;
+mod udp;
+#[cfg(feature = "uom")]
+mod uom_support;
+mod watchdog;
+mod wrench_f32;
+
+#[cfg(feature = "tokio")]
+pub use async_sensor::{AsyncDynPick, AsyncWdf6m200};
+pub use binary_log::{BinaryLogReader, BinaryLogRecord, BinaryLogWriter};
+pub use buffer::WrenchRingBuffer;
+pub use builder::{DynPickBuilder, Wdf6m200Builder};
+pub use calibration::{CalibrationReport, WrenchEwmaCalibrator};
+pub use calibration_file::SavedCalibration;
+#[cfg(feature = "tokio")]
+pub use codec::DynPickCodec;
+pub use control::WrenchAdmittanceController;
+pub use decoupling::CalibrationMatrix;
+pub use derivative::WrenchDifferentiator;
+pub use detection::{
+    AlarmEvent, ContactEvent, ContactEventDetector, WrenchContactDetector, WrenchHysteresis,
+    WrenchMagnitudeAlarm, WrenchPeak, WrenchPeakDetector, WrenchSlipDetector,
+};
+pub use device::{DeviceInfo, FilterLevel};
+pub use diagnostics::DiagnosticsReport;
+pub use display::{WrenchDisplay, WrenchDisplayOptions};
+pub use drift::WrenchDriftCompensator;
+pub use filter::{
+    ButterworthOrder, WrenchBandPassFilter, WrenchButterworthFilter, WrenchDeadband,
+    WrenchExponentialSmoothing, WrenchFIRFilter, WrenchFilter, WrenchHampelFilter,
+    WrenchMedianFilter, WrenchMovingAverage, WrenchNotchFilter, WrenchPipeline,
+    WrenchPipelineBuilder,
+};
+pub use geometry::{
+    ContactPoint, FrameTransform, SensorMounting, WrenchContactPointEstimator,
+    WrenchForceDecomposer, WrenchGravityCompensator, WrenchSafetyZone, STANDARD_GRAVITY,
+};
+pub use integration::WrenchIntegrator;
+#[cfg(feature = "json")]
+pub use logging::JsonLinesLogger;
+pub use logging::{CsvLogger, MeasurementSink};
+pub use mcap::McapExporter;
+pub use measurement::Measurement;
+pub use mixer::{WrenchDifferentialFilter, WrenchMixer};
+#[cfg(feature = "mock")]
+pub use mock::MockSensor;
+pub use model::{RatedLoad, SensorModel, SensorSpec, Wdf6m200Spec, Wdf6m200_14Spec, Wef6a200Spec};
+#[cfg(feature = "nalgebra")]
+pub use nalgebra_support::{triplet_to_vector3, vector3_to_triplet};
+pub use plain_wrench::PlainWrench;
+pub use protocol::STOP_COMMAND;
+pub use replay::ReplaySensor;
+pub use rotation::{LogFile, RotatingLogger, RotationPolicy};
+pub use safety::{SafetyMonitor, SafetyViolation};
+pub use sampling::SampledSensor;
+pub use serialization::{ParseError, WrenchXmlSerializer};
+#[cfg(feature = "fft")]
+pub use spectrum::{SpectrumResult, WrenchFrequencyAnalyzer};
+pub use statistics::{
+    WrenchCovariance, WrenchStats, WrenchStatsSummary, WrenchWindowedStats, WrenchWindowedVariance,
+};
+#[cfg(feature = "tokio")]
+pub use stream::WrenchStream;
+pub use timing::WrenchSamplingScheduler;
+pub use udp::{UdpPublisher, WrenchPacket, WRENCH_PACKET_BYTES};
+#[cfg(feature = "uom")]
+pub use uom_support::{
+    force_to_newton, newton_meter_to_torque, newton_to_force, torque_to_newton_meter,
+};
+pub use watchdog::DataStallWatchdog;
+pub use wrench_f32::WrenchF32;
 
 pub type NewtonMeter<T> = Prod<Newton<T>, Meter<T>>;
 
+/// レンチの成分の軸を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// x方向の力．
+    Fx,
+    /// y方向の力．
+    Fy,
+    /// z方向の力．
+    Fz,
+    /// x軸まわりのトルク．
+    Tx,
+    /// y軸まわりのトルク．
+    Ty,
+    /// z軸まわりのトルク．
+    Tz,
+}
+
+impl Axis {
+    /// すべての軸を`[fx, fy, fz, tx, ty, tz]`の順に並べたもの．
+    pub const ALL: [Axis; AXIS_COUNT] =
+        [Axis::Fx, Axis::Fy, Axis::Fz, Axis::Tx, Axis::Ty, Axis::Tz];
+
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べたときの，この軸の位置を返す．
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// この軸が力の軸であれば`true`を返す．
+    pub const fn is_force(self) -> bool {
+        matches!(self, Axis::Fx | Axis::Fy | Axis::Fz)
+    }
+}
+
 /// レンチ(力とトルクのペア)を表す．
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Wrench {
@@ -30,6 +194,138 @@ impl Wrench {
         let torque = Triplet::from_cloned(0.0).map(NewtonMeter::<f64>::new);
         Wrench { force, torque }
     }
+
+    /// 力の各成分に関数を適用した`Wrench`を返す．トルクはそのまま保たれる．
+    pub fn map_force<F: Fn(Newton<f64>) -> Newton<f64>>(&self, f: F) -> Wrench {
+        Wrench::new(self.force.map(f), self.torque)
+    }
+
+    /// トルクの各成分に関数を適用した`Wrench`を返す．力はそのまま保たれる．
+    pub fn map_torque<F: Fn(NewtonMeter<f64>) -> NewtonMeter<f64>>(&self, f: F) -> Wrench {
+        Wrench::new(self.force, self.torque.map(f))
+    }
+
+    /// 力の各成分に関数を適用した`Wrench`を返す．
+    /// 関数には，成分のほかに軸の番号(x,y,zの順に0,1,2)が渡される．
+    pub fn map_force_with_index<F: Fn(usize, Newton<f64>) -> Newton<f64>>(&self, f: F) -> Wrench {
+        let Triplet { x, y, z } = self.force;
+        let force = Triplet::new(f(0, x), f(1, y), f(2, z));
+        Wrench::new(force, self.torque)
+    }
+
+    /// トルクの各成分に関数を適用した`Wrench`を返す．
+    /// 関数には，成分のほかに軸の番号(x,y,zの順に0,1,2)が渡される．
+    pub fn map_torque_with_index<F: Fn(usize, NewtonMeter<f64>) -> NewtonMeter<f64>>(
+        &self,
+        f: F,
+    ) -> Wrench {
+        let Triplet { x, y, z } = self.torque;
+        let torque = Triplet::new(f(0, x), f(1, y), f(2, z));
+        Wrench::new(self.force, torque)
+    }
+
+    /// 指定した書式でこのレンチを表示するための値を返す．
+    /// 既定の書式で表示する場合は，`Wrench`の`Display`をそのまま用いればよい．
+    pub fn display_with(&self, options: WrenchDisplayOptions) -> WrenchDisplay {
+        WrenchDisplay::new(*self, options)
+    }
+
+    /// 力のユークリッドノルムを返す．
+    pub fn force_norm(&self) -> Newton<f64> {
+        let [fx, fy, fz, ..] = self.to_array();
+        Newton::new((fx * fx + fy * fy + fz * fz).sqrt())
+    }
+
+    /// トルクのユークリッドノルムを返す．
+    pub fn torque_norm(&self) -> NewtonMeter<f64> {
+        let [.., tx, ty, tz] = self.to_array();
+        NewtonMeter::<f64>::new((tx * tx + ty * ty + tz * tz).sqrt())
+    }
+
+    /// 力とトルクをあわせたレンチ全体の大きさを返す．
+    /// トルクを代表長さで割って力の次元にそろえ，`sqrt(|f|² + (|τ| / length)²)`として求める．
+    /// # Params
+    /// 1. `characteristic_length`: トルクを力に換算する代表長さ．工具の長さなど，作業の規模に合わせて選ぶ．
+    ///
+    /// # Panics
+    /// `characteristic_length`が正でない場合．
+    pub fn weighted_norm(&self, characteristic_length: Meter<f64>) -> Newton<f64> {
+        let length = characteristic_length.value_unsafe;
+        assert!(length > 0.0);
+
+        let force = self.force_norm().value_unsafe;
+        let torque = self.torque_norm().value_unsafe / length;
+        Newton::new((force * force + torque * torque).sqrt())
+    }
+
+    /// 各軸の成分の大きさが，定格荷重に対してどの程度の割合に達しているかを`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// 定格荷重以内であれば0以上1以下となり，1を超える軸は定格荷重を超えている．
+    /// 監視や，把持作業などで定格荷重に余裕をもたせる場合に利用できる．
+    pub fn utilization(&self, rated: &RatedLoad) -> [f64; AXIS_COUNT] {
+        let mut utilization = self.to_array();
+        for (u, r) in utilization.iter_mut().zip(rated.to_array().iter()) {
+            *u = u.abs() / r;
+        }
+        utilization
+    }
+
+    /// このレンチを，`transform`が表す別の座標系の原点にはたらくレンチとして表し直す．
+    /// 工具先端やロボットのフランジにはたらくレンチを求める場合に利用できる．
+    /// 詳細は`FrameTransform::apply`を参照．
+    pub fn transform(&self, transform: &FrameTransform) -> Wrench {
+        transform.apply(self)
+    }
+
+    /// 向きの異なる座標系でこのレンチを表し直す．原点の移動は伴わない．
+    /// # Params
+    /// 1. `rotation`: 現在の座標系から新しい座標系への回転行列．
+    pub fn rotated(&self, rotation: &[[f64; 3]; 3]) -> Wrench {
+        geometry::rotate(self, rotation)
+    }
+
+    /// 向きの異なる座標系でこのレンチを表し直す．原点の移動は伴わない．
+    /// # Params
+    /// 1. `quaternion`: 現在の座標系から新しい座標系への回転を表す単位四元数`[w, x, y, z]`．
+    ///
+    /// # Panics
+    /// `quaternion`が単位四元数でない場合．
+    pub fn rotated_by_quaternion(&self, quaternion: [f64; 4]) -> Wrench {
+        self.rotated(&geometry::quaternion_to_rotation(quaternion))
+    }
+
+    /// 向きの異なる座標系でこのレンチを表し直す．原点の移動は伴わない．
+    /// # Params
+    /// 1. `rotation`: 現在の座標系から新しい座標系への回転．
+    #[cfg(feature = "nalgebra")]
+    pub fn rotated_by_unit_quaternion(&self, rotation: &nalgebra::UnitQuaternion<f64>) -> Wrench {
+        let matrix = rotation.to_rotation_matrix();
+        let mut array = [[0.0; 3]; 3];
+        for (i, row) in array.iter_mut().enumerate() {
+            for (j, e) in row.iter_mut().enumerate() {
+                *e = matrix[(i, j)];
+            }
+        }
+        self.rotated(&array)
+    }
+
+    /// 力とトルクの各成分をSI単位の値として`[fx, fy, fz, tx, ty, tz]`の順に並べた配列を返す．
+    pub fn to_array(self) -> [f64; AXIS_COUNT] {
+        [
+            self.force.x.value_unsafe,
+            self.force.y.value_unsafe,
+            self.force.z.value_unsafe,
+            self.torque.x.value_unsafe,
+            self.torque.y.value_unsafe,
+            self.torque.z.value_unsafe,
+        ]
+    }
+
+    /// SI単位の値を`[fx, fy, fz, tx, ty, tz]`の順に並べた配列から`Wrench`を作る．
+    pub fn from_array(array: [f64; AXIS_COUNT]) -> Wrench {
+        let force = Triplet::new(array[0], array[1], array[2]).map(Newton::new);
+        let torque = Triplet::new(array[3], array[4], array[5]).map(NewtonMeter::<f64>::new);
+        Wrench { force, torque }
+    }
 }
 
 impl Add for Wrench {
@@ -45,187 +341,1527 @@ impl Add for Wrench {
 impl Sub for Wrench {
     type Output = Wrench;
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        let force = self.force - rhs.force;
-        let torque = self.torque - rhs.torque;
-        Wrench { force, torque }
+    fn sub(self, rhs: Self) -> Self::Output {
+        let force = self.force - rhs.force;
+        let torque = self.torque - rhs.torque;
+        Wrench { force, torque }
+    }
+}
+
+impl AddAssign for Wrench {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Wrench {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f64> for Wrench {
+    type Output = Wrench;
+
+    /// 力とトルクの各成分を`rhs`倍する．
+    fn mul(self, rhs: f64) -> Self::Output {
+        let force = self.force.map(|e| e * rhs);
+        let torque = self.torque.map(|e| e * rhs);
+        Wrench { force, torque }
+    }
+}
+
+impl Mul<Wrench> for f64 {
+    type Output = Wrench;
+
+    /// 力とトルクの各成分を`self`倍する．
+    fn mul(self, rhs: Wrench) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<f64> for Wrench {
+    type Output = Wrench;
+
+    /// 力とトルクの各成分を`rhs`で割る．
+    fn div(self, rhs: f64) -> Self::Output {
+        let force = self.force.map(|e| e / rhs);
+        let torque = self.torque.map(|e| e / rhs);
+        Wrench { force, torque }
+    }
+}
+
+impl Neg for Wrench {
+    type Output = Wrench;
+
+    fn neg(self) -> Self::Output {
+        self * -1.0
+    }
+}
+
+impl Sum for Wrench {
+    fn sum<I: Iterator<Item = Wrench>>(iter: I) -> Self {
+        iter.fold(Wrench::zeroed(), |acc, cur| acc + cur)
+    }
+}
+
+impl<'a> Sum<&'a Wrench> for Wrench {
+    fn sum<I: Iterator<Item = &'a Wrench>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl From<Wrench> for [f64; AXIS_COUNT] {
+    /// 各成分をSI単位の値として`[fx, fy, fz, tx, ty, tz]`の順に並べる．
+    fn from(wrench: Wrench) -> Self {
+        wrench.to_array()
+    }
+}
+
+impl From<[f64; AXIS_COUNT]> for Wrench {
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べたSI単位の値から`Wrench`を作る．
+    fn from(array: [f64; AXIS_COUNT]) -> Self {
+        Wrench::from_array(array)
+    }
+}
+
+impl From<Wrench> for (f64, f64, f64, f64, f64, f64) {
+    /// 各成分をSI単位の値として`(fx, fy, fz, tx, ty, tz)`の順に並べる．
+    fn from(wrench: Wrench) -> Self {
+        let [fx, fy, fz, tx, ty, tz] = wrench.to_array();
+        (fx, fy, fz, tx, ty, tz)
+    }
+}
+
+impl From<(f64, f64, f64, f64, f64, f64)> for Wrench {
+    /// SI単位の値として`(fx, fy, fz, tx, ty, tz)`の順に並べた各成分から作る．
+    fn from((fx, fy, fz, tx, ty, tz): (f64, f64, f64, f64, f64, f64)) -> Self {
+        Wrench::from_array([fx, fy, fz, tx, ty, tz])
+    }
+}
+
+impl From<Wrench> for (Triplet<Newton<f64>>, Triplet<NewtonMeter<f64>>) {
+    fn from(wrench: Wrench) -> Self {
+        (wrench.force, wrench.torque)
+    }
+}
+
+impl From<(Triplet<Newton<f64>>, Triplet<NewtonMeter<f64>>)> for Wrench {
+    fn from((force, torque): (Triplet<Newton<f64>>, Triplet<NewtonMeter<f64>>)) -> Self {
+        Wrench { force, torque }
+    }
+}
+
+/// 測定値を受信するたびに呼び出されるコールバック．
+type RecordCallback = Box<dyn Fn(&Wrench) + Send>;
+
+/// 力とトルクの各軸について，それぞれ1N，1Nmあたりデジタル出力値がいくつ変化するか．
+type Sensitivity = (Triplet<PerNewton<f64>>, Triplet<PerNewtonMeter<f64>>);
+
+/// Wacohtech DynPickシリーズの6軸力覚センサのドライバ．
+/// 型式ごとの感度などの仕様は`S`によって与えられる．
+pub struct DynPick<S: SensorSpec> {
+    /// センサに接続されたシリアルポート．
+    serial_port: Box<dyn serialport::SerialPort>,
+    /// 現在のセンサ出力値．
+    raw_wrench: Wrench,
+    /// 現在のセンサ出力値の，換算前の各軸のデジタル出力値．
+    raw_counts: [u16; AXIS_COUNT],
+    /// センサ出力値から減ずる補正値．
+    /// センサは力がはたらいていない場合も0ではない出力を出す．
+    /// そのため，センサからの生の出力からこのオフセット値を減じて補正してやる必要がある．
+    offset: Wrench,
+    /// 測定値を受信するたびに呼び出されるコールバック．
+    record_callback: Option<RecordCallback>,
+    /// 最後にセンサへ出力値の送信を要求した時刻．
+    last_request_time: Option<Instant>,
+    /// 接続時に設定した読み取り操作のタイムアウト．
+    read_timeout: Duration,
+    /// 直近の通信遅延の記録．古いものから順に並んでいる．
+    latency_history: VecDeque<Duration>,
+    /// 通信状態の診断のための記録．
+    diagnostics: DiagnosticsCounter,
+    /// 書き込み操作のタイムアウト．`None`の場合は読み取り操作と同じタイムアウトを用いる．
+    write_timeout: Option<Duration>,
+    /// センサが連続送信モードで動作しているかどうか．
+    streaming: bool,
+    /// 最後に`device_info`で読み出した機器情報．
+    device_info: Option<DeviceInfo>,
+    /// このインスタンスから最後にセンサに設定した内蔵フィルタ．
+    device_filter: Option<FilterLevel>,
+    /// 要求済みでまだ受信していない出力値があるかどうか．
+    request_pending: bool,
+    /// `update`のたびに，受信の前に古いデータを捨てるかどうか．
+    auto_drain: bool,
+    /// 最後に受信した出力値について，過負荷となっている軸．
+    last_overload: [bool; AXIS_COUNT],
+    /// 過負荷を検出した際に，`update`がエラーを返すかどうか．
+    overload_error: bool,
+    /// 最後に出力値を受信した時刻．
+    last_received_time: Option<(Instant, SystemTime)>,
+    /// 次に受信する出力値に付与する通し番号．
+    next_sequence: u64,
+    /// 最後に受信したフレームのレコード番号．
+    last_record_number: Option<u8>,
+    /// 最後に受信したフレームの直前に取りこぼしたフレームの数．
+    last_dropped_frames: u8,
+    /// 受信したが，まだ読み出していないバイト列．
+    /// フレームの区切りを再同期した際に，次のフレームの先頭部分を持ち越すために用いる．
+    unread_bytes: VecDeque<u8>,
+    /// デジタル出力値の換算に用いる，力とトルクの各軸の感度．
+    /// 型式が感度の既定値を持たず，まだ感度を設定していない場合は`None`．
+    sensitivity: Option<Sensitivity>,
+    /// 軸間の干渉を補正するための校正行列．
+    /// `Some`の場合は，各軸の感度の代わりにこの行列を用いてデジタル出力値を換算する．
+    calibration_matrix: Option<CalibrationMatrix>,
+    /// 現在のゼロ点を求めたキャリブレーションの時刻と，用いた測定値の個数．
+    /// ゼロ点をキャリブレーションによらず設定した場合は`None`．
+    calibration_metadata: Option<(SystemTime, usize)>,
+    /// ゼロ点のドリフトを補償する補償器．`None`の場合は補償しない．
+    drift_compensator: Option<WrenchDriftCompensator>,
+    /// 測定値から工具の重力の影響を取り除く補償器．`None`の場合は取り除かない．
+    gravity_compensator: Option<WrenchGravityCompensator>,
+    /// センサ座標系で表した，現在の重力加速度[m/s²]．
+    gravity_in_sensor_frame: [f64; 3],
+    /// 測定値を受信するたびに許容範囲の超過を判定する監視器．`None`の場合は判定しない．
+    safety_monitor: Option<SafetyMonitor>,
+    /// データの途絶を検出する監視器．`None`の場合は検出しない．
+    watchdog: Option<DataStallWatchdog>,
+    /// センサの取り付け方．測定値をセンサ座標系から利用者の座標系に変換するために用いる．
+    mounting: SensorMounting,
+    /// 接続しているセンサの仕様．
+    spec: PhantomData<fn() -> S>,
+}
+
+/// WDF-6M200-3 Wacohtech 6-axis force/touque sensor
+pub type Wdf6m200 = DynPick<Wdf6m200Spec>;
+
+/// WDF-6M200-14 Wacohtech 6-axis force/touque sensor
+///
+/// 感度の既定値を持たないため，最初の`update`の前に`read_device_sensitivity`か`set_sensitivity`で感度を設定すること．
+pub type Wdf6m200_14 = DynPick<Wdf6m200_14Spec>;
+
+/// WEF-6A200-4 Wacohtech 6-axis force/touque sensor
+///
+/// 感度の既定値を持たないため，最初の`update`の前に`read_device_sensitivity`か`set_sensitivity`で感度を設定すること．
+pub type Wef6a200 = DynPick<Wef6a200Spec>;
+
+/// PCに接続されている力覚センサのデバイス情報．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensorPortInfo {
+    /// センサが接続されたシリアルポートへのパス．
+    pub path: PathBuf,
+    /// USBデバイスのシリアル番号．取得できなかった場合は`None`．
+    pub serial_number: Option<String>,
+    /// USBデバイスのベンダID．
+    pub vendor_id: u16,
+    /// USBデバイスのプロダクトID．
+    pub product_id: u16,
+}
+
+impl<S: SensorSpec> DynPick<S> {
+    /// コンピュータに接続されたセンサとの通信を確立する．
+    /// # Params
+    /// 1. `read_timeout_duration`: シリアル通信の読み取り操作がこの時間経過しても完了していない場合，タイムアウトとなる．
+    ///
+    /// # Returns
+    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub fn open(read_timeout_duration: Duration) -> Result<DynPick<S>, SensorError> {
+        let sensor_port_path = find_sensor_port()?;
+        Self::open_path(sensor_port_path, read_timeout_duration)
+    }
+
+    /// PCに接続されている力覚センサの情報をすべて返す．
+    /// `list_sensors`と同じ．
+    pub fn find_all() -> Vec<SensorPortInfo> {
+        list_sensors()
+    }
+
+    /// 指定したシリアル番号を持つセンサとの通信を確立する．
+    /// 同じ型式のセンサが複数接続されている場合に，それぞれを区別して通信するために用いる．
+    /// # Params
+    /// 1. `serial_number`: センサのUSBデバイスのシリアル番号．
+    /// 1. `read_timeout_duration`: シリアル通信の読み取り操作がこの時間経過しても完了していない場合，タイムアウトとなる．
+    ///
+    /// # Returns
+    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
+    /// 指定したシリアル番号を持つセンサが見つからない場合は`Err(SensorError::SensorNotFound)`を返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub fn open_by_serial_number(
+        serial_number: &str,
+        read_timeout_duration: Duration,
+    ) -> Result<DynPick<S>, SensorError> {
+        let sensor_port_path =
+            find_sensor_port_matching(|port| port.serial_number.as_deref() == Some(serial_number))?;
+        Self::open_path(sensor_port_path, read_timeout_duration)
+    }
+
+    /// デバイスの探索を行わず，指定したシリアルポートに接続されたセンサとの通信を確立する．
+    /// # Params
+    /// 1. `path`: センサが接続されたシリアルポートへのパス．`/dev/ttyUSB0`や`COM3`など．
+    /// 1. `read_timeout_duration`: シリアル通信の読み取り操作がこの時間経過しても完了していない場合，タイムアウトとなる．
+    ///
+    /// # Returns
+    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub fn open_path<P: AsRef<Path>>(
+        path: P,
+        read_timeout_duration: Duration,
+    ) -> Result<DynPick<S>, SensorError> {
+        Self::connect(path.as_ref(), BAUD_RATE, read_timeout_duration, None)
+    }
+
+    /// 通信設定を細かく指定してセンサとの通信を確立するためのビルダを返す．
+    pub fn builder() -> DynPickBuilder<S> {
+        DynPickBuilder::new()
+    }
+
+    /// 指定したシリアルポートに接続されたセンサとの通信を確立する．
+    /// `write_timeout_duration`が`None`の場合，書き込み操作にも`read_timeout_duration`を用いる．
+    pub(crate) fn connect(
+        path: &Path,
+        baud_rate: u32,
+        read_timeout_duration: Duration,
+        write_timeout_duration: Option<Duration>,
+    ) -> Result<DynPick<S>, SensorError> {
+        // ハードウェアの仕様に合わせて通信設定を作る．
+        // センサの仕様書を見て，ここの通信設定を決めた．
+        let settings = serialport::SerialPortSettings {
+            baud_rate,
+            data_bits: serialport::DataBits::Eight,
+            flow_control: serialport::FlowControl::None,
+            parity: serialport::Parity::None,
+            stop_bits: serialport::StopBits::One,
+            timeout: read_timeout_duration,
+        };
+
+        // シリアル通信確立
+        let serial_port = serialport::open_with_settings(path.as_os_str(), &settings)?;
+
+        Self::from_serial_port(serial_port, write_timeout_duration)
+    }
+
+    /// 通信を確立済みのシリアルポートを用いてセンサとの通信を始める．
+    /// 読み取り操作のタイムアウトには，シリアルポートに設定されているものを用いる．
+    pub(crate) fn from_serial_port(
+        serial_port: Box<dyn serialport::SerialPort>,
+        write_timeout_duration: Option<Duration>,
+    ) -> Result<DynPick<S>, SensorError> {
+        let mut sensor = Self {
+            read_timeout: serial_port.timeout(),
+            serial_port,
+            raw_wrench: Wrench::zeroed(),
+            raw_counts: [0; AXIS_COUNT],
+            offset: Wrench::zeroed(),
+            record_callback: None,
+            last_request_time: None,
+            latency_history: VecDeque::with_capacity(LATENCY_HISTORY_LENGTH),
+            diagnostics: DiagnosticsCounter::default(),
+            write_timeout: write_timeout_duration,
+            streaming: false,
+            device_info: None,
+            device_filter: None,
+            request_pending: false,
+            auto_drain: false,
+            last_overload: [false; AXIS_COUNT],
+            overload_error: false,
+            last_received_time: None,
+            next_sequence: 0,
+            last_record_number: None,
+            last_dropped_frames: 0,
+            unread_bytes: VecDeque::with_capacity(RESPONSE_BYTES),
+            sensitivity: Self::default_sensitivity(),
+            calibration_matrix: None,
+            calibration_metadata: None,
+            drift_compensator: None,
+            gravity_compensator: None,
+            gravity_in_sensor_frame: [0.0, 0.0, -STANDARD_GRAVITY],
+            safety_monitor: None,
+            watchdog: None,
+            mounting: SensorMounting::identity(),
+            spec: PhantomData,
+        };
+
+        // 最初のupdate()に備えて，データを送信するようにセンサに要求する
+        sensor.request_next_data()?;
+
+        Ok(sensor)
+    }
+
+    /// 接続しているセンサの型式を返す．
+    /// 型式は，デジタル出力値を力やトルクに換算する際の感度の決定に用いられる．
+    pub fn model(&self) -> SensorModel {
+        S::MODEL
+    }
+
+    /// デジタル出力値から力への換算に用いている，各軸の感度を返す．
+    /// 感度が定まっていない場合は`None`を返す．
+    pub fn force_sensitivity(&self) -> Option<Triplet<PerNewton<f64>>> {
+        self.sensitivity.map(|(force, _)| force)
+    }
+
+    /// デジタル出力値からトルクへの換算に用いている，各軸の感度を返す．
+    /// 感度が定まっていない場合は`None`を返す．
+    pub fn torque_sensitivity(&self) -> Option<Triplet<PerNewtonMeter<f64>>> {
+        self.sensitivity.map(|(_, torque)| torque)
+    }
+
+    /// デジタル出力値の換算に用いる各軸の感度を設定する．
+    /// 独自に再キャリブレーションを行った場合などに利用できる．
+    /// # Params
+    /// 1. `force`: 力の各軸について，1Nあたりデジタル出力値がいくつ変化するか．
+    /// 1. `torque`: トルクの各軸について，1Nmあたりデジタル出力値がいくつ変化するか．
+    ///
+    /// # Panics
+    /// いずれかの感度が0，または有限でない場合．
+    pub fn set_sensitivity(
+        &mut self,
+        force: Triplet<PerNewton<f64>>,
+        torque: Triplet<PerNewtonMeter<f64>>,
+    ) {
+        assert_valid_sensitivity(force, torque);
+        self.sensitivity = Some((force, torque));
+    }
+
+    /// デジタル出力値の換算に用いる校正行列を設定する．
+    /// 設定した場合，各軸の感度の代わりに校正行列を用いて換算する．
+    /// `None`を与えると，再び各軸の感度を用いて換算する．
+    pub fn set_calibration_matrix(&mut self, matrix: Option<CalibrationMatrix>) {
+        self.calibration_matrix = matrix;
+    }
+
+    /// デジタル出力値の換算に用いている校正行列を返す．
+    /// 各軸の感度を用いて換算している場合は`None`を返す．
+    pub fn calibration_matrix(&self) -> Option<&CalibrationMatrix> {
+        self.calibration_matrix.as_ref()
+    }
+
+    /// デジタル出力値の換算に用いる各軸の感度を，センサの型式ごとの代表値に戻す．
+    /// 型式が感度の既定値を持たない場合は，感度が定まっていない状態に戻る．
+    pub fn reset_sensitivity(&mut self) {
+        self.sensitivity = Self::default_sensitivity();
+    }
+
+    /// センサの型式ごとの感度の代表値を返す．型式が既定値を持たない場合は`None`を返す．
+    fn default_sensitivity() -> Option<Sensitivity> {
+        Some((S::force_sensitivity()?, S::torque_sensitivity()?))
+    }
+
+    /// センサに書き込まれている個体ごとの感度を読み出し，以降のデジタル出力値の換算に用いる．
+    /// 型式ごとの代表値よりも正確な測定値が得られる．
+    ///
+    /// # Returns
+    /// 感度を読み出せた場合は`Ok(())`を返す．
+    /// 読み出した感度のいずれかが0，または有限でない場合は`Err(SensorError::InvalidSensitivity)`を返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    /// エラーを返した場合，換算に用いる感度は変更されない．
+    pub fn read_device_sensitivity(&mut self) -> Result<(), SensorError> {
+        let response = self.query(SENSITIVITY_COMMAND)?;
+
+        // 応答は各軸の感度を[fx, fy, fz, tx, ty, tz]の順にカンマ区切りで並べた文字列
+        let mut sensitivity = [0.0; AXIS_COUNT];
+        let mut values = response.split(',');
+        for s in sensitivity.iter_mut() {
+            let value = values.next().ok_or(SensorError::InvalidTextLength)?;
+            *s = value.trim().parse()?;
+        }
+        if values.next().is_some() {
+            return Err(SensorError::InvalidTextLength);
+        }
+        // 0や有限でない感度で換算すると，測定値が無限大や非数になってしまう
+        if !sensitivity.iter().all(|&s| is_valid_sensitivity(s)) {
+            return Err(SensorError::InvalidSensitivity(sensitivity));
+        }
+
+        let force =
+            Triplet::new(sensitivity[0], sensitivity[1], sensitivity[2]).map(PerNewton::<f64>::new);
+        let torque = Triplet::new(sensitivity[3], sensitivity[4], sensitivity[5])
+            .map(PerNewtonMeter::<f64>::new);
+        self.sensitivity = Some((force, torque));
+        Ok(())
+    }
+
+    /// センサ本体に内蔵された移動平均フィルタを設定する．
+    /// ホスト側でフィルタ処理を行わずに，測定値の遅れとノイズの大きさを調整できる．
+    /// 設定は，これ以降にセンサが送信する出力値に反映される．
+    pub fn set_device_filter(&mut self, level: FilterLevel) -> Result<(), SensorError> {
+        self.send_setting_command(&protocol::encode_filter_setting(level))?;
+        self.device_filter = Some(level);
+        Ok(())
+    }
+
+    /// `set_device_filter`で最後に設定した内蔵フィルタを返す．
+    /// このインスタンスからまだ設定していない場合は`None`を返す．
+    pub fn device_filter(&self) -> Option<FilterLevel> {
+        self.device_filter
+    }
+
+    /// センサ本体から型式名，ファームウェアのバージョン，シリアル番号を読み出す．
+    ///
+    /// # Returns
+    /// 読み出せた場合，機器情報`info`を`Ok(info)`として返す．
+    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
+    pub fn device_info(&mut self) -> Result<DeviceInfo, SensorError> {
+        let response = self.query(VERSION_COMMAND)?;
+
+        // 応答は型式名，ファームウェアのバージョン，シリアル番号をカンマ区切りで並べた文字列
+        let mut fields = response.split(',').map(|field| field.trim().to_owned());
+        let mut next_field = || fields.next().ok_or(SensorError::InvalidTextLength);
+        let info = DeviceInfo {
+            model: next_field()?,
+            firmware_version: next_field()?,
+            serial_number: next_field()?,
+        };
+        self.device_info = Some(info.clone());
+        Ok(info)
+    }
+
+    /// センサ本体にゼロ点調整を指令し，現在の出力を無負荷時の出力とする．
+    /// ホスト側の`calibrate`と異なり，調整結果はセンサ本体に保持されるため，
+    /// 再接続後も有効であり，このドライバによる処理よりも前に適用される．
+    ///
+    /// ホスト側で保持しているオフセットは変更されない．
+    /// 必要であれば，この後に改めて`calibrate`を呼ぶこと．
+    pub fn zero_on_device(&mut self) -> Result<(), SensorError> {
+        self.send_setting_command(&[ZERO_COMMAND])
+    }
+
+    /// 最後に受信したフレームのレコード番号を返す．
+    /// まだフレームを受信していない場合は`None`を返す．
+    pub fn last_record_number(&self) -> Option<u8> {
+        self.last_record_number
+    }
+
+    /// 最後に受信したフレームと，その1つ前に受信したフレームの間に取りこぼしたフレームの数を返す．
+    /// レコード番号の飛びから検出するため，`protocol::RECORD_NUMBER_MODULUS`個以上の取りこぼしは検出できない．
+    pub fn last_dropped_frames(&self) -> u8 {
+        self.last_dropped_frames
+    }
+
+    /// 最後にこのセンサから取得した測定値を返す．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    /// センサと通信して観測値を更新するには`update`メソッドを利用する．
+    ///
+    /// `set_gravity_compensator`で補償器を設定している場合は，工具にはたらく重力の影響を取り除いた値を返す．
+    /// `set_mounting`で取り付け方を設定している場合は，利用者の座標系で表した値を返す．
+    pub fn last_measurement(&self) -> Wrench {
+        self.mounting.apply(&self.sensor_frame_measurement())
+    }
+
+    /// センサ座標系で表した，ゼロ点と重力の影響を取り除いた測定値を返す．
+    fn sensor_frame_measurement(&self) -> Wrench {
+        let measurement = self.raw_wrench - self.offset;
+        match &self.gravity_compensator {
+            Some(compensator) => compensator.compensate(measurement, self.gravity_in_sensor_frame),
+            None => measurement,
+        }
+    }
+
+    /// 最後にこのセンサから受信した，換算前の各軸のデジタル出力値を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// 感度の不具合の調査や，独自のキャリブレーションに利用できる．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    pub fn last_raw_counts(&self) -> [u16; AXIS_COUNT] {
+        self.raw_counts
+    }
+
+    /// 最後に受信した出力値について，過負荷となっている軸を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// デジタル出力値が出力範囲の端に達している軸と，測定値の大きさが定格荷重を超えている軸を過負荷とみなす．
+    /// 定格荷重の既定値を持たない型式では，出力範囲の端に達している軸のみを過負荷とみなす．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    pub fn last_overload(&self) -> [bool; AXIS_COUNT] {
+        self.last_overload
+    }
+
+    /// 過負荷を検出した際に，`update`が`Err(SensorError::Overload)`を返すかどうかを設定する．
+    /// エラーを返す場合も測定値は更新されるため，`last_measurement`で過負荷時の測定値を得られる．
+    pub fn set_overload_error(&mut self, enabled: bool) {
+        self.overload_error = enabled;
+    }
+
+    /// 最後にこのセンサから取得した測定値を単精度で返す．
+    /// 倍精度で求めた`last_measurement`の結果を単精度に丸めたものである．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    pub fn last_measurement_f32(&self) -> WrenchF32 {
+        self.last_measurement().into()
+    }
+
+    /// 最後に受信したデジタル出力値を，倍精度を経由せずに単精度のまま換算したセンサの生の出力値を返す．
+    /// 補正値の減算や座標変換は行わない．また，`set_calibration_matrix`で設定した校正行列は用いず，各軸の感度で換算する．
+    /// 感度が定まっていない場合は`None`を返す．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    pub fn last_raw_wrench_f32(&self) -> Option<WrenchF32> {
+        let (force, torque) = self.sensitivity?;
+        Some(WrenchF32::from_counts(
+            self.raw_counts,
+            S::center_count(),
+            force.map(|s| PerNewton::<f32>::new(s.value_unsafe as f32)),
+            torque.map(|s| PerNewtonMeter::<f32>::new(s.value_unsafe as f32)),
+        ))
+    }
+
+    /// 最後にこのセンサから取得した測定値を，`dimensioned`の型を用いない`PlainWrench`として返す．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    pub fn last_measurement_plain(&self) -> PlainWrench {
+        self.last_measurement().into()
+    }
+
+    /// 最後にこのセンサから取得した測定値を，その受信時刻とともに返す．
+    /// このメソッドでは，センサとの直接の通信は行わない．
+    /// まだ測定値を受信していない場合は`None`を返す．
+    pub fn last_measurement_stamped(&self) -> Option<Measurement> {
+        let (timestamp, system_time) = self.last_received_time?;
+        Some(Measurement {
+            wrench: self.last_measurement(),
+            timestamp,
+            system_time,
+            sequence: self.next_sequence - 1,
+            // 受信時刻が記録されていれば，レコード番号も記録されている
+            record_number: self.last_record_number.unwrap_or_default(),
+            overload: self.last_overload,
+        })
+    }
+
+    /// センサと通信して測定値情報を更新し，更新した測定値をその受信時刻とともに返す．
+    pub fn update_stamped(&mut self) -> Result<Measurement, SensorError> {
+        self.update()?;
+        // 更新に成功していれば，必ず受信時刻が記録されている
+        Ok(self.last_measurement_stamped().unwrap())
+    }
+
+    /// センサと通信して，測定値情報を更新する．
+    /// 更新した測定値を得るには`last_measurement`メソッドを利用する．
+    ///
+    /// 受信に失敗した場合もフレームの区切りを立て直し，必要であれば送信を要求しなおすため，
+    /// 次の呼び出しでは改めて出力値を受信できる．
+    ///
+    /// 感度が定まっておらず，校正行列も設定していない場合は，受信せずに`Err(SensorError::MissingSensitivity)`を返す．
+    pub fn update(&mut self) -> Result<(), SensorError> {
+        self.check_conversion()?;
+        let start = Instant::now();
+        let result = self
+            .drain_if_enabled()
+            .and_then(|_| self.receive())
+            .or_else(|e| self.recover_from_failed_receive(e))
+            // 次の観測に備えて，センサに力を送信するように命令しておく
+            .and_then(|_| self.request_next_data())
+            .and_then(|_| self.check_overload());
+        let result = self.check_watchdog(result);
+        self.diagnostics
+            .record_update(start.elapsed(), result.is_ok());
+
+        result
+    }
+
+    /// 測定値情報を更新し，更新した測定値を順に返すイテレータを返す．
+    /// 連続送信モードでは，センサから次々に届くフレームを順に読み出すために利用できる．
+    /// イテレータは終端を持たないため，`take`などで取り出す個数を制限すること．
+    pub fn measurements(&mut self) -> impl Iterator<Item = Result<Wrench, SensorError>> + '_ {
+        std::iter::from_fn(move || Some(self.update().map(|_| self.last_measurement())))
+    }
+
+    /// センサを連続送信モードに切り替える．
+    /// 連続送信モードでは，センサは要求を待たずに最大の周期で出力値を送信し続けるため，
+    /// 1回ごとに送信を要求する場合よりも高い頻度かつ小さい遅れで測定値を得られる．
+    ///
+    /// 連続送信中は`update`が要求を送信せずに次のフレームを受信する．
+    /// 受信が追いつかない場合，古いフレームが受信バッファに溜まることに注意すること．
+    /// また，連続送信中は応答と出力値のフレームを区別できないため，
+    /// `read_device_sensitivity`や`set_device_filter`などの出力値の受信以外の命令は`Err(SensorError::Streaming)`を返す．
+    pub fn start_streaming(&mut self) -> Result<(), SensorError> {
+        if self.streaming {
+            return Ok(());
+        }
+
+        self.discard_pending_response()?;
+        self.send_command(STREAM_COMMAND)?;
+        self.streaming = true;
+        Ok(())
+    }
+
+    /// センサの連続送信モードを終了し，1回ごとに送信を要求する通常の動作に戻す．
+    ///
+    /// # Returns
+    /// 停止命令の送信後もセンサからデータが届き続ける場合，`Err(SensorError::UnsupportedCommand)`を返す．
+    pub fn stop_streaming(&mut self) -> Result<(), SensorError> {
+        if !self.streaming {
+            return Ok(());
+        }
+
+        self.streaming = false;
+        self.request_stop()?;
+        // 以降のupdate()に備えて，データを送信するようにセンサに要求する
+        self.request_next_data()
+    }
+
+    /// センサが連続送信モードで動作していれば`true`を返す．
+    pub fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    /// `update`と同様に測定値情報を更新するが，観測した通信遅延に応じて読み取りのタイムアウトを自動で調整する．
+    ///
+    /// 直近の通信遅延の平均が得られている場合，この呼び出しの間だけタイムアウトを`2 * 平均遅延 + 1ms`とし，
+    /// 呼び出しを終える際に接続時に設定したタイムアウトに戻す．
+    /// 調整したタイムアウトの間に出力値が届かなかった場合は，それまでの通信遅延の記録を捨てる．
+    /// 通信遅延は，このメソッドの呼び出し時点でセンサからのデータが届ききっておらず，
+    /// 受信を待つ必要があった場合にのみ記録される．
+    pub fn update_with_adaptive_timeout(&mut self) -> Result<(), SensorError> {
+        self.check_conversion()?;
+        let start = Instant::now();
+        let result = self.update_adaptively();
+        let result = self.check_watchdog(result);
+        self.diagnostics
+            .record_update(start.elapsed(), result.is_ok());
+
+        result
+    }
+
+    /// `update_with_adaptive_timeout`の本体．
+    fn update_adaptively(&mut self) -> Result<(), SensorError> {
+        // 調整したタイムアウトはこの呼び出しの間だけ用い，終わったら設定値に戻す
+        if let Some(latency) = self.observed_latency() {
+            self.serial_port
+                .set_timeout(latency * 2 + Duration::from_millis(1))?;
+        }
+        let received = self.receive_measuring_latency();
+        self.serial_port.set_timeout(self.read_timeout)?;
+
+        // 観測した遅延より応答が遅れた場合は記録を捨て，次の呼び出しでは設定値のタイムアウトで待つ
+        if let Err(e) = &received {
+            if e.is_timeout() {
+                self.latency_history.clear();
+            }
+        }
+        received.or_else(|e| self.recover_from_failed_receive(e))?;
+
+        self.request_next_data()?;
+
+        self.check_overload()
+    }
+
+    /// 要求済みの出力値を受信し，受信を待つ必要があった場合は通信遅延を記録する．
+    fn receive_measuring_latency(&mut self) -> Result<(), SensorError> {
+        self.drain_if_enabled()?;
+
+        // 既にデータが届いている場合は，受信にかかった時間が通信遅延を表さない
+        let waited = (self.serial_port.bytes_to_read()? as usize) < RESPONSE_BYTES;
+        self.receive()?;
+        if let (true, Some(request_time)) = (waited, self.last_request_time) {
+            if self.latency_history.len() == LATENCY_HISTORY_LENGTH {
+                self.latency_history.pop_front();
+            }
+            self.latency_history.push_back(request_time.elapsed());
+        }
+        Ok(())
+    }
+
+    /// `update_with_adaptive_timeout`で観測した，直近の通信遅延の平均を返す．
+    /// まだ通信遅延が観測されていない場合は`None`を返す．
+    pub fn observed_latency(&self) -> Option<Duration> {
+        match self.latency_history.len() {
+            0 => None,
+            len => Some(self.latency_history.iter().sum::<Duration>() / len as u32),
+        }
+    }
+
+    /// これまでの通信状態に関する統計情報を返す．
+    pub fn generate_diagnostics_report(&self) -> DiagnosticsReport {
+        self.diagnostics.report()
+    }
+
+    /// 通信状態に関する統計情報をすべて0に戻す．
+    pub fn reset_diagnostics(&mut self) {
+        self.diagnostics = DiagnosticsCounter::default();
+    }
+
+    /// `update`で測定値を受信するたびに呼び出されるコールバックを設定する．
+    /// コールバックには，補正値を減じる前のセンサの生の出力値が渡される．
+    /// 既にコールバックが設定されている場合は，新しいコールバックで置き換える．
+    pub fn set_record_callback(&mut self, callback: impl Fn(&Wrench) + Send + 'static) {
+        self.record_callback = Some(Box::new(callback));
+    }
+
+    /// `set_record_callback`で設定したコールバックを解除する．
+    pub fn clear_record_callback(&mut self) {
+        self.record_callback = None;
+    }
+
+    /// 指定した期間センサからの出力を受信し，その平均をゼロ点とすることでキャリブレーションを行う．
+    ///
+    /// # Returns
+    /// 受信に成功した測定値の個数と，その各軸の平均および標準偏差．
+    /// 受信に成功した測定値が試行回数の半分に満たない場合は`SensorError::InsufficientSamples`を返し，ゼロ点は変更しない．
+    ///
+    /// # Panics
+    /// `measurement_times`が0の場合．
+    pub fn calibrate(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError> {
+        self.calibrate_with_progress(measurement_period, measurement_times, |_, _| {})
+    }
+
+    /// `calibrate`と同様にキャリブレーションを行い，測定値を1つ受信しようとするたびに`progress`を呼び出す．
+    /// 試行回数が多く時間のかかるキャリブレーションの進捗を，GUIなどに表示するために利用できる．
+    /// # Params
+    /// 1. `measurement_period`: 測定値を受信する間隔．
+    /// 1. `measurement_times`: 測定値の受信を試みる回数．
+    /// 1. `progress`: 進捗を受け取るコールバック．(試行済みの回数, `measurement_times`)が渡される．
+    ///
+    /// # Returns
+    /// `calibrate`と同じ．
+    ///
+    /// # Panics
+    /// `measurement_times`が0の場合．
+    pub fn calibrate_with_progress(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<CalibrationReport, SensorError> {
+        assert!(measurement_times > 0);
+        self.check_conversion()?;
+
+        let mut accumulator = CalibrationAccumulator::new();
+
+        // 指定回数，センサからの生データを集計する．受信に失敗した測定値は用いない
+        for i in 0..measurement_times {
+            match self.update() {
+                Ok(()) => accumulator.push(self.raw_wrench),
+                Err(_) => accumulator.push_failure(),
+            }
+            progress(i + 1, measurement_times);
+            // 次の取得時刻まで待機
+            std::thread::sleep(measurement_period);
+        }
+        // 生データの平均をとり，補正後の値が0となるようにオフセットを定める．
+        let report = accumulator.finish()?;
+        self.offset = report.mean;
+        self.calibration_metadata = Some((SystemTime::now(), report.sample_count));
+        Ok(report)
+    }
+
+    /// 現在のゼロ点を，キャリブレーションの時刻，測定値の個数，センサのシリアル番号とともにファイルに保存する．
+    /// 保存したゼロ点は`load_calibration`で読み込める．
+    ///
+    /// シリアル番号はセンサ本体から読み出す．読み出せなかった場合はシリアル番号を記録しない．
+    /// 連続送信中はセンサに問い合わせず，それまでに`device_info`で読み出したシリアル番号があればそれを記録する．
+    /// ゼロ点を`calibrate`によらず設定した場合は，保存した時刻と測定値の個数0を記録する．
+    pub fn save_calibration<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SensorError> {
+        let (timestamp, sample_count) = self
+            .calibration_metadata
+            .unwrap_or_else(|| (SystemTime::now(), 0));
+        let serial_number = if self.streaming {
+            // 連続送信中に問い合わせると，応答と出力値のフレームが混ざってしまう
+            self.device_info
+                .as_ref()
+                .map(|info| info.serial_number.clone())
+        } else {
+            self.device_info().ok().map(|info| info.serial_number)
+        };
+
+        let calibration = SavedCalibration {
+            offset: self.offset,
+            timestamp,
+            sample_count,
+            serial_number,
+        };
+        calibration.save(path)?;
+        Ok(())
+    }
+
+    /// `save_calibration`で保存したゼロ点を読み込み，このセンサのゼロ点として設定する．
+    ///
+    /// # Returns
+    /// 読み込めた場合，保存されていた内容`calibration`を`Ok(calibration)`として返す．
+    /// 別のセンサで求めたゼロ点を誤って用いないよう，必要に応じて呼び出し側でシリアル番号を確認すること．
+    pub fn load_calibration<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<SavedCalibration, SensorError> {
+        let calibration = SavedCalibration::load(path)?;
+        self.offset = calibration.offset;
+        self.calibration_metadata = Some((calibration.timestamp, calibration.sample_count));
+        Ok(calibration)
+    }
+
+    /// センサから測定値を1つ受信し，その生の出力値を新たなゼロ点とする．
+    /// 工具を持ち替えた直後など，`calibrate`のように複数の測定値を平均するまでもない場合に用いる．
+    ///
+    /// # Returns
+    /// 受信に失敗した場合は，その内容を表すエラー`e`を`Err(e)`として返し，ゼロ点は変更しない．
+    pub fn tare(&mut self) -> Result<(), SensorError> {
+        self.update()?;
+        self.set_offset(self.raw_wrench);
+        Ok(())
+    }
+
+    /// センサ出力値から減じている現在のゼロ点を返す．
+    pub fn offset(&self) -> Wrench {
+        self.offset
+    }
+
+    /// センサ出力値から減ずるゼロ点を直接設定する．
+    pub fn set_offset(&mut self, offset: Wrench) {
+        self.offset = offset;
+        self.calibration_metadata = None;
+    }
+
+    /// ゼロ点のドリフトを自動的に補償する補償器を設定する．
+    /// 設定すると，出力値を受信するたびに補償器がゼロ点を更新する．
+    /// `None`を与えると補償を止める．それまでに更新されたゼロ点はそのまま残る．
+    pub fn set_drift_compensator(&mut self, compensator: Option<WrenchDriftCompensator>) {
+        self.drift_compensator = compensator;
+    }
+
+    /// 設定されているドリフトの補償器を返す．
+    pub fn drift_compensator(&self) -> Option<&WrenchDriftCompensator> {
+        self.drift_compensator.as_ref()
+    }
+
+    /// 工具にはたらく重力の影響を測定値から取り除く補償器を設定する．
+    /// 設定すると，`last_measurement`は`set_orientation`で与えた姿勢に応じて重力の影響を取り除いた値を返す．
+    /// `None`を与えると補償を止める．
+    ///
+    /// 工具の重さがゼロ点に含まれないよう，ゼロ点は工具を取り付けていない状態で求めておくこと．
+    pub fn set_gravity_compensator(&mut self, compensator: Option<WrenchGravityCompensator>) {
+        self.gravity_compensator = compensator;
+    }
+
+    /// 設定されている重力の補償器を返す．
+    pub fn gravity_compensator(&self) -> Option<&WrenchGravityCompensator> {
+        self.gravity_compensator.as_ref()
+    }
+
+    /// 重力の補償に用いるセンサの現在の姿勢を，回転行列で設定する．
+    /// 姿勢を設定するまでは，センサのz軸が鉛直上向きであるとみなす．
+    /// # Params
+    /// 1. `rotation`: センサ座標系からワールド座標系への回転行列．ワールド座標系のz軸は鉛直上向きとする．
+    pub fn set_orientation(&mut self, rotation: &[[f64; 3]; 3]) {
+        self.gravity_in_sensor_frame = WrenchGravityCompensator::gravity_in_sensor_frame(rotation);
+    }
+
+    /// 重力の補償に用いるセンサの現在の姿勢を，単位四元数で設定する．
+    /// # Params
+    /// 1. `quaternion`: センサ座標系からワールド座標系への回転を表す単位四元数`[w, x, y, z]`．
+    ///    ワールド座標系のz軸は鉛直上向きとする．
+    ///
+    /// # Panics
+    /// `quaternion`が単位四元数でない場合．
+    pub fn set_orientation_quaternion(&mut self, quaternion: [f64; 4]) {
+        self.set_orientation(&geometry::quaternion_to_rotation(quaternion));
+    }
+
+    /// 測定値を受信するたびに許容範囲の超過を判定する監視器を設定する．`None`を与えると監視をやめる．
+    /// 判定には`last_measurement`と同じ，ゼロ点や重力の影響を取り除いた測定値を用いる．
+    pub fn set_safety_monitor(&mut self, monitor: Option<SafetyMonitor>) {
+        self.safety_monitor = monitor;
+    }
+
+    /// 現在設定されている監視器を返す．
+    pub fn safety_monitor(&self) -> Option<&SafetyMonitor> {
+        self.safety_monitor.as_ref()
+    }
+
+    /// センサの取り付け方を設定する．以降の`last_measurement`などは，利用者の座標系で表した値を返す．
+    /// ゼロ点や`record_callback`に渡す生の出力値，重力の補償に用いる姿勢は，引き続きセンサ座標系で表す．
+    pub fn set_mounting(&mut self, mounting: SensorMounting) {
+        self.mounting = mounting;
+    }
+
+    /// 現在のセンサの取り付け方を返す．
+    pub fn mounting(&self) -> SensorMounting {
+        self.mounting
+    }
+
+    /// データの途絶を検出する監視器を設定する．`None`を与えると検出をやめる．
+    /// 監視器は設定した時点から改めて監視を始める．
+    pub fn set_watchdog(&mut self, watchdog: Option<DataStallWatchdog>) {
+        self.watchdog = watchdog;
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.reset();
+        }
+    }
+
+    /// 現在設定されている監視器を返す．
+    pub fn watchdog(&self) -> Option<&DataStallWatchdog> {
+        self.watchdog.as_ref()
+    }
+
+    /// 最後に有効なフレームを受信してからの経過時間を返す．
+    /// まだ受信していない場合は`None`を返す．
+    pub fn time_since_last_frame(&self) -> Option<Duration> {
+        self.last_received_time
+            .map(|(received, _)| received.elapsed())
+    }
+
+    /// ゼロ点を解除し，センサの生の出力値がそのまま`last_measurement`で得られるようにする．
+    pub fn clear_offset(&mut self) {
+        self.set_offset(Wrench::zeroed());
+    }
+
+    /// 受信バッファに溜まっている古いデータを捨て，改めてセンサにデータの送信を要求する．
+    /// 次の`update`では，このメソッドの呼び出し以降に測定された出力値が得られる．
+    ///
+    /// 通常の`update`は前回の`update`の直後に要求した出力値を受信するため，
+    /// しばらく`update`を呼ばなかった場合は古い測定値が得られる．
+    /// これを避けたい場合は，`update`の前にこのメソッドを呼ぶか，`set_auto_drain`を利用する．
+    pub fn drain(&mut self) -> Result<(), SensorError> {
+        // 要求済みの出力値がまだ届いていなければ，到着を待ってから捨てる
+        self.discard_pending_response()?;
+        self.serial_port.clear(serialport::ClearBuffer::Input)?;
+        self.unread_bytes.clear();
+
+        self.request_next_data()
+    }
+
+    /// `update`のたびに，受信の前に`drain`を行うかどうかを設定する．
+    /// 有効にすると常に新しい測定値が得られるが，1回の`update`にかかる時間は通信の往復分だけ長くなる．
+    pub fn set_auto_drain(&mut self, enabled: bool) {
+        self.auto_drain = enabled;
+    }
+
+    /// `set_auto_drain`で有効にしていれば`drain`を行う．
+    fn drain_if_enabled(&mut self) -> Result<(), SensorError> {
+        if self.auto_drain {
+            self.drain()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 受信バッファに溜まっているフレームをすべて受信して捨てる．
+    /// しばらく`update`を呼ばなかった場合などに，古いフレームを読み飛ばすために利用できる．
+    ///
+    /// 単に受信バッファを破棄するのとは異なり，フレームを1つずつ受信して解釈するため，
+    /// 受信バッファが空になった時点でフレームの区切りがずれていないことが保証される．
+    /// 1つ以上のフレームを捨てた場合は，次の`update`に備えて改めてセンサにデータの送信を要求する．
+    ///
+    /// # Returns
+    /// 捨てたフレームの数`n`を`Ok(n)`として返す．
+    pub fn drain_pending_frames(&mut self) -> Result<usize, SensorError> {
+        let mut count = 0;
+        while !self.unread_bytes.is_empty() || self.serial_port.bytes_to_read()? > 0 {
+            self.request_pending = false;
+            let reading = self.read_frame()?;
+            // 意図して捨てたフレームは取りこぼしとして数えない
+            self.track_record_number(reading.record_number);
+            count += 1;
+        }
+
+        if count > 0 {
+            self.request_next_data()?;
+        }
+
+        Ok(count)
     }
-}
-
-/// WDF-6M200-3 Wacohtech 6-axis force/touque sensor
-pub struct Wdf6m200 {
-    /// センサに接続されたシリアルポート．
-    serial_port: Box<dyn serialport::SerialPort>,
-    /// 現在のセンサ出力値．
-    raw_wrench: Wrench,
-    /// センサ出力値から減ずる補正値．
-    /// センサは力がはたらいていない場合も0ではない出力を出す．
-    /// そのため，センサからの生の出力からこのオフセット値を減じて補正してやる必要がある．
-    offset: Wrench,
-}
 
-impl Wdf6m200 {
-    /// コンピュータに接続されたセンサとの通信を確立する．
-    /// # Params
-    /// 1. `read_timeout_duration`: シリアル通信の読み取り操作がこの時間経過しても完了していない場合，タイムアウトとなる．
+    /// センサにデータ送信の停止を指令する．
+    /// 停止命令を受理したセンサは，以降データを送信しなくなる．
+    ///
+    /// 停止命令より前に要求していたデータは破棄されるため，
+    /// このメソッドの呼び出し直後の`update`はタイムアウトによって失敗する．
     ///
     /// # Returns
-    /// センサとの通信が確立できた場合，センサのインスタンス`sensor`を`Ok(sensor)`として返す．
-    /// 通信に失敗した場合，その内容を表すエラー`e`を`Err(e)`として返す．
-    pub fn open(read_timeout_duration: Duration) -> Result<Wdf6m200, SensorError> {
-        // PCに接続されているデバイスの中から力覚センサを探し，そのデバイスへのパスを取得する
-        let sensor_port_path = serial_ports::ListPorts::new()
-            .iter()
-            // デバイスのうち，USB接続されているものをみつける
-            .filter_map(|port| {
-                if let serial_ports::ListPortType::UsbPort(info) = &port.port_type {
-                    Some((port, info))
-                } else {
-                    None
-                }
-            })
-            // IDが力覚センサと一致するデバイスをみつける
-            .filter(|(_, info)| info.vid == SENSOR_DEVICE_VENDOR_ID)
-            .filter(|(_, info)| info.pid == SENSOR_DEVICE_PRODUCT_ID)
-            .map(|(port, _)| port.device.clone())
-            .next()
-            .ok_or(SensorError::SensorNotFound)?;
-
-        // ハードウェアの仕様に合わせて通信設定を作る．
-        // センサの仕様書を見て，ここの通信設定を決めた．
-        let settings = serialport::SerialPortSettings {
-            baud_rate: 921600,
-            data_bits: serialport::DataBits::Eight,
-            flow_control: serialport::FlowControl::None,
-            parity: serialport::Parity::None,
-            stop_bits: serialport::StopBits::One,
-            timeout: read_timeout_duration,
-        };
+    /// 停止命令の送信後もセンサからデータが届き続ける場合，`Err(SensorError::UnsupportedCommand)`を返す．
+    pub fn request_stop(&mut self) -> Result<(), SensorError> {
+        self.send_command(STOP_COMMAND)?;
+        self.unread_bytes.clear();
+        self.request_pending = false;
+        self.streaming = false;
 
-        // シリアル通信確立
-        let serial_port =
-            serialport::open_with_settings(&sensor_port_path.into_os_string(), &settings)?;
+        // 停止命令以前に要求したデータを捨てながら，データが届かない状態がしばらく続くのを待つ．
+        // 停止命令が受理されていれば，読み取りのタイムアウトの2倍が経過するまでに届かなくなるはず
+        let timeout = self.serial_port.timeout();
+        let quiet_period = STOP_QUIET_PERIOD.min(timeout);
+        let deadline = Instant::now() + timeout * 2;
+        let mut quiet_since = Instant::now();
+        loop {
+            if self.serial_port.bytes_to_read()? > 0 {
+                self.serial_port.clear(serialport::ClearBuffer::Input)?;
+                quiet_since = Instant::now();
+            }
 
-        let mut sensor = Self {
-            serial_port,
-            raw_wrench: Wrench::zeroed(),
-            offset: Wrench::zeroed(),
-        };
+            let now = Instant::now();
+            if now.duration_since(quiet_since) >= quiet_period {
+                return Ok(());
+            }
+            if now >= deadline {
+                return Err(SensorError::UnsupportedCommand(STOP_COMMAND));
+            }
+            std::thread::sleep(STOP_POLL_INTERVAL);
+        }
+    }
 
-        // 最初のupdate()に備えて，データを送信するようにセンサに要求する
-        sensor.request_next_data()?;
+    /// 次の出力値を送信するようセンサに指令する．
+    /// センサからデータを受信するには，前もってこのメソッドを呼び出す必要がある．
+    fn request_next_data(&mut self) -> Result<(), SensorError> {
+        // 連続送信中はセンサが自発的にデータを送信するので，要求は不要
+        if self.streaming {
+            return Ok(());
+        }
 
-        Ok(sensor)
+        self.send_bytes(&protocol::encode_request())?;
+        self.last_request_time = Some(Instant::now());
+        self.request_pending = true;
+        Ok(())
     }
 
-    /// 最後にこのセンサから取得した測定値を返す．
-    /// このメソッドでは，センサとの直接の通信は行わない．
-    /// センサと通信して観測値を更新するには`update`メソッドを利用する．
-    pub fn last_measurement(&self) -> Wrench {
-        self.raw_wrench - self.offset
+    /// 出力値の受信に失敗した際に，次の`update`で出力値を受信できるように受信の状態を立て直し，`error`を返す．
+    /// 要求を待って送信する通常の動作では，要求しない限りセンサは何も送信しないため，
+    /// 受信しきれなかったフレームの残りを捨ててから，改めてセンサに送信を要求する．
+    /// 連続送信中は，フレームの区切りを`read_frame`で再同期するだけで次のフレームを受信できる．
+    fn recover_from_failed_receive(&mut self, error: SensorError) -> Result<(), SensorError> {
+        if !self.streaming {
+            // 立て直しに失敗しても次の呼び出しで再び試みるので，受信に失敗した理由を優先して返す
+            self.unread_bytes.clear();
+            let _ = self
+                .serial_port
+                .clear(serialport::ClearBuffer::Input)
+                .map_err(SensorError::from)
+                .and_then(|_| self.request_next_data());
+        }
+        Err(error)
     }
 
-    /// センサと通信して，測定値情報を更新する．
-    /// 更新した測定値を得るには`last_measurement`メソッドを利用する．
-    pub fn update(&mut self) -> Result<(), SensorError> {
-        self.raw_wrench = self
-            .read_bytes()
-            .and_then(Self::convert_reception_to_raw_wrench)?;
+    /// 要求済みの出力値をセンサから受信し，測定値情報を更新する．
+    fn receive(&mut self) -> Result<(), SensorError> {
+        self.request_pending = false;
+        let reading = self.read_frame()?;
+        self.last_received_time = Some((Instant::now(), SystemTime::now()));
+        self.next_sequence += 1;
+        self.track_record_number(reading.record_number);
+        let digitals = reading.counts;
+        if digitals.iter().any(|&d| d == 0 || d >= DIGITAL_OUTPUT_MAX) {
+            self.diagnostics.record_saturated_frame();
+        }
+        let centered = Self::center_digitals(digitals);
+        let raw_wrench = match (&self.calibration_matrix, self.sensitivity) {
+            (Some(matrix), _) => matrix.apply(centered),
+            (None, Some((force, torque))) => {
+                Self::convert_digitals_to_raw_wrench(centered, force, torque)
+            }
+            (None, None) => return Err(SensorError::MissingSensitivity),
+        };
 
-        // 次の観測に備えて，センサに力を送信するように命令しておく
-        self.request_next_data()?;
+        if let Some(callback) = &self.record_callback {
+            callback(&raw_wrench);
+        }
+        self.raw_wrench = raw_wrench;
+        self.raw_counts = digitals;
+
+        if let (Some(compensator), Some((received, _))) =
+            (&mut self.drift_compensator, self.last_received_time)
+        {
+            self.offset = compensator.compensate(self.offset, raw_wrench, received);
+        }
+
+        self.last_overload = self.detect_overload();
+
+        if let Some(measurement) = self.last_measurement_stamped() {
+            if let Some(monitor) = &mut self.safety_monitor {
+                monitor.check(&measurement);
+            }
+        }
 
         Ok(())
     }
 
-    /// 指定した期間センサからの出力を受信し，その平均をゼロ点とすることでキャリブレーションを行う．
-    /// # Panics
-    /// `measurement_times`が0の場合．
-    pub fn calibrate(&mut self, measurement_period: Duration, measurement_times: usize) {
-        assert!(measurement_times > 0);
+    /// デジタル出力値を測定値に換算するための感度か校正行列が定まっていなければ，
+    /// `Err(SensorError::MissingSensitivity)`を返す．
+    fn check_conversion(&self) -> Result<(), SensorError> {
+        if self.sensitivity.is_none() && self.calibration_matrix.is_none() {
+            return Err(SensorError::MissingSensitivity);
+        }
+        Ok(())
+    }
+
+    /// `set_watchdog`で監視器を設定しており，更新に失敗した時点でデータが途絶していれば，
+    /// `result`の代わりに`Err(SensorError::DataStalled)`を返す．
+    fn check_watchdog(&mut self, result: Result<(), SensorError>) -> Result<(), SensorError> {
+        let last_frame = self.last_received_time.map(|(received, _)| received);
+        match (&mut self.watchdog, result) {
+            (Some(watchdog), Err(e)) => match watchdog.check(last_frame, Instant::now()) {
+                Some(elapsed) => Err(SensorError::DataStalled(elapsed)),
+                None => Err(e),
+            },
+            (Some(watchdog), Ok(())) => {
+                watchdog.check(last_frame, Instant::now());
+                Ok(())
+            }
+            (None, result) => result,
+        }
+    }
+
+    /// `set_overload_error`で有効にしており，最後に受信した出力値が過負荷であればエラーを返す．
+    fn check_overload(&self) -> Result<(), SensorError> {
+        match (self.overload_error, self.last_overload.contains(&true)) {
+            (true, true) => Err(SensorError::Overload(self.last_overload)),
+            _ => Ok(()),
+        }
+    }
 
-        let mut raw_wrenches = vec![];
+    /// 最後に受信した出力値について，過負荷となっている軸を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// デジタル出力値が出力範囲の端に達している軸と，測定値の大きさが定格荷重を超えている軸を過負荷とみなす．
+    fn detect_overload(&self) -> [bool; AXIS_COUNT] {
+        // 定格荷重はセンサ座標系の軸ごとに定まっている
+        let measurement = self.sensor_frame_measurement().to_array();
+        // 定格荷重の既定値がなければ，出力範囲の端に達しているかどうかのみで判定する
+        let rated = S::rated_load().map_or([f64::INFINITY; AXIS_COUNT], RatedLoad::to_array);
 
-        // 指定回数，センサからの生データを収集する
-        for _ in 0..measurement_times {
-            if let Err(_) = self.update() {}
-            raw_wrenches.push(self.raw_wrench);
-            // 次の取得時刻まで待機
-            std::thread::sleep(measurement_period);
+        let mut overload = [false; AXIS_COUNT];
+        for (i, o) in overload.iter_mut().enumerate() {
+            let count = self.raw_counts[i];
+            let saturated = count == 0 || count >= DIGITAL_OUTPUT_MAX;
+            *o = saturated || measurement[i].abs() > rated[i];
         }
-        // 生データの平均をとり，補正後の値が0となるようにオフセットを定める．
-        let raw_wrench_sum = raw_wrenches
-            .iter()
-            .fold(Wrench::zeroed(), |acc, &cur| acc + cur);
-        let raw_force_average = raw_wrench_sum.force.map(|e| e / raw_wrenches.len() as f64);
-        let raw_torque_average = raw_wrench_sum.torque.map(|e| e / raw_wrenches.len() as f64);
+        overload
+    }
 
-        self.offset = Wrench::new(raw_force_average, raw_torque_average);
+    /// センサに1バイトの命令を送信する．
+    fn send_command(&mut self, command: u8) -> Result<(), SensorError> {
+        self.send_bytes(&[command])
     }
 
-    /// 次の出力値を送信するようセンサに指令する．
-    /// センサからデータを受信するには，前もってこのメソッドを呼び出す必要がある．
-    fn request_next_data(&mut self) -> Result<(), SensorError> {
-        // Read命令を送信
-        const WRITE_DATA: [u8; 1] = ['R' as u8];
-        let write_count = self.serial_port.write(&WRITE_DATA)?;
+    /// センサに複数バイトからなる命令を送信する．
+    fn send_bytes(&mut self, write_data: &[u8]) -> Result<(), SensorError> {
+        let write_count = match self.write_timeout {
+            // シリアルポートのタイムアウトは読み書きで共通なので，書き込みの間だけ一時的に変更する
+            Some(write_timeout) => {
+                let read_timeout = self.serial_port.timeout();
+                self.serial_port.set_timeout(write_timeout)?;
+                let result = self.serial_port.write(write_data);
+                self.serial_port.set_timeout(read_timeout)?;
+                result?
+            }
+            None => self.serial_port.write(write_data)?,
+        };
         // 送信できたデータサイズで成否判定
         match write_count {
-            c if c == WRITE_DATA.len() => Ok(()),
-            c => Err(SensorError::Write(WRITE_DATA.len(), c)),
+            c if c == write_data.len() => Ok(()),
+            c => Err(SensorError::Write(write_data.len(), c)),
         }
     }
 
     /// センサから受信したデータを読み出して返す．
+    /// 1回の読み取りで1フレーム全体が届くとは限らないため，
+    /// 1フレーム分のデータが揃うか，読み取りのタイムアウトが経過するまで読み取りを繰り返す．
     fn read_bytes(&mut self) -> Result<[u8; RESPONSE_BYTES], SensorError> {
         let mut read_bytes = [0; RESPONSE_BYTES];
-        let read_count = self.serial_port.read(&mut read_bytes)?;
-        // 送信できたデータサイズで成否判定
+        let mut read_count = 0;
+        // 再同期の際に持ち越したバイト列があれば，それを先頭に詰める
+        while read_count < RESPONSE_BYTES {
+            match self.unread_bytes.pop_front() {
+                Some(byte) => {
+                    read_bytes[read_count] = byte;
+                    read_count += 1;
+                }
+                None => break,
+            }
+        }
+        let deadline = Instant::now() + self.serial_port.timeout();
+
+        while read_count < RESPONSE_BYTES {
+            match self.serial_port.read(&mut read_bytes[read_count..]) {
+                Ok(c) => read_count += c,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                // 途中までしか届かなかった場合は，受信できたデータサイズを報告する
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut && read_count > 0 => break,
+                Err(e) => return Err(e.into()),
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        // 受信できたデータサイズで成否判定
         match read_count {
             RESPONSE_BYTES => Ok(read_bytes),
             c => Err(SensorError::Read(RESPONSE_BYTES, c)),
         }
     }
 
-    /// センサから受信したデータをレンチ情報に変換して返す．
-    fn convert_reception_to_raw_wrench(
-        reception: [u8; RESPONSE_BYTES],
-    ) -> Result<Wrench, SensorError> {
-        let digitals = {
-            // 受信データを文字列として解釈する
-            let text = std::str::from_utf8(&reception)?;
-            let mut array = [0; AXIS_COUNT];
-            // 各軸別々にデータを抽出
-            for i in 0..AXIS_COUNT {
-                // 該当する軸のデータが生バイト列のどの範囲にあるのか計算
-                let start = AXIS_DATA_START_INDEX + i * AXIS_DATUM_LENGTH;
-                let end = 1 + (i + 1) * AXIS_DATUM_LENGTH;
-                // 該当部分の文字列を読み，16進数テキストから整数へ変換
-                let axis_text = text.get(start..end).ok_or(SensorError::InvalidTextLength)?;
-                let digital = u16::from_str_radix(axis_text, 16)?;
-                array[i] = digital;
-            }
+    /// 出力値の受信以外の命令をセンサに送信し，その応答を1行受信して返す．
+    /// 要求済みの出力値があれば先に受信して捨て，応答の受信後に改めて送信を要求する．
+    /// 連続送信中は命令を送信せずに`Err(SensorError::Streaming)`を返す．
+    fn query(&mut self, command: u8) -> Result<String, SensorError> {
+        // 連続送信中に問い合わせると，応答と出力値のフレームが混ざってしまう
+        if self.streaming {
+            return Err(SensorError::Streaming(command));
+        }
+        let request_pending = self.discard_pending_response()?;
+
+        self.send_command(command)?;
+        let response = self.read_line();
+
+        // 以降のupdate()に備えて，データを送信するようにセンサに要求しなおす
+        if request_pending {
+            self.request_next_data()?;
+        }
+        response
+    }
+
+    /// 応答を返さない設定命令をセンサに送信する．
+    /// 要求済みの出力値があれば先に受信して捨て，命令の送信後に改めて送信を要求する．
+    /// 連続送信中は命令を送信せずに`Err(SensorError::Streaming)`を返す．
+    fn send_setting_command(&mut self, command: &[u8]) -> Result<(), SensorError> {
+        if self.streaming {
+            return Err(SensorError::Streaming(command[0]));
+        }
+        let request_pending = self.discard_pending_response()?;
+
+        self.send_bytes(command)?;
 
-            // このデジタル出力値の配列は，x,y,z方向の力，x,y,z方向のトルクの順に情報が格納されている．
-            array
+        if request_pending {
+            self.request_next_data()?;
+        }
+        Ok(())
+    }
+
+    /// 要求済みでまだ受信していない出力値があれば，受信して捨てる．
+    /// # Returns
+    /// 捨てる出力値があった場合は`Ok(true)`を返す．
+    fn discard_pending_response(&mut self) -> Result<bool, SensorError> {
+        if self.request_pending {
+            self.request_pending = false;
+            self.read_bytes()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// 受信したフレームのレコード番号を記録し，取りこぼしたフレームを検出する．
+    fn track_record_number(&mut self, record_number: u8) {
+        self.last_dropped_frames = match self.last_record_number {
+            Some(previous) => protocol::dropped_frames(previous, record_number),
+            None => 0,
         };
+        self.diagnostics
+            .record_dropped_frames(self.last_dropped_frames);
+        self.last_record_number = Some(record_number);
+    }
+
+    /// 1フレーム分のデータを受信して解釈する．
+    /// 解釈できなかった場合は，次のフレームの先頭まで受信データを読み飛ばしてからエラーを返す．
+    fn read_frame(&mut self) -> Result<RawReading, SensorError> {
+        let frame = self.read_bytes()?;
+        S::parse_response(&frame).or_else(|e| {
+            self.resynchronize(&frame)?;
+            Err(e.into())
+        })
+    }
+
+    /// 解釈できなかったフレームを受け取り，フレームの区切りを再同期する．
+    /// 1バイトでも取りこぼすと以降のフレームの区切りがすべてずれてしまうため，
+    /// 改行コードを探して，その直後を次のフレームの先頭とみなす．
+    fn resynchronize(&mut self, frame: &[u8]) -> Result<(), SensorError> {
+        match protocol::find_frame_boundary(frame) {
+            // 改行コードより後ろは次のフレームの先頭部分なので，次回の受信に持ち越す
+            Some(boundary) => {
+                self.unread_bytes.extend(&frame[boundary..]);
+                self.diagnostics.record_discarded_bytes(boundary);
+            }
+            // 改行コードが含まれていなければ，改行コードが現れるまで読み飛ばす
+            None => {
+                self.diagnostics.record_discarded_bytes(frame.len());
+                let skipped = self.read_until_newline()?;
+                self.diagnostics.record_discarded_bytes(skipped.len());
+            }
+        }
+        Ok(())
+    }
+
+    /// 改行コードが現れるまでセンサからデータを受信し，改行コードを除いた文字列を返す．
+    fn read_line(&mut self) -> Result<String, SensorError> {
+        let mut line = self.read_until_newline()?;
+        line.truncate(line.len() - NEWLINE_BYTES);
+
+        Ok(std::str::from_utf8(&line)?.to_owned())
+    }
+
+    /// 改行コードが現れるまでセンサからデータを受信し，改行コードを含めたバイト列を返す．
+    fn read_until_newline(&mut self) -> Result<Vec<u8>, SensorError> {
+        let mut line = vec![];
+        while !line.ends_with(&NEWLINE) {
+            if line.len() >= MAX_LINE_BYTES {
+                return Err(SensorError::InvalidTextLength);
+            }
+            let byte = self.read_byte()?;
+            line.push(byte);
+        }
+        Ok(line)
+    }
 
-        // デジタル出力値からレンチへ変換
-        let raw_wrench = {
-            let force = {
-                let digital = Triplet::new(digitals[0], digitals[1], digitals[2]).map(|i| i as f64);
-                let sensitivity = force_sensitivity();
-                digital.map_entrywise(sensitivity, |d, s| d / s)
-            };
-            let torque = {
-                let digital = Triplet::new(digitals[3], digitals[4], digitals[5]).map(|i| i as f64);
-                let sensitivity = torque_sensitivity();
-                digital.map_entrywise(sensitivity, |d, s| d / s)
-            };
-            Wrench::new(force, torque)
+    /// センサから1バイトを受信して返す．
+    fn read_byte(&mut self) -> Result<u8, SensorError> {
+        if let Some(byte) = self.unread_bytes.pop_front() {
+            return Ok(byte);
+        }
+
+        let mut byte = [0];
+        match self.serial_port.read(&mut byte)? {
+            1 => Ok(byte[0]),
+            c => Err(SensorError::Read(1, c)),
+        }
+    }
+
+    /// デジタル出力値から，無負荷時の出力に相当する中心値を差し引いて返す．
+    /// デジタル出力値は符号なしの値として送られてくるため，
+    /// 中心値を差し引くことで，負の向きの力やトルクが負の値として得られる．
+    fn center_digitals(digitals: [u16; AXIS_COUNT]) -> [f64; AXIS_COUNT] {
+        let center = S::center_count() as f64;
+        digitals.map(|d| d as f64 - center)
+    }
+
+    /// 中心値を差し引いたデジタル出力値を，与えられた感度を用いてレンチ情報に変換して返す．
+    fn convert_digitals_to_raw_wrench(
+        digitals: [f64; AXIS_COUNT],
+        force_sensitivity: Triplet<PerNewton<f64>>,
+        torque_sensitivity: Triplet<PerNewtonMeter<f64>>,
+    ) -> Wrench {
+        let force = {
+            let digital = Triplet::new(digitals[0], digitals[1], digitals[2]);
+            digital.map_entrywise(force_sensitivity, |d, s| d / s)
+        };
+        let torque = {
+            let digital = Triplet::new(digitals[3], digitals[4], digitals[5]);
+            digital.map_entrywise(torque_sensitivity, |d, s| d / s)
         };
+        Wrench::new(force, torque)
+    }
+}
+
+/// デジタル出力値の換算に用いることのできる感度であれば`true`を返す．
+fn is_valid_sensitivity(sensitivity: f64) -> bool {
+    sensitivity != 0.0 && sensitivity.is_finite()
+}
+
+/// 各軸の感度がデジタル出力値の換算に用いることのできる値であることを確かめる．
+/// # Panics
+/// いずれかの感度が0，または有限でない場合．
+fn assert_valid_sensitivity(force: Triplet<PerNewton<f64>>, torque: Triplet<PerNewtonMeter<f64>>) {
+    assert!(is_valid_sensitivity(force.x.value_unsafe));
+    assert!(is_valid_sensitivity(force.y.value_unsafe));
+    assert!(is_valid_sensitivity(force.z.value_unsafe));
+    assert!(is_valid_sensitivity(torque.x.value_unsafe));
+    assert!(is_valid_sensitivity(torque.y.value_unsafe));
+    assert!(is_valid_sensitivity(torque.z.value_unsafe));
+}
+
+/// PCに接続されているデバイスの中から力覚センサを探し，そのデバイスへのパスを返す．
+fn find_sensor_port() -> Result<PathBuf, SensorError> {
+    find_sensor_port_matching(|_| true)
+}
+
+/// PCに接続されている力覚センサのうち，`predicate`を満たすものを探し，そのデバイスへのパスを返す．
+fn find_sensor_port_matching<F>(predicate: F) -> Result<PathBuf, SensorError>
+where
+    F: Fn(&SensorPortInfo) -> bool,
+{
+    find_device_port_matching(&[DEFAULT_VID_PID], predicate)
+}
+
+/// PCに接続されている，ベンダIDとプロダクトIDの組が`vid_pids`のいずれかに一致するデバイスのうち，
+/// `predicate`を満たすものを探し，そのデバイスへのパスを返す．
+pub(crate) fn find_device_port_matching<F>(
+    vid_pids: &[(u16, u16)],
+    predicate: F,
+) -> Result<PathBuf, SensorError>
+where
+    F: Fn(&SensorPortInfo) -> bool,
+{
+    list_sensors_with_ids(vid_pids)
+        .into_iter()
+        .find(|port| predicate(port))
+        .map(|port| port.path)
+        .ok_or(SensorError::SensorNotFound)
+}
+
+/// PCに接続されている力覚センサの情報をすべて返す．
+/// 複数のセンサを用いる場合に，通信を確立する前にそれぞれのセンサを特定するために利用できる．
+pub fn list_sensors() -> Vec<SensorPortInfo> {
+    list_sensors_with_ids(&[DEFAULT_VID_PID])
+}
+
+/// PCに接続されているデバイスのうち，ベンダIDとプロダクトIDの組が`vid_pids`のいずれかに一致するものの情報をすべて返す．
+/// 既定とは異なるUSBシリアル変換器を搭載したセンサを探すために利用できる．
+/// # Params
+/// 1. `vid_pids`: センサとみなすデバイスの`(ベンダID, プロダクトID)`の組の一覧．
+pub fn list_sensors_with_ids(vid_pids: &[(u16, u16)]) -> Vec<SensorPortInfo> {
+    serial_ports::ListPorts::new()
+        .iter()
+        // デバイスのうち，USB接続されているものをみつける
+        .filter_map(|port| {
+            if let serial_ports::ListPortType::UsbPort(info) = &port.port_type {
+                Some((port, info))
+            } else {
+                None
+            }
+        })
+        // IDが指定したものと一致するデバイスをみつける
+        .filter(|(_, info)| vid_pids.contains(&(info.vid, info.pid)))
+        .map(|(port, info)| SensorPortInfo {
+            path: port.device.clone(),
+            serial_number: info.serial_number.clone(),
+            vendor_id: info.vid,
+            product_id: info.pid,
+        })
+        .collect()
+}
+
+/// 力覚センサとしての共通の操作を表す．
+/// 実機のセンサ，テスト用のモック，将来対応するセンサなどを，呼び出し側を変えずに差し替えられるようにする．
+pub trait ForceTorqueSensor {
+    /// センサと通信して，測定値情報を更新する．
+    fn update(&mut self) -> Result<(), SensorError>;
+
+    /// 最後にこのセンサから取得した測定値を返す．
+    fn last_measurement(&self) -> Wrench;
+
+    /// 指定した期間センサからの出力を受信し，その平均をゼロ点とすることでキャリブレーションを行う．
+    ///
+    /// # Returns
+    /// 受信に成功した測定値の個数と，その各軸の平均および標準偏差．
+    /// 受信に成功した測定値が試行回数の半分に満たない場合は`SensorError::InsufficientSamples`を返し，ゼロ点は変更しない．
+    ///
+    /// # Panics
+    /// `measurement_times`が0の場合．
+    fn calibrate(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError>;
+}
+
+impl<S: SensorSpec> ForceTorqueSensor for DynPick<S> {
+    fn update(&mut self) -> Result<(), SensorError> {
+        DynPick::update(self)
+    }
+
+    fn last_measurement(&self) -> Wrench {
+        DynPick::last_measurement(self)
+    }
+
+    fn calibrate(
+        &mut self,
+        measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError> {
+        DynPick::calibrate(self, measurement_period, measurement_times)
+    }
+}
 
-        Ok(raw_wrench)
+impl<S: SensorSpec> Drop for DynPick<S> {
+    /// 連続送信中であればセンサにデータ送信の停止を指令し，未送信のデータを送りきってからシリアルポートを閉じる．
+    fn drop(&mut self) {
+        // ドロップ時にはエラーを報告する手段がないので，失敗しても無視する
+        if self.streaming {
+            let _ = self.send_command(STOP_COMMAND);
+        }
+        let _ = self.serial_port.flush();
     }
 }
 
@@ -248,6 +1884,36 @@ pub enum SensorError {
     InvalidTextLength,
     /// センサから受信した文字列を整数に変換できない．
     ParseInt(std::num::ParseIntError),
+    /// センサから受信した文字列を実数に変換できない．
+    ParseFloat(std::num::ParseFloatError),
+    /// センサが命令を受理しなかった．
+    UnsupportedCommand(u8),
+    /// センサから受信したフレームを解釈できない．
+    Frame(FrameError),
+    /// いずれかの軸が過負荷となっている．値は`[fx, fy, fz, tx, ty, tz]`の順に並べた各軸の過負荷の有無．
+    Overload([bool; AXIS_COUNT]),
+    /// キャリブレーションで受信に成功した測定値が少なすぎる．値は(必要な個数, 実際に受信できた個数)．
+    InsufficientSamples(usize, usize),
+    /// 有効なフレームを一定時間受信していない．値は最後に有効なフレームを受信してからの経過時間．
+    DataStalled(Duration),
+    /// センサから読み出した感度が0，または有限でない．値は`[fx, fy, fz, tx, ty, tz]`の順に並べた読み出した感度．
+    InvalidSensitivity([f64; AXIS_COUNT]),
+    /// 連続送信中には送信できない命令を送信しようとした．値はその命令．
+    Streaming(u8),
+    /// デジタル出力値の換算に用いる感度が定まっていない．
+    /// センサの型式が感度の既定値を持たない場合は，`read_device_sensitivity`か`set_sensitivity`で感度を設定する必要がある．
+    MissingSensitivity,
+}
+
+impl SensorError {
+    /// 出力値が読み取りのタイムアウトまでに届ききらなかったことを表すエラーであれば`true`を返す．
+    fn is_timeout(&self) -> bool {
+        match self {
+            SensorError::Read(..) => true,
+            SensorError::Io(e) => e.kind() == std::io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
 }
 
 impl Display for SensorError {
@@ -258,9 +1924,13 @@ impl Display for SensorError {
             SensorError::Io(x) => x.fmt(f),
             SensorError::Utf8(x) => x.fmt(f),
             SensorError::InvalidTextLength => {
-                write!(f, "Received text's length from the sensor does not match the expected one")
+                write!(
+                    f,
+                    "Received text's length from the sensor does not match the expected one"
+                )
             }
             SensorError::ParseInt(x) => x.fmt(f),
+            SensorError::ParseFloat(x) => x.fmt(f),
             SensorError::Read(desired, actual) => write!(
                 f,
                 "The driver should read {} bytes from the sensor, but actually {} bytes read",
@@ -271,6 +1941,45 @@ impl Display for SensorError {
                 "The driver should write {} bytes to the sensor, but actually {} bytes written",
                 desired, actual
             ),
+            SensorError::UnsupportedCommand(command) => write!(
+                f,
+                "The sensor did not acknowledge the command {:?}",
+                *command as char
+            ),
+            SensorError::Frame(x) => x.fmt(f),
+            SensorError::Overload(overload) => {
+                let axes = Axis::ALL
+                    .iter()
+                    .zip(overload.iter())
+                    .filter(|(_, &o)| o)
+                    .map(|(axis, _)| format!("{:?}", axis))
+                    .collect::<Vec<_>>();
+                write!(f, "The sensor is overloaded on {}", axes.join(", "))
+            }
+            SensorError::InsufficientSamples(required, actual) => write!(
+                f,
+                "Calibration requires at least {} valid samples, but only {} samples were received",
+                required, actual
+            ),
+            SensorError::DataStalled(elapsed) => write!(
+                f,
+                "No valid frame has been received from the sensor for {:?}",
+                elapsed
+            ),
+            SensorError::InvalidSensitivity(sensitivity) => write!(
+                f,
+                "The sensor reported an invalid sensitivity {:?}",
+                sensitivity
+            ),
+            SensorError::Streaming(command) => write!(
+                f,
+                "The command {:?} cannot be sent while the sensor is streaming",
+                *command as char
+            ),
+            SensorError::MissingSensitivity => write!(
+                f,
+                "The sensitivity of the sensor is not known. Read it from the sensor or set it explicitly"
+            ),
         }
     }
 }
@@ -301,41 +2010,282 @@ impl From<std::num::ParseIntError> for SensorError {
     }
 }
 
-// 力覚センサから返ってくるバイト列の解釈方法:
-// ---------------------------
-// X111122223333444455556666++
-// X: 受信データの先頭1バイトはレコード番号．
-// 1111...6666: 次に各軸に対応した電圧が4バイト (合計で6*4=24バイト)．
-// ++: 最後に改行コード(CR+LF)が2バイト
+impl From<FrameError> for SensorError {
+    fn from(err: FrameError) -> Self {
+        SensorError::Frame(err)
+    }
+}
+
+impl From<std::num::ParseFloatError> for SensorError {
+    fn from(err: std::num::ParseFloatError) -> Self {
+        SensorError::ParseFloat(err)
+    }
+}
 
-/// 各軸に関するデジタル出力値のバイト数．
-const AXIS_DATUM_LENGTH: usize = 4;
-/// 各軸に関するデジタル出力が何バイトめから始まるか．
-const AXIS_DATA_START_INDEX: usize = 1;
+/// センサとのシリアル通信のボーレート．
+pub(crate) const BAUD_RATE: u32 = 921600;
 /// 軸数．
 const AXIS_COUNT: usize = 6;
-/// 改行コードの記述に要するバイト数．
-const NEWLINE_BYTES: usize = 2;
-/// センサから受信されるべきバイト数．
-const RESPONSE_BYTES: usize =
-    AXIS_DATA_START_INDEX + AXIS_DATUM_LENGTH * AXIS_COUNT + NEWLINE_BYTES;
+/// 出力値以外の応答として受信する1行の最大バイト数．
+const MAX_LINE_BYTES: usize = 128;
+
+/// データ送信の停止を確かめる際に，データが届かない状態が続く必要のある時間．
+/// 連続送信中のフレームの間隔より十分に長くとる．
+const STOP_QUIET_PERIOD: Duration = Duration::from_millis(10);
+/// データ送信の停止を確かめる際に，受信バッファを確認する間隔．
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// 通信遅延の平均を求めるために記録しておく，直近の通信遅延の個数．
+const LATENCY_HISTORY_LENGTH: usize = 10;
 
 /// センサデバイスの開発元ID
 const SENSOR_DEVICE_VENDOR_ID: u16 = 0x10C4;
 /// センサデバイスの製品ID
 const SENSOR_DEVICE_PRODUCT_ID: u16 = 0xEA60;
+/// デバイスの探索に既定で用いる，センサデバイスの`(開発元ID, 製品ID)`の組．
+pub const DEFAULT_VID_PID: (u16, u16) = (SENSOR_DEVICE_VENDOR_ID, SENSOR_DEVICE_PRODUCT_ID);
 
-type PerNewton<T> = Quot<Unitless<T>, Newton<T>>;
-type PerNewtonMeter<T> = Quot<Unitless<T>, NewtonMeter<T>>;
+/// 力あたりのデジタル出力値の変化量を表す型．
+pub type PerNewton<T> = Quot<Unitless<T>, Newton<T>>;
+/// トルクあたりのデジタル出力値の変化量を表す型．
+pub type PerNewtonMeter<T> = Quot<Unitless<T>, NewtonMeter<T>>;
 
-/// センサ各軸について，1Nあたりデジタル出力値がいくつ変化するか．
-/// これはセンサの仕様表から取ってきた値．
-fn force_sensitivity() -> Triplet<PerNewton<f64>> {
-    Triplet::new(24.9, 24.6, 24.5).map(PerNewton::<f64>::new)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_port::FakeSerialPort;
+
+    /// 代替のシリアルポートに接続したセンサを作る．
+    fn connect_fake() -> (Wdf6m200, FakeSerialPort) {
+        let port = FakeSerialPort::new(Duration::from_millis(10));
+        let sensor = Wdf6m200::from_serial_port(Box::new(port.clone()), None).unwrap();
+        (sensor, port)
+    }
+
+    /// すべての軸のデジタル出力値が中央値である，レコード番号0のフレーム．
+    const CENTERED_FRAME: &[u8; RESPONSE_BYTES] = b"0200020002000200020002000\r\n";
+
+    #[test]
+    fn adaptive_timeout_is_restored_after_update() {
+        let (mut sensor, port) = connect_fake();
+        sensor.latency_history.push_back(Duration::from_millis(2));
+        port.push_incoming(CENTERED_FRAME);
+
+        sensor.update_with_adaptive_timeout().unwrap();
+
+        let state = port.state();
+        assert_eq!(
+            state.timeout_history,
+            [Duration::from_millis(5), Duration::from_millis(10)]
+        );
+        assert_eq!(state.timeout, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn adaptive_timeout_widens_after_timeout() {
+        let (mut sensor, port) = connect_fake();
+        sensor.latency_history.push_back(Duration::from_millis(2));
+
+        // 出力値が届かないため，タイムアウトとなる
+        assert!(sensor.update_with_adaptive_timeout().is_err());
+        assert_eq!(port.state().timeout, Duration::from_millis(10));
+        assert_eq!(sensor.observed_latency(), None);
+
+        // 通信遅延の記録がないので，次の呼び出しでは設定値のタイムアウトで待つ
+        port.state().timeout_history.clear();
+        port.push_incoming(CENTERED_FRAME);
+        sensor.update_with_adaptive_timeout().unwrap();
+        assert_eq!(port.state().timeout_history, [Duration::from_millis(10)]);
+    }
+
+    #[test]
+    fn update_recovers_from_corrupted_frames_in_request_mode() {
+        let (mut sensor, port) = connect_fake();
+
+        // 16進数の数字でないバイトを含むフレーム
+        port.push_incoming(b"02000200020002000200020G0\r\n");
+        assert!(matches!(
+            sensor.update(),
+            Err(SensorError::Frame(FrameError::InvalidDigit(_)))
+        ));
+        // 受信に失敗しても，次の出力値を要求しなおしている
+        assert_eq!(port.state().written, b"RR");
+        port.push_incoming(CENTERED_FRAME);
+        sensor.update().unwrap();
+        assert_eq!(sensor.last_raw_counts(), [0x2000; AXIS_COUNT]);
+
+        // 途中のバイトを取りこぼして短くなったフレーム
+        port.push_incoming(b"1200020002000200020002000\r");
+        assert!(sensor.update().is_err());
+        assert_eq!(port.state().written, b"RRRR");
+        port.push_incoming(b"2201020002000200020002000\r\n");
+        sensor.update().unwrap();
+        assert_eq!(sensor.last_raw_counts()[0], 0x2010);
+        assert_eq!(sensor.last_record_number(), Some(2));
+    }
+
+    #[test]
+    fn raw_wrench_f32_matches_double_precision() {
+        let (mut sensor, port) = connect_fake();
+        port.push_incoming(b"0210020002000200020001F00\r\n");
+        sensor.update().unwrap();
+
+        let single = sensor.last_raw_wrench_f32().unwrap().to_array();
+        let double = sensor.raw_wrench.to_array();
+        for (s, d) in single.iter().zip(double.iter()) {
+            assert!((*s as f64 - d).abs() < 1e-4, "{:?} != {:?}", single, double);
+        }
+        assert!(single[0] > 0.0 && single[5] < 0.0);
+    }
+
+    #[test]
+    fn commands_with_responses_are_rejected_while_streaming() {
+        let (mut sensor, port) = connect_fake();
+        port.push_incoming(CENTERED_FRAME);
+        sensor.start_streaming().unwrap();
+        port.state().written.clear();
+        // 連続送信中のフレームが届き続けている
+        port.push_incoming(CENTERED_FRAME);
+
+        let is_streaming_error =
+            |result: Result<(), SensorError>| matches!(result, Err(SensorError::Streaming(_)));
+        assert!(is_streaming_error(sensor.read_device_sensitivity()));
+        assert!(is_streaming_error(sensor.device_info().map(|_| ())));
+        assert!(is_streaming_error(
+            sensor.set_device_filter(FilterLevel::None)
+        ));
+        assert!(is_streaming_error(sensor.zero_on_device()));
+
+        // 命令を送信せず，届いたフレームもそのまま残っている
+        assert!(port.state().written.is_empty());
+        sensor.update().unwrap();
+    }
+
+    #[test]
+    fn save_calibration_does_not_query_while_streaming() {
+        let (mut sensor, port) = connect_fake();
+        port.push_incoming(CENTERED_FRAME);
+        sensor.start_streaming().unwrap();
+        port.state().written.clear();
+
+        let path = std::env::temp_dir().join(format!(
+            "wacohtech-save-calibration-{}.txt",
+            std::process::id()
+        ));
+        sensor.save_calibration(&path).unwrap();
+        let saved = SavedCalibration::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(port.state().written.is_empty());
+        assert_eq!(saved.serial_number, None);
+    }
+
+    #[test]
+    fn request_stop_returns_once_port_is_quiet() {
+        let port = FakeSerialPort::new(Duration::from_millis(500));
+        let mut sensor = Wdf6m200::from_serial_port(Box::new(port.clone()), None).unwrap();
+        // 停止命令以前に要求したフレームが届いている
+        port.push_incoming(CENTERED_FRAME);
+
+        let start = Instant::now();
+        sensor.request_stop().unwrap();
+
+        // タイムアウトを待たずに，データが届かない状態が続いた時点で戻る
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert!(port.state().incoming.is_empty());
+        assert_eq!(port.state().written.last(), Some(&STOP_COMMAND));
+    }
+
+    #[test]
+    fn update_requires_sensitivity_for_models_without_defaults() {
+        let port = FakeSerialPort::new(Duration::from_millis(10));
+        let mut sensor = Wef6a200::from_serial_port(Box::new(port.clone()), None).unwrap();
+        assert_eq!(sensor.force_sensitivity(), None);
+        port.push_incoming(CENTERED_FRAME);
+
+        // 感度が定まるまでは受信しない
+        assert!(matches!(
+            sensor.update(),
+            Err(SensorError::MissingSensitivity)
+        ));
+        assert!(matches!(
+            sensor.calibrate(Duration::from_millis(0), 1),
+            Err(SensorError::MissingSensitivity)
+        ));
+        assert_eq!(port.state().incoming.len(), RESPONSE_BYTES);
+        assert_eq!(sensor.last_raw_wrench_f32(), None);
 
-/// センサ各軸について，1Nmあたりデジタル出力値がいくつ変化するか．
-/// これはセンサの仕様表から取ってきた値．
-fn torque_sensitivity() -> Triplet<PerNewtonMeter<f64>> {
-    Triplet::new(1664.7, 1639.7, 1638.0).map(PerNewtonMeter::<f64>::new)
+        // 接続時に要求した出力値を先に受信して捨ててから，感度を問い合わせる
+        port.push_incoming(b"32,32,32,1600,1600,1600\r\n");
+        sensor.read_device_sensitivity().unwrap();
+        port.push_incoming(b"1214020002000200020002000\r\n");
+        sensor.update().unwrap();
+        assert!((sensor.last_measurement().force.x.value_unsafe - 10.0).abs() < 1e-9);
+
+        sensor.reset_sensitivity();
+        assert_eq!(sensor.torque_sensitivity(), None);
+    }
+
+    #[test]
+    fn read_device_sensitivity_rejects_invalid_values() {
+        let (mut sensor, port) = connect_fake();
+        let force = sensor.force_sensitivity();
+        let torque = sensor.torque_sensitivity();
+
+        for reply in [
+            &b"25,25,0,1600,1600,1600\r\n"[..],
+            b"25,25,25,inf,1600,1600\r\n",
+            b"25,NaN,25,1600,1600,1600\r\n",
+        ] {
+            // 1回目の問い合わせでは，接続時に要求した出力値を先に受信して捨てる
+            port.push_incoming(CENTERED_FRAME);
+            port.push_incoming(reply);
+            match sensor.read_device_sensitivity() {
+                Err(SensorError::InvalidSensitivity(_)) => {}
+                other => panic!("unexpected result {:?}", other),
+            }
+            assert_eq!(sensor.force_sensitivity(), force);
+            assert_eq!(sensor.torque_sensitivity(), torque);
+        }
+
+        port.push_incoming(CENTERED_FRAME);
+        port.push_incoming(b"10,20,30,400,500,600\r\n");
+        sensor.read_device_sensitivity().unwrap();
+        assert_eq!(
+            sensor.force_sensitivity().unwrap().y,
+            PerNewton::<f64>::new(20.0)
+        );
+        assert_eq!(
+            sensor.torque_sensitivity().unwrap().z,
+            PerNewtonMeter::<f64>::new(600.0)
+        );
+    }
+
+    #[test]
+    fn drop_flushes_without_stop_command_in_request_mode() {
+        let (sensor, port) = connect_fake();
+        // 接続時に最初の出力値を要求している
+        assert_eq!(port.state().written, protocol::encode_request());
+        assert_eq!(port.state().flush_count, 0);
+
+        drop(sensor);
+
+        let state = port.state();
+        assert_eq!(state.written, protocol::encode_request());
+        assert_eq!(state.flush_count, 1);
+    }
+
+    #[test]
+    fn drop_sends_stop_command_and_flushes_while_streaming() {
+        let (mut sensor, port) = connect_fake();
+        port.push_incoming(CENTERED_FRAME);
+        sensor.start_streaming().unwrap();
+        port.state().written.clear();
+
+        drop(sensor);
+
+        let state = port.state();
+        assert_eq!(state.written, [STOP_COMMAND]);
+        assert_eq!(state.flush_count, 1);
+    }
 }
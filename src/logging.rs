@@ -0,0 +1,280 @@
+//! 測定値をファイルに記録するための機能．
+
+use crate::{DynPick, Measurement, SensorSpec, AXIS_COUNT};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// 記録した内容をファイルに書き出す既定の間隔．
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 測定値を逐次記録する書き込み先に共通の操作．
+/// 記録の形式を，呼び出し側を変えずに差し替えるために用いる．
+pub trait MeasurementSink {
+    /// 測定値を1件記録する．
+    /// # Params
+    /// 1. `measurement`: 記録する測定値．
+    /// 1. `raw_counts`: 測定値の換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    fn log(&mut self, measurement: &Measurement, raw_counts: [u16; AXIS_COUNT]) -> io::Result<()>;
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// センサが最後に受信した測定値を1件記録する．
+    /// まだ測定値を受信していない場合は何もしない．
+    fn log_sensor<S: SensorSpec>(&mut self, sensor: &DynPick<S>) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        match sensor.last_measurement_stamped() {
+            Some(measurement) => self.log(&measurement, sensor.last_raw_counts()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 測定値をCSV形式で逐次記録する．
+///
+/// 1行目を見出し行とし，2行目以降の各行に次の列をカンマ区切りで記録する．
+/// 1. `time_s`: 最初に記録した測定値からの経過時間[s]．
+/// 1. `fx`, `fy`, `fz`, `tx`, `ty`, `tz`: 測定値(SI単位)．
+/// 1. `sequence`: 測定値の通し番号．
+/// 1. `unix_time_s`: 測定値を受信したシステム時刻のUNIX時刻[s]．
+/// 1. `count_fx`, ..., `count_tz`: 換算前の各軸のデジタル出力値．
+///
+/// 先頭の7列は`ReplaySensor::from_csv`が読み込む形式と同じであるため，記録したファイルをそのまま再生できる．
+///
+/// 書き込みはバッファリングし，一定の間隔ごとにファイルへ書き出す．
+pub struct CsvLogger<W: Write> {
+    /// 書き込み先．
+    writer: BufWriter<W>,
+    /// 最初に記録した測定値の受信時刻．まだ記録していなければ`None`．
+    start: Option<Instant>,
+    /// 書き込み先に書き出す間隔．
+    flush_interval: Duration,
+    /// 最後に書き込み先に書き出した時刻．
+    last_flush: Instant,
+}
+
+impl CsvLogger<File> {
+    /// ファイルを作成し，見出し行を書き込む．既にファイルが存在する場合は上書きする．
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<CsvLogger<File>> {
+        let file = File::create(path)?;
+        Self::new(file)
+    }
+}
+
+impl<W: Write> CsvLogger<W> {
+    /// 任意の書き込み先に見出し行を書き込み，記録を始める．
+    pub fn new(writer: W) -> io::Result<CsvLogger<W>> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(
+            writer,
+            "time_s,fx,fy,fz,tx,ty,tz,sequence,unix_time_s,\
+             count_fx,count_fy,count_fz,count_tx,count_ty,count_tz"
+        )?;
+
+        Ok(CsvLogger {
+            writer,
+            start: None,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// 書き込み先に書き出す間隔を設定する．既定では1秒．
+    /// `Duration::from_secs(0)`を設定すると，記録のたびに書き出す．
+    pub fn set_flush_interval(&mut self, interval: Duration) {
+        self.flush_interval = interval;
+    }
+
+    /// 書き込み先に書き出す間隔を返す．
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval
+    }
+
+    /// 測定値を1行記録する．
+    /// # Params
+    /// 1. `measurement`: 記録する測定値．
+    /// 1. `raw_counts`: 測定値の換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub fn log(
+        &mut self,
+        measurement: &Measurement,
+        raw_counts: [u16; AXIS_COUNT],
+    ) -> io::Result<()> {
+        let start = *self.start.get_or_insert(measurement.timestamp);
+        let time = measurement
+            .timestamp
+            .saturating_duration_since(start)
+            .as_secs_f64();
+        let unix_time = measurement
+            .system_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        let mut columns = vec![time.to_string()];
+        columns.extend(measurement.wrench.to_array().iter().map(|e| e.to_string()));
+        columns.push(measurement.sequence.to_string());
+        columns.push(unix_time.to_string());
+        columns.extend(raw_counts.iter().map(|c| c.to_string()));
+        writeln!(self.writer, "{}", columns.join(","))?;
+
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.writer.flush()
+    }
+
+    /// バッファリングしている内容を書き出し，書き込み先を返す．
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl<W: Write> MeasurementSink for CsvLogger<W> {
+    fn log(&mut self, measurement: &Measurement, raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        CsvLogger::log(self, measurement, raw_counts)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        CsvLogger::flush(self)
+    }
+}
+
+/// 測定値をJSON Lines形式で逐次記録する．
+/// ログ収集基盤への転送や，`jq`による手軽な解析に利用できる．
+///
+/// 各行に，次のキーをもつJSONオブジェクトを1つずつ記録する．
+/// ```json
+/// {"sequence":0,"unix_time_s":1700000000.25,"record_number":0,"wrench":[0.1,-0.2,0.3,0.001,-0.002,0.003],"overload":[false,false,false,false,false,false],"raw_counts":[8192,8192,8192,8192,8192,8192]}
+/// ```
+/// `wrench`は`[fx, fy, fz, tx, ty, tz]`の順に並べた測定値(SI単位)である．
+///
+/// 書き込みはバッファリングし，一定の間隔ごとにファイルへ書き出す．
+#[cfg(feature = "json")]
+pub struct JsonLinesLogger<W: Write> {
+    /// 書き込み先．
+    writer: BufWriter<W>,
+    /// 書き込み先に書き出す間隔．
+    flush_interval: Duration,
+    /// 最後に書き込み先に書き出した時刻．
+    last_flush: Instant,
+}
+
+#[cfg(feature = "json")]
+impl JsonLinesLogger<File> {
+    /// ファイルを作成する．既にファイルが存在する場合は上書きする．
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<JsonLinesLogger<File>> {
+        let file = File::create(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<W: Write> JsonLinesLogger<W> {
+    /// 任意の書き込み先に記録を始める．
+    pub fn new(writer: W) -> JsonLinesLogger<W> {
+        JsonLinesLogger {
+            writer: BufWriter::new(writer),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// 書き込み先に書き出す間隔を設定する．既定では1秒．
+    /// `Duration::from_secs(0)`を設定すると，記録のたびに書き出す．
+    pub fn set_flush_interval(&mut self, interval: Duration) {
+        self.flush_interval = interval;
+    }
+
+    /// 書き込み先に書き出す間隔を返す．
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval
+    }
+
+    /// 測定値を1行記録する．
+    /// # Params
+    /// 1. `measurement`: 記録する測定値．
+    /// 1. `raw_counts`: 測定値の換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub fn log(
+        &mut self,
+        measurement: &Measurement,
+        raw_counts: [u16; AXIS_COUNT],
+    ) -> io::Result<()> {
+        let record = JsonRecord {
+            measurement,
+            raw_counts,
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        writeln!(self.writer)?;
+
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.writer.flush()
+    }
+
+    /// バッファリングしている内容を書き出し，書き込み先を返す．
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+#[cfg(feature = "json")]
+impl<W: Write> MeasurementSink for JsonLinesLogger<W> {
+    fn log(&mut self, measurement: &Measurement, raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        JsonLinesLogger::log(self, measurement, raw_counts)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        JsonLinesLogger::flush(self)
+    }
+}
+
+/// JSON Lines形式の1行として記録する内容．
+#[cfg(feature = "json")]
+struct JsonRecord<'a> {
+    /// 記録する測定値．
+    measurement: &'a Measurement,
+    /// 測定値の換算前の各軸のデジタル出力値．
+    raw_counts: [u16; AXIS_COUNT],
+}
+
+/// `jq`などで扱いやすいよう，受信時刻は`SystemTime`の構造ではなくUNIX時刻[s]として出力する．
+#[cfg(feature = "json")]
+impl serde::Serialize for JsonRecord<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let unix_time = self
+            .measurement
+            .system_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let mut state = serializer.serialize_struct("JsonRecord", 6)?;
+        state.serialize_field("sequence", &self.measurement.sequence)?;
+        state.serialize_field("unix_time_s", &unix_time)?;
+        state.serialize_field("record_number", &self.measurement.record_number)?;
+        state.serialize_field("wrench", &self.measurement.wrench)?;
+        state.serialize_field("overload", &self.measurement.overload)?;
+        state.serialize_field("raw_counts", &self.raw_counts)?;
+        state.end()
+    }
+}
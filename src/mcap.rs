@@ -0,0 +1,336 @@
+//! 測定値をMCAP形式のファイルに書き出すための機能．
+//!
+//! 測定値はROS 2の`geometry_msgs/msg/WrenchStamped`メッセージとしてCDR形式で格納する．
+//! 書き出すファイルは索引を持たない最小限の構成であり，FoxgloveやPlotJugglerなどでそのまま読み込める．
+
+use crate::logging::MeasurementSink;
+use crate::{BinaryLogRecord, Measurement, Wrench, AXIS_COUNT};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// MCAP形式のファイルの先頭と末尾に置くマジックナンバー．
+const MAGIC: [u8; 8] = *b"\x89MCAP0\r\n";
+/// Headerレコードのオペコード．
+const OP_HEADER: u8 = 0x01;
+/// Footerレコードのオペコード．
+const OP_FOOTER: u8 = 0x02;
+/// Schemaレコードのオペコード．
+const OP_SCHEMA: u8 = 0x03;
+/// Channelレコードのオペコード．
+const OP_CHANNEL: u8 = 0x04;
+/// Messageレコードのオペコード．
+const OP_MESSAGE: u8 = 0x05;
+/// DataEndレコードのオペコード．
+const OP_DATA_END: u8 = 0x0F;
+/// 測定値のメッセージのスキーマID．
+const SCHEMA_ID: u16 = 1;
+/// 測定値のメッセージのチャネルID．
+const CHANNEL_ID: u16 = 1;
+/// 測定値のメッセージの型名．
+const SCHEMA_NAME: &str = "geometry_msgs/msg/WrenchStamped";
+/// 測定値のメッセージの定義．依存するメッセージの定義を連結したもの．
+const SCHEMA_DEFINITION: &str = "\
+std_msgs/Header header
+geometry_msgs/Wrench wrench
+================================================================================
+MSG: std_msgs/Header
+builtin_interfaces/Time stamp
+string frame_id
+================================================================================
+MSG: builtin_interfaces/Time
+int32 sec
+uint32 nanosec
+================================================================================
+MSG: geometry_msgs/Wrench
+Vector3 force
+Vector3 torque
+================================================================================
+MSG: geometry_msgs/Vector3
+float64 x
+float64 y
+float64 z
+";
+
+/// 測定値を`geometry_msgs/msg/WrenchStamped`メッセージとしてMCAP形式のファイルに書き出す．
+/// 記録した測定値を，ロボットの他のトピックとあわせて可視化するために用いる．
+///
+/// ファイルの末尾は`finish`を呼び出した時点で書き込むため，書き出しの最後には必ず`finish`を呼び出すこと．
+pub struct McapExporter<W: Write> {
+    /// 書き込み先．
+    writer: BufWriter<W>,
+    /// メッセージのヘッダに設定する座標系の名前．
+    frame_id: String,
+}
+
+impl McapExporter<File> {
+    /// ファイルを作成し，先頭部分を書き込む．既にファイルが存在する場合は上書きする．
+    /// # Params
+    /// 1. `path`: 書き出すファイルのパス．
+    /// 1. `topic`: メッセージを格納するトピック名．
+    /// 1. `frame_id`: メッセージのヘッダに設定する座標系の名前．
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        topic: &str,
+        frame_id: &str,
+    ) -> io::Result<McapExporter<File>> {
+        let file = File::create(path)?;
+        Self::new(file, topic, frame_id)
+    }
+}
+
+impl<W: Write> McapExporter<W> {
+    /// 任意の書き込み先に先頭部分を書き込み，書き出しを始める．
+    /// # Params
+    /// 1. `writer`: 書き込み先．
+    /// 1. `topic`: メッセージを格納するトピック名．
+    /// 1. `frame_id`: メッセージのヘッダに設定する座標系の名前．
+    pub fn new(writer: W, topic: &str, frame_id: &str) -> io::Result<McapExporter<W>> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&MAGIC)?;
+
+        let mut header = vec![];
+        put_string(&mut header, "ros2");
+        put_string(&mut header, env!("CARGO_PKG_NAME"));
+        write_record(&mut writer, OP_HEADER, &header)?;
+
+        let mut schema = SCHEMA_ID.to_le_bytes().to_vec();
+        put_string(&mut schema, SCHEMA_NAME);
+        put_string(&mut schema, "ros2msg");
+        put_bytes(&mut schema, SCHEMA_DEFINITION.as_bytes());
+        write_record(&mut writer, OP_SCHEMA, &schema)?;
+
+        let mut channel = CHANNEL_ID.to_le_bytes().to_vec();
+        channel.extend_from_slice(&SCHEMA_ID.to_le_bytes());
+        put_string(&mut channel, topic);
+        put_string(&mut channel, "cdr");
+        // メタデータは持たないため，空の連想配列とする
+        channel.extend_from_slice(&0u32.to_le_bytes());
+        write_record(&mut writer, OP_CHANNEL, &channel)?;
+
+        Ok(McapExporter {
+            writer,
+            frame_id: frame_id.to_string(),
+        })
+    }
+
+    /// レンチを1件書き出す．
+    /// # Params
+    /// 1. `wrench`: 書き出すレンチ．
+    /// 1. `system_time`: レンチを取得した時刻．メッセージのヘッダと記録時刻に用いる．
+    /// 1. `sequence`: メッセージの通し番号．
+    pub fn write(
+        &mut self,
+        wrench: &Wrench,
+        system_time: SystemTime,
+        sequence: u64,
+    ) -> io::Result<()> {
+        let stamp = system_time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let log_time = stamp.as_nanos() as u64;
+
+        let mut message = CHANNEL_ID.to_le_bytes().to_vec();
+        // MCAPの通し番号は32ビットなので，下位32ビットを用いる
+        message.extend_from_slice(&(sequence as u32).to_le_bytes());
+        message.extend_from_slice(&log_time.to_le_bytes());
+        message.extend_from_slice(&log_time.to_le_bytes());
+        message.extend_from_slice(&encode_wrench_stamped(
+            wrench,
+            stamp.as_secs() as i32,
+            stamp.subsec_nanos(),
+            &self.frame_id,
+        ));
+        write_record(&mut self.writer, OP_MESSAGE, &message)
+    }
+
+    /// 測定値を1件書き出す．
+    pub fn write_measurement(&mut self, measurement: &Measurement) -> io::Result<()> {
+        self.write(
+            &measurement.wrench,
+            measurement.system_time,
+            measurement.sequence,
+        )
+    }
+
+    /// `BinaryLogReader`で読み出した測定値を1件書き出す．
+    pub fn write_binary_record(&mut self, record: &BinaryLogRecord) -> io::Result<()> {
+        self.write(&record.wrench, record.system_time, record.sequence)
+    }
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// ファイルの末尾を書き込んで書き出しを終え，書き込み先を返す．
+    pub fn finish(mut self) -> io::Result<W> {
+        // CRCは計算しないため0とする
+        write_record(&mut self.writer, OP_DATA_END, &0u32.to_le_bytes())?;
+
+        // 索引を持たないため，要約部分の位置は0とする
+        let mut footer = vec![0; 16];
+        footer.extend_from_slice(&0u32.to_le_bytes());
+        write_record(&mut self.writer, OP_FOOTER, &footer)?;
+
+        self.writer.write_all(&MAGIC)?;
+        self.writer.flush()?;
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl<W: Write> MeasurementSink for McapExporter<W> {
+    /// 換算前のデジタル出力値は`WrenchStamped`に含まれないため，書き出さない．
+    fn log(&mut self, measurement: &Measurement, _raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        self.write_measurement(measurement)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        McapExporter::flush(self)
+    }
+}
+
+/// オペコードと長さを付けて，MCAPのレコードを1つ書き込む．
+fn write_record<W: Write>(writer: &mut W, opcode: u8, content: &[u8]) -> io::Result<()> {
+    writer.write_all(&[opcode])?;
+    writer.write_all(&(content.len() as u64).to_le_bytes())?;
+    writer.write_all(content)
+}
+
+/// MCAPの文字列として，長さを付けて書き込む．
+fn put_string(buffer: &mut Vec<u8>, value: &str) {
+    put_bytes(buffer, value.as_bytes());
+}
+
+/// MCAPのバイト列として，長さを付けて書き込む．
+fn put_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value);
+}
+
+/// `geometry_msgs/msg/WrenchStamped`メッセージを，リトルエンディアンのCDR形式で表したバイト列を返す．
+fn encode_wrench_stamped(wrench: &Wrench, sec: i32, nanosec: u32, frame_id: &str) -> Vec<u8> {
+    // 先頭の4バイトはエンコーディングを表すヘッダであり，以降の位置合わせの基準には含めない
+    let mut message = vec![0x00, 0x01, 0x00, 0x00];
+    message.extend_from_slice(&sec.to_le_bytes());
+    message.extend_from_slice(&nanosec.to_le_bytes());
+    // 文字列は終端のヌル文字を含めた長さを前に置く
+    message.extend_from_slice(&(frame_id.len() as u32 + 1).to_le_bytes());
+    message.extend_from_slice(frame_id.as_bytes());
+    message.push(0);
+    while (message.len() - 4) % 8 != 0 {
+        message.push(0);
+    }
+    for e in wrench.to_array().iter() {
+        message.extend_from_slice(&e.to_le_bytes());
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+    use std::time::Duration;
+
+    /// 先頭と末尾のマジックナンバーを除いた部分を，オペコードと内容の組に分ける．
+    fn split_records(bytes: &[u8]) -> Vec<(u8, &[u8])> {
+        assert_eq!(&bytes[..MAGIC.len()], &MAGIC);
+        assert_eq!(&bytes[bytes.len() - MAGIC.len()..], &MAGIC);
+
+        let mut rest = &bytes[MAGIC.len()..bytes.len() - MAGIC.len()];
+        let mut records = vec![];
+        while !rest.is_empty() {
+            let length = u64::from_le_bytes(rest[1..9].try_into().unwrap()) as usize;
+            records.push((rest[0], &rest[9..9 + length]));
+            rest = &rest[9 + length..];
+        }
+        records
+    }
+
+    /// 長さを前に置いた文字列を順に並べたバイト列を作る．
+    fn strings(values: &[&str]) -> Vec<u8> {
+        let mut buffer = vec![];
+        for value in values {
+            put_string(&mut buffer, value);
+        }
+        buffer
+    }
+
+    #[test]
+    fn exported_file_consists_of_expected_records() {
+        let wrench = Wrench::from_array([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let system_time = UNIX_EPOCH + Duration::new(1_700_000_000, 250);
+        let mut exporter = McapExporter::new(Vec::new(), "/wrench", "ft").unwrap();
+        exporter.write(&wrench, system_time, 0x1_0000_0007).unwrap();
+        let bytes = exporter.finish().unwrap();
+
+        let records = split_records(&bytes);
+        let opcodes: Vec<_> = records.iter().map(|&(opcode, _)| opcode).collect();
+        assert_eq!(
+            opcodes,
+            [
+                OP_HEADER,
+                OP_SCHEMA,
+                OP_CHANNEL,
+                OP_MESSAGE,
+                OP_DATA_END,
+                OP_FOOTER
+            ]
+        );
+
+        assert_eq!(
+            records[0].1,
+            strings(&["ros2", env!("CARGO_PKG_NAME")]).as_slice()
+        );
+
+        let mut schema = SCHEMA_ID.to_le_bytes().to_vec();
+        schema.extend_from_slice(&strings(&[SCHEMA_NAME, "ros2msg", SCHEMA_DEFINITION]));
+        assert_eq!(records[1].1, schema.as_slice());
+
+        let mut channel = CHANNEL_ID.to_le_bytes().to_vec();
+        channel.extend_from_slice(&SCHEMA_ID.to_le_bytes());
+        channel.extend_from_slice(&strings(&["/wrench", "cdr"]));
+        channel.extend_from_slice(&[0; 4]);
+        assert_eq!(records[2].1, channel.as_slice());
+
+        let message = records[3].1;
+        let log_time = 1_700_000_000_000_000_250u64.to_le_bytes();
+        assert_eq!(&message[0..2], &CHANNEL_ID.to_le_bytes());
+        // 通し番号は下位32ビットのみを格納する
+        assert_eq!(&message[2..6], &7u32.to_le_bytes());
+        assert_eq!(&message[6..14], &log_time);
+        assert_eq!(&message[14..22], &log_time);
+        assert_eq!(
+            &message[22..],
+            encode_wrench_stamped(&wrench, 1_700_000_000, 250, "ft").as_slice()
+        );
+
+        assert_eq!(records[4].1, &[0; 4]);
+        assert_eq!(records[5].1, &[0; 20]);
+    }
+
+    #[test]
+    fn wrench_stamped_fields_are_aligned_in_cdr() {
+        let wrench = Wrench::from_array([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let wrench_bytes: Vec<u8> = wrench
+            .to_array()
+            .iter()
+            .flat_map(|e| e.to_le_bytes())
+            .collect();
+
+        // "ft"は終端を含めて3バイトであり，ヘッダを除いた位置で15バイトめまで占めるので1バイト詰める
+        let message = encode_wrench_stamped(&wrench, -2, 5, "ft");
+        assert_eq!(&message[..4], &[0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(&message[4..8], &(-2i32).to_le_bytes());
+        assert_eq!(&message[8..12], &5u32.to_le_bytes());
+        assert_eq!(&message[12..16], &3u32.to_le_bytes());
+        assert_eq!(&message[16..20], b"ft\0\0");
+        assert_eq!(&message[20..], wrench_bytes.as_slice());
+
+        // "base"は終端を含めて5バイトであり，ヘッダを除いた位置で17バイトめまで占めるので7バイト詰める
+        let message = encode_wrench_stamped(&wrench, 0, 0, "base");
+        assert_eq!(&message[12..16], &5u32.to_le_bytes());
+        assert_eq!(&message[16..28], b"base\0\0\0\0\0\0\0\0");
+        assert_eq!(&message[28..], wrench_bytes.as_slice());
+    }
+}
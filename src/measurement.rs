@@ -0,0 +1,24 @@
+//! 時刻情報付きの測定値．
+
+use crate::{Wrench, AXIS_COUNT};
+use std::time::{Instant, SystemTime};
+
+/// センサから取得した測定値と，その取得時刻．
+///
+/// `sequence`はホスト側で受信のたびに1ずつ増える通し番号であり，一周することはない．
+/// チャネルやフィルタを経た後段で，測定値の順序の入れ替わりや重複を検出するために利用できる．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    /// 測定値．
+    pub wrench: Wrench,
+    /// 測定値を受信した時刻．制御周期の計算など，時間間隔を求めるために用いる．
+    pub timestamp: Instant,
+    /// 測定値を受信した時刻のシステム時刻．ログの記録など，絶対時刻が必要な場合に用いる．
+    pub system_time: SystemTime,
+    /// ホスト側で付与した通し番号．センサとの通信を確立してから最初に受信した測定値が0となる．
+    pub sequence: u64,
+    /// センサが付与したフレームのレコード番号．
+    pub record_number: u8,
+    /// 各軸が過負荷となっているかどうか．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub overload: [bool; AXIS_COUNT],
+}
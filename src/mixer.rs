@@ -0,0 +1,208 @@
+//! 複数のセンサの測定値の合成．
+
+use crate::{ForceTorqueSensor, FrameTransform, SensorError, Wdf6m200, Wrench, AXIS_COUNT};
+
+/// 複数のセンサの測定値を，センサごとの重みで加重平均する．
+/// 双腕での操作など，複数のセンサで同じ対象から受ける力を測る場合に利用できる．
+///
+/// 既定では重みの総和が1となるように正規化してから合成する．
+pub struct WrenchMixer {
+    /// 合成するセンサとその重み．
+    sensors: Vec<(Box<dyn ForceTorqueSensor>, f64)>,
+    /// 重みを正規化してから合成するかどうか．
+    normalize: bool,
+}
+
+impl WrenchMixer {
+    /// センサを1つも持たない状態で作る．
+    pub fn new() -> WrenchMixer {
+        WrenchMixer {
+            sensors: vec![],
+            normalize: true,
+        }
+    }
+
+    /// 合成するセンサを追加する．
+    pub fn add_sensor(&mut self, sensor: Box<dyn ForceTorqueSensor>, weight: f64) {
+        self.sensors.push((sensor, weight));
+    }
+
+    /// `index`番目に追加したセンサを取り除き，そのセンサを返す．
+    /// # Panics
+    /// `index`が追加したセンサの数以上の場合．
+    pub fn remove_sensor(&mut self, index: usize) -> Box<dyn ForceTorqueSensor> {
+        self.sensors.remove(index).0
+    }
+
+    /// 現在合成しているセンサの数を返す．
+    pub fn sensor_count(&self) -> usize {
+        self.sensors.len()
+    }
+
+    /// 重みを正規化してから合成するかどうかを設定する．
+    /// `false`を設定した場合，各センサの測定値に重みを掛けたものの総和をそのまま合成結果とする．
+    pub fn set_normalization(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    /// すべてのセンサと通信して，測定値情報を更新する．
+    /// 一部のセンサで更新に失敗した場合も残りのセンサの更新は行い，最初に発生したエラーを返す．
+    pub fn update_all(&mut self) -> Result<(), SensorError> {
+        let mut result = Ok(());
+        for (sensor, _) in self.sensors.iter_mut() {
+            if let Err(e) = sensor.update() {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// 各センサの最後の測定値を重みで合成したレンチを返す．
+    /// センサがない場合や，正規化する設定で重みの総和が0の場合は，力とトルクが0であるレンチを返す．
+    pub fn mixed_measurement(&self) -> Wrench {
+        let weight_sum: f64 = self.sensors.iter().map(|(_, weight)| weight).sum();
+        let scale = if !self.normalize {
+            1.0
+        } else if weight_sum == 0.0 {
+            return Wrench::zeroed();
+        } else {
+            1.0 / weight_sum
+        };
+
+        let mut mixed = [0.0; AXIS_COUNT];
+        for (sensor, weight) in self.sensors.iter() {
+            let measurement = sensor.last_measurement().to_array();
+            for (m, e) in mixed.iter_mut().zip(measurement.iter()) {
+                *m += weight * scale * e;
+            }
+        }
+
+        Wrench::from_array(mixed)
+    }
+}
+
+impl Default for WrenchMixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 同じ剛体に取り付けた2つのセンサの測定値から，同相成分と差動成分とを求める．
+///
+/// 2つのセンサの測定値は座標変換後には本来一致するはずなので，
+/// 同相成分(平均)はノイズを抑えた測定値として，差動成分(差)は故障の兆候として利用できる．
+///
+/// センサBの測定値は`FrameTransform::apply`によってセンサAの原点にはたらくレンチに変換してから比較するため，
+/// 2つのセンサの原点が離れていてもよい．
+/// 変換はセンサAとセンサBの間の剛体にはたらく外力がないものとみなすため，
+/// 外力は2つのセンサの外側からはたらく必要がある．
+pub struct WrenchDifferentialFilter {
+    /// 基準とするセンサ．
+    sensor_a: Wdf6m200,
+    /// もう一方のセンサ．
+    sensor_b: Wdf6m200,
+    /// センサBの座標系からセンサAの座標系への変換．
+    transform_ab: FrameTransform,
+}
+
+impl WrenchDifferentialFilter {
+    /// # Params
+    /// 1. `sensor_a`: 基準とするセンサ．
+    /// 1. `sensor_b`: もう一方のセンサ．
+    /// 1. `transform_ab`: センサBの座標系からセンサAの座標系への変換．
+    ///    センサAの座標系から見たセンサBの姿勢と原点の位置を表す．
+    pub fn new(
+        sensor_a: Wdf6m200,
+        sensor_b: Wdf6m200,
+        transform_ab: FrameTransform,
+    ) -> WrenchDifferentialFilter {
+        WrenchDifferentialFilter {
+            sensor_a,
+            sensor_b,
+            transform_ab,
+        }
+    }
+
+    /// 両方のセンサと通信して，測定値情報を更新する．
+    /// 一方のセンサで更新に失敗した場合ももう一方のセンサの更新は行い，最初に発生したエラーを返す．
+    pub fn update(&mut self) -> Result<(), SensorError> {
+        let result_a = self.sensor_a.update();
+        let result_b = self.sensor_b.update();
+        result_a.and(result_b)
+    }
+
+    /// 2つのセンサの測定値の平均を，センサAの座標系で返す．
+    pub fn common_mode(&self) -> Wrench {
+        let (a, b) = self.measurements();
+        let mut common = [0.0; AXIS_COUNT];
+        for (c, (a, b)) in common.iter_mut().zip(a.iter().zip(b.iter())) {
+            *c = (a + b) / 2.0;
+        }
+        Wrench::from_array(common)
+    }
+
+    /// センサAの測定値からセンサBの測定値を減じたものを，センサAの座標系で返す．
+    pub fn differential_mode(&self) -> Wrench {
+        let (a, b) = self.measurements();
+        Wrench::from_array(a) - Wrench::from_array(b)
+    }
+
+    /// センサAへの参照を返す．
+    pub fn sensor_a(&mut self) -> &mut Wdf6m200 {
+        &mut self.sensor_a
+    }
+
+    /// センサBへの参照を返す．
+    pub fn sensor_b(&mut self) -> &mut Wdf6m200 {
+        &mut self.sensor_b
+    }
+
+    /// 2つのセンサを返す．
+    pub fn into_sensors(self) -> (Wdf6m200, Wdf6m200) {
+        (self.sensor_a, self.sensor_b)
+    }
+
+    /// センサAの原点にはたらくレンチとしてセンサAの座標系で表した，2つのセンサの最後の測定値を返す．
+    fn measurements(&self) -> ([f64; AXIS_COUNT], [f64; AXIS_COUNT]) {
+        let a = self.sensor_a.last_measurement();
+        let b = self.transform_ab.apply(&self.sensor_b.last_measurement());
+        (a.to_array(), b.to_array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_port::FakeSerialPort;
+
+    fn connect_fake() -> Wdf6m200 {
+        let port = FakeSerialPort::new(std::time::Duration::from_millis(10));
+        Wdf6m200::from_serial_port(Box::new(port), None).unwrap()
+    }
+
+    #[test]
+    fn offset_sensors_agree_after_transform() {
+        // センサBはセンサAのz軸方向0.1mの位置に，同じ向きで取り付けられている
+        let mut sensor_a = connect_fake();
+        let mut sensor_b = connect_fake();
+        // センサBで測った1Nの力は，センサAの原点まわりに(0, 0, 0.1) × (1, 0, 0) = (0, 0.1, 0)のトルクを生じる
+        sensor_b.raw_wrench = Wrench::from_array([1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        sensor_a.raw_wrench = Wrench::from_array([1.0, 0.0, 0.0, 0.0, 0.1, 0.0]);
+
+        let filter = WrenchDifferentialFilter::new(
+            sensor_a,
+            sensor_b,
+            FrameTransform::from_translation([0.0, 0.0, 0.1]),
+        );
+
+        let common = filter.common_mode().to_array();
+        let differential = filter.differential_mode().to_array();
+        let expected = [1.0, 0.0, 0.0, 0.0, 0.1, 0.0];
+        for i in 0..AXIS_COUNT {
+            assert!((common[i] - expected[i]).abs() < 1e-9, "{:?}", common);
+            assert!(differential[i].abs() < 1e-9, "{:?}", differential);
+        }
+    }
+}
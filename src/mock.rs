@@ -0,0 +1,106 @@
+//! 実機のセンサを接続せずにテストを行うためのモック．
+
+use crate::calibration::CalibrationAccumulator;
+use crate::{CalibrationReport, ForceTorqueSensor, SensorError, Wrench};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// あらかじめ与えた筋書きどおりに測定値やエラーを返す，テスト用のセンサ．
+/// 力覚センサを接続していないCI環境などで，`ForceTorqueSensor`を利用するコードを試験できる．
+///
+/// `update`を呼ぶたびに筋書きの先頭から1つずつ取り出し，測定値であればそれをセンサの生の出力値とし，
+/// エラーであればそのエラーを返す．筋書きを使い切った後の`update`はタイムアウトのエラーを返す．
+#[derive(Debug)]
+pub struct MockSensor {
+    /// これから返す測定値またはエラー．
+    script: VecDeque<Result<Wrench, SensorError>>,
+    /// 現在のセンサ出力値．
+    raw_wrench: Wrench,
+    /// センサ出力値から減ずる補正値．
+    offset: Wrench,
+    /// `update`が呼ばれた回数．
+    update_count: usize,
+}
+
+impl MockSensor {
+    /// 筋書きが空の状態で作る．
+    pub fn new() -> MockSensor {
+        MockSensor {
+            script: VecDeque::new(),
+            raw_wrench: Wrench::zeroed(),
+            offset: Wrench::zeroed(),
+            update_count: 0,
+        }
+    }
+
+    /// 与えた測定値を順に返す筋書きで作る．
+    pub fn from_wrenches(wrenches: impl IntoIterator<Item = Wrench>) -> MockSensor {
+        let mut sensor = Self::new();
+        for wrench in wrenches {
+            sensor.push_wrench(wrench);
+        }
+        sensor
+    }
+
+    /// 筋書きの末尾に測定値を追加する．
+    pub fn push_wrench(&mut self, wrench: Wrench) {
+        self.script.push_back(Ok(wrench));
+    }
+
+    /// 筋書きの末尾にエラーを追加する．
+    pub fn push_error(&mut self, error: SensorError) {
+        self.script.push_back(Err(error));
+    }
+
+    /// 筋書きに残っている測定値とエラーの数を返す．
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+
+    /// これまでに`update`が呼ばれた回数を返す．
+    pub fn update_count(&self) -> usize {
+        self.update_count
+    }
+}
+
+impl Default for MockSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ForceTorqueSensor for MockSensor {
+    fn update(&mut self) -> Result<(), SensorError> {
+        self.update_count += 1;
+        let next = self
+            .script
+            .pop_front()
+            .unwrap_or_else(|| Err(SensorError::Io(std::io::ErrorKind::TimedOut.into())));
+        self.raw_wrench = next?;
+        Ok(())
+    }
+
+    fn last_measurement(&self) -> Wrench {
+        self.raw_wrench - self.offset
+    }
+
+    /// 実機のセンサと同様にゼロ点を定めるが，テストを速やかに行うため`measurement_period`の待機は行わない．
+    fn calibrate(
+        &mut self,
+        _measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError> {
+        assert!(measurement_times > 0);
+
+        let mut accumulator = CalibrationAccumulator::new();
+        for _ in 0..measurement_times {
+            match self.update() {
+                Ok(()) => accumulator.push(self.raw_wrench),
+                Err(_) => accumulator.push_failure(),
+            }
+        }
+        let report = accumulator.finish()?;
+        self.offset = report.mean;
+        Ok(report)
+    }
+}
@@ -0,0 +1,245 @@
+//! Wacohtech DynPickシリーズの各型式の仕様．
+
+use crate::protocol::{self, FrameError, RawReading};
+use crate::{NewtonMeter, PerNewton, PerNewtonMeter, Wrench, WrenchF32, AXIS_COUNT};
+use dimensioned::si::Newton;
+use pair_macro::Triplet;
+use std::fmt::{self, Display, Formatter};
+
+/// このクレートで扱えるWacohtech DynPickシリーズのセンサの型式．
+/// いずれの型式も同じシリアル通信プロトコルを用いるが，定格荷重と感度が異なる．
+///
+/// WDF-6M200-3の感度と定格荷重は，このクレートが当初から用いてきたセンサの仕様表の値である．
+/// WDF-6M200-14とWEF-6A200-4については仕様表の値を確認できていないため，感度と定格荷重の既定値を持たない．
+/// これらの型式では，`DynPick::read_device_sensitivity`でセンサに書き込まれた感度を読み出すか，
+/// `DynPick::set_sensitivity`で検査成績書の値を設定してから測定すること．
+/// いずれの型式でも感度は個体ごとに異なるため，正確な測定にはこれらを用いるのが望ましい．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SensorModel {
+    /// WDF-6M200-3．
+    Wdf6m200_3,
+    /// WDF-6M200-14．
+    Wdf6m200_14,
+    /// WEF-6A200-4．
+    Wef6a200_4,
+}
+
+impl SensorModel {
+    /// このクレートで扱えるすべての型式．
+    pub const ALL: [SensorModel; 3] = [
+        SensorModel::Wdf6m200_3,
+        SensorModel::Wdf6m200_14,
+        SensorModel::Wef6a200_4,
+    ];
+
+    /// 型式名を返す．
+    pub const fn name(self) -> &'static str {
+        match self {
+            SensorModel::Wdf6m200_3 => "WDF-6M200-3",
+            SensorModel::Wdf6m200_14 => "WDF-6M200-14",
+            SensorModel::Wef6a200_4 => "WEF-6A200-4",
+        }
+    }
+
+    /// 各軸の力の定格荷重を返す．
+    /// 既定値を持たない型式では`None`を返す．
+    pub fn rated_force(self) -> Option<Triplet<Newton<f64>>> {
+        match self {
+            SensorModel::Wdf6m200_3 => Some(Triplet::from_cloned(Newton::new(200.0))),
+            SensorModel::Wdf6m200_14 | SensorModel::Wef6a200_4 => None,
+        }
+    }
+
+    /// 各軸のトルクの定格荷重を返す．
+    /// 既定値を持たない型式では`None`を返す．
+    pub fn rated_torque(self) -> Option<Triplet<NewtonMeter<f64>>> {
+        match self {
+            SensorModel::Wdf6m200_3 => Some(Triplet::from_cloned(NewtonMeter::new(4.0))),
+            SensorModel::Wdf6m200_14 | SensorModel::Wef6a200_4 => None,
+        }
+    }
+
+    /// 各軸の定格荷重を返す．
+    /// 既定値を持たない型式では`None`を返す．
+    pub fn rated_load(self) -> Option<RatedLoad> {
+        Some(RatedLoad {
+            force: self.rated_force()?,
+            torque: self.rated_torque()?,
+        })
+    }
+
+    /// 無負荷時のデジタル出力値の中心値を返す．
+    pub const fn center_count(self) -> u16 {
+        match self {
+            SensorModel::Wdf6m200_3 | SensorModel::Wdf6m200_14 | SensorModel::Wef6a200_4 => 8192,
+        }
+    }
+
+    /// センサ各軸について，1Nあたりデジタル出力値がいくつ変化するかを返す．
+    /// 既定値を持たない型式では`None`を返す．
+    pub fn force_sensitivity(self) -> Option<Triplet<PerNewton<f64>>> {
+        match self {
+            SensorModel::Wdf6m200_3 => {
+                Some(Triplet::new(24.9, 24.6, 24.5).map(PerNewton::<f64>::new))
+            }
+            SensorModel::Wdf6m200_14 | SensorModel::Wef6a200_4 => None,
+        }
+    }
+
+    /// センサ各軸について，1Nmあたりデジタル出力値がいくつ変化するかを返す．
+    /// 既定値を持たない型式では`None`を返す．
+    pub fn torque_sensitivity(self) -> Option<Triplet<PerNewtonMeter<f64>>> {
+        match self {
+            SensorModel::Wdf6m200_3 => {
+                Some(Triplet::new(1664.7, 1639.7, 1638.0).map(PerNewtonMeter::<f64>::new))
+            }
+            SensorModel::Wdf6m200_14 | SensorModel::Wef6a200_4 => None,
+        }
+    }
+}
+
+/// センサ各軸の定格荷重．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatedLoad {
+    /// 各軸の力の定格荷重．
+    pub force: Triplet<Newton<f64>>,
+    /// 各軸のトルクの定格荷重．
+    pub torque: Triplet<NewtonMeter<f64>>,
+}
+
+impl RatedLoad {
+    /// 定格荷重を`[fx, fy, fz, tx, ty, tz]`の順に並べて返す．
+    pub fn to_array(self) -> [f64; crate::AXIS_COUNT] {
+        Wrench::new(self.force, self.torque).to_array()
+    }
+}
+
+impl Default for SensorModel {
+    /// `Wdf6m200::open`が想定する型式であるWDF-6M200-3を返す．
+    fn default() -> Self {
+        SensorModel::Wdf6m200_3
+    }
+}
+
+impl Display for SensorModel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// `DynPick`が扱うセンサの型式ごとの仕様．
+/// 新たな型式に対応するには，この型を実装した型を作ればよい．
+pub trait SensorSpec: 'static {
+    /// センサの型式．
+    const MODEL: SensorModel;
+
+    /// 各軸の力の定格荷重を返す．既定値を持たない場合は`None`を返す．
+    fn rated_force() -> Option<Triplet<Newton<f64>>> {
+        Self::MODEL.rated_force()
+    }
+
+    /// 各軸のトルクの定格荷重を返す．既定値を持たない場合は`None`を返す．
+    fn rated_torque() -> Option<Triplet<NewtonMeter<f64>>> {
+        Self::MODEL.rated_torque()
+    }
+
+    /// 各軸の定格荷重を返す．既定値を持たない場合は`None`を返す．
+    fn rated_load() -> Option<RatedLoad> {
+        Some(RatedLoad {
+            force: Self::rated_force()?,
+            torque: Self::rated_torque()?,
+        })
+    }
+
+    /// 無負荷時のデジタル出力値の中心値を返す．
+    /// デジタル出力値からこの値を差し引いたものが，力やトルクに比例する．
+    fn center_count() -> u16 {
+        Self::MODEL.center_count()
+    }
+
+    /// センサ各軸について，1Nあたりデジタル出力値がいくつ変化するかを返す．
+    /// 既定値を持たない場合は`None`を返す．
+    fn force_sensitivity() -> Option<Triplet<PerNewton<f64>>> {
+        Self::MODEL.force_sensitivity()
+    }
+
+    /// センサ各軸について，1Nmあたりデジタル出力値がいくつ変化するかを返す．
+    /// 既定値を持たない場合は`None`を返す．
+    fn torque_sensitivity() -> Option<Triplet<PerNewtonMeter<f64>>> {
+        Self::MODEL.torque_sensitivity()
+    }
+
+    /// 各軸のデジタル出力値を，この型式の感度を用いて単精度のまま力とトルクに換算する．
+    /// `parse_response`と組み合わせれば，受信したフレームから倍精度を経由せずに単精度の測定値が得られる．
+    /// 感度の既定値を持たない場合は`None`を返す．
+    fn wrench_f32_from_counts(counts: [u16; AXIS_COUNT]) -> Option<WrenchF32> {
+        Some(WrenchF32::from_counts(
+            counts,
+            Self::center_count(),
+            Self::force_sensitivity()?.map(|s| PerNewton::<f32>::new(s.value_unsafe as f32)),
+            Self::torque_sensitivity()?.map(|s| PerNewtonMeter::<f32>::new(s.value_unsafe as f32)),
+        ))
+    }
+
+    /// センサから受信した1フレーム分のデータを解釈する．
+    /// 既定では，DynPickシリーズに共通の応答形式として`protocol::parse_frame`で解釈する．
+    fn parse_response(frame: &[u8]) -> Result<RawReading, FrameError> {
+        protocol::parse_frame(frame)
+    }
+}
+
+/// WDF-6M200-3の仕様．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Wdf6m200Spec;
+
+impl SensorSpec for Wdf6m200Spec {
+    const MODEL: SensorModel = SensorModel::Wdf6m200_3;
+}
+
+/// WDF-6M200-14の仕様．感度と定格荷重の既定値を持たない．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Wdf6m200_14Spec;
+
+impl SensorSpec for Wdf6m200_14Spec {
+    const MODEL: SensorModel = SensorModel::Wdf6m200_14;
+}
+
+/// WEF-6A200-4の仕様．感度と定格荷重の既定値を持たない．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Wef6a200Spec;
+
+impl SensorSpec for Wef6a200Spec {
+    const MODEL: SensorModel = SensorModel::Wef6a200_4;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_is_converted_to_wrench_f32() {
+        let reading = Wdf6m200Spec::parse_response(b"32000207B2000200020002000\r\n").unwrap();
+        assert_eq!(reading.record_number, 3);
+
+        let wrench = Wdf6m200Spec::wrench_f32_from_counts(reading.counts).unwrap();
+        // fyのデジタル出力値は中心値より0x7B = 123大きく，WDF-6M200-3のfyの感度24.6で割ると5Nとなる
+        let expected = [0.0, 5.0, 0.0, 0.0, 0.0, 0.0];
+        for (a, e) in wrench.to_array().iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-5, "{:?}", wrench);
+        }
+    }
+
+    #[test]
+    fn models_without_verified_values_have_no_defaults() {
+        let rated = Wdf6m200Spec::rated_load().unwrap();
+        assert_eq!(rated.to_array(), [200.0, 200.0, 200.0, 4.0, 4.0, 4.0]);
+
+        for model in [SensorModel::Wdf6m200_14, SensorModel::Wef6a200_4] {
+            assert_eq!(model.force_sensitivity(), None);
+            assert_eq!(model.torque_sensitivity(), None);
+            assert_eq!(model.rated_load(), None);
+        }
+        let counts = [Wef6a200Spec::center_count(); AXIS_COUNT];
+        assert!(Wef6a200Spec::wrench_f32_from_counts(counts).is_none());
+    }
+}
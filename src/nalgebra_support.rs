@@ -0,0 +1,52 @@
+//! nalgebraの型との相互変換．
+//! 運動学や動力学の計算にnalgebraを用いている場合に，測定値をそのまま渡せるようにする．
+
+use crate::{Newton, NewtonMeter, Triplet, Wrench};
+use nalgebra::{Scalar, Vector3, Vector6};
+
+impl Wrench {
+    /// 力をSI単位の値の3次元ベクトルとして返す．
+    pub fn force_vector(&self) -> Vector3<f64> {
+        triplet_to_vector3(self.force.map(|e| e.value_unsafe))
+    }
+
+    /// トルクをSI単位の値の3次元ベクトルとして返す．
+    pub fn torque_vector(&self) -> Vector3<f64> {
+        triplet_to_vector3(self.torque.map(|e| e.value_unsafe))
+    }
+
+    /// SI単位の値で表した力とトルクの3次元ベクトルから`Wrench`を作る．
+    pub fn from_vectors(force: &Vector3<f64>, torque: &Vector3<f64>) -> Wrench {
+        let force = vector3_to_triplet(force).map(Newton::new);
+        let torque = vector3_to_triplet(torque).map(NewtonMeter::<f64>::new);
+        Wrench::new(force, torque)
+    }
+}
+
+impl From<Wrench> for Vector6<f64> {
+    /// 各成分をSI単位の値として`[fx, fy, fz, tx, ty, tz]`の順に並べる．
+    fn from(wrench: Wrench) -> Self {
+        let [fx, fy, fz, tx, ty, tz] = wrench.to_array();
+        Vector6::new(fx, fy, fz, tx, ty, tz)
+    }
+}
+
+impl From<Vector6<f64>> for Wrench {
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べたSI単位の値から`Wrench`を作る．
+    fn from(vector: Vector6<f64>) -> Self {
+        Wrench::from_array([
+            vector[0], vector[1], vector[2], vector[3], vector[4], vector[5],
+        ])
+    }
+}
+
+/// `Triplet`を3次元ベクトルに変換する．
+pub fn triplet_to_vector3<T: Scalar>(triplet: Triplet<T>) -> Vector3<T> {
+    let Triplet { x, y, z } = triplet;
+    Vector3::new(x, y, z)
+}
+
+/// 3次元ベクトルを`Triplet`に変換する．
+pub fn vector3_to_triplet<T: Scalar>(vector: &Vector3<T>) -> Triplet<T> {
+    Triplet::new(vector[0].clone(), vector[1].clone(), vector[2].clone())
+}
@@ -0,0 +1,74 @@
+//! `dimensioned`の型を用いずに表したレンチ．
+
+use crate::{Wrench, AXIS_COUNT};
+use std::ops::{Add, Sub};
+
+/// 力とトルクをSI単位の`f64`の値で表したレンチ．
+/// `dimensioned`の型を他のクレートとの境界で受け渡しにくい場合などに，`Wrench`の代わりに利用できる．
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlainWrench {
+    /// 力[N]．`[fx, fy, fz]`の順に並ぶ．
+    pub force: [f64; 3],
+    /// トルク[N·m]．`[tx, ty, tz]`の順に並ぶ．
+    pub torque: [f64; 3],
+}
+
+impl PlainWrench {
+    pub const fn new(force: [f64; 3], torque: [f64; 3]) -> PlainWrench {
+        PlainWrench { force, torque }
+    }
+
+    /// 力とトルクが0である`PlainWrench`を返す．
+    pub const fn zeroed() -> PlainWrench {
+        PlainWrench::new([0.0; 3], [0.0; 3])
+    }
+
+    /// 力とトルクの各成分を`[fx, fy, fz, tx, ty, tz]`の順に並べた配列を返す．
+    pub fn to_array(self) -> [f64; AXIS_COUNT] {
+        let [fx, fy, fz] = self.force;
+        let [tx, ty, tz] = self.torque;
+        [fx, fy, fz, tx, ty, tz]
+    }
+
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べた配列から`PlainWrench`を作る．
+    pub fn from_array(array: [f64; AXIS_COUNT]) -> PlainWrench {
+        let [fx, fy, fz, tx, ty, tz] = array;
+        PlainWrench::new([fx, fy, fz], [tx, ty, tz])
+    }
+}
+
+impl Add for PlainWrench {
+    type Output = PlainWrench;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = self.to_array();
+        for (s, r) in sum.iter_mut().zip(rhs.to_array().iter()) {
+            *s += r;
+        }
+        PlainWrench::from_array(sum)
+    }
+}
+
+impl Sub for PlainWrench {
+    type Output = PlainWrench;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut difference = self.to_array();
+        for (d, r) in difference.iter_mut().zip(rhs.to_array().iter()) {
+            *d -= r;
+        }
+        PlainWrench::from_array(difference)
+    }
+}
+
+impl From<Wrench> for PlainWrench {
+    fn from(wrench: Wrench) -> Self {
+        PlainWrench::from_array(wrench.to_array())
+    }
+}
+
+impl From<PlainWrench> for Wrench {
+    fn from(wrench: PlainWrench) -> Self {
+        Wrench::from_array(wrench.to_array())
+    }
+}
@@ -0,0 +1,295 @@
+//! センサとのシリアル通信で用いるバイト列の生成と解釈．
+//!
+//! このモジュールはシリアルポートに依存しないため，
+//! 非同期処理を用いる通信部や組込み環境への移植，ファジングなどで再利用できる．
+
+use crate::{FilterLevel, AXIS_COUNT};
+use std::fmt::{self, Display, Formatter};
+
+/// センサに1回分の出力値の送信を要求する命令．
+pub const READ_COMMAND: u8 = b'R';
+/// センサに出力値の連続送信を要求する命令．
+pub const STREAM_COMMAND: u8 = b'S';
+/// センサにデータ送信の停止を要求する命令．
+/// ファームウェアによって命令が異なる場合に備えて公開している．
+pub const STOP_COMMAND: u8 = b'E';
+/// センサの内蔵フィルタを設定する命令．直前にフィルタの強さを表す数字を送る．
+pub const FILTER_COMMAND: u8 = b'F';
+/// センサに機器情報の送信を要求する命令．
+pub const VERSION_COMMAND: u8 = b'V';
+/// センサ本体にゼロ点調整を行わせる命令．
+pub const ZERO_COMMAND: u8 = b'O';
+/// センサに書き込まれている各軸の感度の送信を要求する命令．
+pub const SENSITIVITY_COMMAND: u8 = b'p';
+
+// 力覚センサから返ってくるバイト列の解釈方法:
+// ---------------------------
+// X111122223333444455556666++
+// X: 受信データの先頭1バイトはレコード番号．16進数1桁で表され，フレームごとに1ずつ増えて0に戻る．
+// 1111...6666: 次に各軸に対応した電圧が4バイト (合計で6*4=24バイト)．
+// ++: 最後に改行コード(CR+LF)が2バイト
+
+/// 各軸に関するデジタル出力値のバイト数．
+pub const AXIS_DATUM_LENGTH: usize = 4;
+/// 各軸に関するデジタル出力が何バイトめから始まるか．
+pub const AXIS_DATA_START_INDEX: usize = 1;
+/// レコード番号がとりうる値の個数．
+pub const RECORD_NUMBER_MODULUS: u8 = 16;
+/// 各軸に関するデジタル出力値の最大値．
+pub const DIGITAL_OUTPUT_MAX: u16 = 0x3FFF;
+/// 改行コード．
+pub const NEWLINE: [u8; 2] = *b"\r\n";
+/// 改行コードの記述に要するバイト数．
+pub const NEWLINE_BYTES: usize = NEWLINE.len();
+/// 出力値を表す1フレームのバイト数．
+pub const RESPONSE_BYTES: usize =
+    AXIS_DATA_START_INDEX + AXIS_DATUM_LENGTH * AXIS_COUNT + NEWLINE_BYTES;
+
+/// センサから受信した1フレーム分の出力値．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawReading {
+    /// フレームの先頭に付与されたレコード番号．`0`以上`RECORD_NUMBER_MODULUS`未満の値をとる．
+    pub record_number: u8,
+    /// 各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub counts: [u16; AXIS_COUNT],
+}
+
+/// 受信したフレームを解釈できなかったことを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// フレームの長さが`RESPONSE_BYTES`と一致しない．値は実際の長さ．
+    InvalidLength(usize),
+    /// フレームが改行コードで終わっていない．
+    MissingNewline,
+    /// デジタル出力値を表す部分に16進数の数字でないバイトが含まれる．値はそのバイトの位置．
+    InvalidDigit(usize),
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::InvalidLength(len) => write!(
+                f,
+                "A frame should be {} bytes long, but actually {} bytes",
+                RESPONSE_BYTES, len
+            ),
+            FrameError::MissingNewline => write!(f, "A frame does not end with CR+LF"),
+            FrameError::InvalidDigit(index) => {
+                write!(f, "The byte at {} in a frame is not a hex digit", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// センサに送信する命令．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    /// 1回分の出力値の送信を要求する．
+    Read,
+    /// 出力値の連続送信を開始する．
+    StartStreaming,
+    /// データ送信を停止する．
+    Stop,
+    /// センサ本体にゼロ点調整を行わせる．
+    Zero,
+    /// 内蔵フィルタを設定する．
+    SetFilter(FilterLevel),
+    /// 各軸の感度の送信を要求する．
+    ReadSensitivity,
+    /// 機器情報の送信を要求する．
+    ReadVersion,
+}
+
+impl Command {
+    /// この命令を表すバイト列を返す．
+    pub const fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Command::Read => &[READ_COMMAND],
+            Command::StartStreaming => &[STREAM_COMMAND],
+            Command::Stop => &[STOP_COMMAND],
+            Command::Zero => &[ZERO_COMMAND],
+            Command::SetFilter(level) => &FILTER_SETTINGS[level as usize],
+            Command::ReadSensitivity => &[SENSITIVITY_COMMAND],
+            Command::ReadVersion => &[VERSION_COMMAND],
+        }
+    }
+}
+
+/// センサに1回分の出力値の送信を要求するためのバイト列を返す．
+pub const fn encode_request() -> [u8; 1] {
+    [READ_COMMAND]
+}
+
+/// センサの内蔵フィルタを設定するためのバイト列を返す．
+pub const fn encode_filter_setting(level: FilterLevel) -> [u8; 2] {
+    [level.command_digit(), FILTER_COMMAND]
+}
+
+/// 内蔵フィルタの各設定を表すバイト列．`FilterLevel`の宣言順に並ぶ．
+const FILTER_SETTINGS: &[[u8; 2]; 4] = &[
+    encode_filter_setting(FilterLevel::None),
+    encode_filter_setting(FilterLevel::Average2),
+    encode_filter_setting(FilterLevel::Average4),
+    encode_filter_setting(FilterLevel::Average8),
+];
+
+/// センサから受信した1フレーム分のバイト列を解釈する．
+/// # Params
+/// 1. `frame`: 改行コードを含む1フレーム分のバイト列．
+///
+/// # Returns
+/// 解釈できた場合，その出力値`reading`を`Ok(reading)`として返す．
+/// 解釈できなかった場合，その理由`e`を`Err(e)`として返す．
+pub fn parse_frame(frame: &[u8]) -> Result<RawReading, FrameError> {
+    if frame.len() != RESPONSE_BYTES {
+        return Err(FrameError::InvalidLength(frame.len()));
+    }
+    if !frame.ends_with(&NEWLINE) {
+        return Err(FrameError::MissingNewline);
+    }
+
+    let record_number = hex_digit(frame, 0)? as u8;
+
+    let mut counts = [0; AXIS_COUNT];
+    // 各軸別々にデータを抽出
+    for (i, count) in counts.iter_mut().enumerate() {
+        // 該当する軸のデータが生バイト列のどの範囲にあるのか計算し，16進数テキストから整数へ変換
+        let start = AXIS_DATA_START_INDEX + i * AXIS_DATUM_LENGTH;
+        for index in start..start + AXIS_DATUM_LENGTH {
+            *count = *count << 4 | hex_digit(frame, index)? as u16;
+        }
+    }
+
+    Ok(RawReading {
+        record_number,
+        counts,
+    })
+}
+
+/// フレームの`index`番目のバイトを16進数の数字として解釈する．
+fn hex_digit(frame: &[u8], index: usize) -> Result<u32, FrameError> {
+    (frame[index] as char)
+        .to_digit(16)
+        .ok_or(FrameError::InvalidDigit(index))
+}
+
+/// 連続して受信した2つのフレームのレコード番号から，その間に取りこぼしたフレームの数を求める．
+/// # Params
+/// 1. `previous`: 先に受信したフレームのレコード番号．
+/// 1. `current`: 後に受信したフレームのレコード番号．
+///
+/// # Returns
+/// 取りこぼしたフレームの数を返す．
+/// レコード番号は一周すると元に戻るため，`RECORD_NUMBER_MODULUS`個以上の取りこぼしは検出できない．
+pub const fn dropped_frames(previous: u8, current: u8) -> u8 {
+    let modulus = RECORD_NUMBER_MODULUS as u16;
+    ((current as u16 + 2 * modulus - previous as u16 - 1) % modulus) as u8
+}
+
+/// フレームの区切りがずれたバイト列から，最後の改行コードの直後の位置を探す．
+/// その位置以降は次のフレームの先頭部分とみなせるため，区切りの再同期に利用できる．
+///
+/// # Returns
+/// 改行コードが見つかった場合，その直後の位置`i`を`Some(i)`として返す．
+/// 見つからなかった場合は`None`を返す．
+pub fn find_frame_boundary(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(NEWLINE_BYTES)
+        .rposition(|w| w == NEWLINE)
+        .map(|position| position + NEWLINE_BYTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// レコード番号が`A`で，各軸の値が`[0x0000, 0x2000, 0x3FFF, 0x1234, 0xabcd, 0x0001]`のフレーム．
+    const FRAME: &[u8; RESPONSE_BYTES] = b"A000020003FFF1234abcd0001\r\n";
+
+    #[test]
+    fn frame_is_parsed_into_record_number_and_counts() {
+        let reading = parse_frame(FRAME).unwrap();
+        assert_eq!(reading.record_number, 10);
+        assert_eq!(
+            reading.counts,
+            [0x0000, 0x2000, 0x3FFF, 0x1234, 0xABCD, 0x0001]
+        );
+    }
+
+    #[test]
+    fn frame_without_crlf_is_rejected() {
+        let mut frame = *FRAME;
+        frame[RESPONSE_BYTES - 1] = b'\r';
+        assert_eq!(parse_frame(&frame), Err(FrameError::MissingNewline));
+
+        let mut frame = *FRAME;
+        frame[RESPONSE_BYTES - 2] = b'\n';
+        assert_eq!(parse_frame(&frame), Err(FrameError::MissingNewline));
+    }
+
+    #[test]
+    fn frame_with_non_hex_digit_is_rejected() {
+        let mut frame = *FRAME;
+        frame[0] = b'G';
+        assert_eq!(parse_frame(&frame), Err(FrameError::InvalidDigit(0)));
+
+        let mut frame = *FRAME;
+        frame[7] = b' ';
+        assert_eq!(parse_frame(&frame), Err(FrameError::InvalidDigit(7)));
+    }
+
+    #[test]
+    fn frame_with_wrong_length_is_rejected() {
+        assert_eq!(
+            parse_frame(&FRAME[1..]),
+            Err(FrameError::InvalidLength(RESPONSE_BYTES - 1))
+        );
+
+        let mut longer = FRAME.to_vec();
+        longer.insert(0, b'0');
+        assert_eq!(
+            parse_frame(&longer),
+            Err(FrameError::InvalidLength(RESPONSE_BYTES + 1))
+        );
+        assert_eq!(parse_frame(b""), Err(FrameError::InvalidLength(0)));
+    }
+
+    #[test]
+    fn dropped_frames_wrap_around_record_number_modulus() {
+        assert_eq!(dropped_frames(3, 4), 0);
+        assert_eq!(dropped_frames(3, 6), 2);
+        assert_eq!(dropped_frames(15, 0), 0);
+        assert_eq!(dropped_frames(14, 1), 2);
+        // 同じレコード番号は，ちょうど一周分よりも1つ少ない取りこぼしとみなす
+        assert_eq!(dropped_frames(5, 5), RECORD_NUMBER_MODULUS - 1);
+    }
+
+    #[test]
+    fn frame_boundary_is_found_after_last_newline() {
+        assert_eq!(find_frame_boundary(b""), None);
+        assert_eq!(find_frame_boundary(b"0200020002000"), None);
+        // 改行コードの途中で切れている場合は区切りとみなさない
+        assert_eq!(find_frame_boundary(b"02000\r"), None);
+        assert_eq!(find_frame_boundary(b"000\r\n"), Some(5));
+        assert_eq!(find_frame_boundary(b"00\r\n1200\r\n30"), Some(10));
+    }
+
+    #[test]
+    fn set_filter_command_matches_filter_setting_encoding() {
+        let levels = [
+            FilterLevel::None,
+            FilterLevel::Average2,
+            FilterLevel::Average4,
+            FilterLevel::Average8,
+        ];
+        for level in levels {
+            assert_eq!(
+                Command::SetFilter(level).as_bytes(),
+                &encode_filter_setting(level)[..]
+            );
+        }
+        assert_eq!(Command::SetFilter(FilterLevel::Average8).as_bytes(), b"3F");
+    }
+}
@@ -0,0 +1,267 @@
+//! 記録済みの測定値の再生．
+
+use crate::calibration::CalibrationAccumulator;
+use crate::{BinaryLogReader, CalibrationReport, ForceTorqueSensor, SensorError, Wrench};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 記録済みの測定値を，記録時と同じ時間間隔で順に返すセンサ．
+/// 実験で得た実データに対して，制御器などを決定的に試験できる．
+///
+/// 最初の`update`の呼び出し時刻を記録の先頭の時刻とみなし，
+/// 以降の`update`は各測定値の記録時刻に相当する時刻まで待機してから測定値を更新する．
+/// 記録を使い切った後の`update`はエラーを返す．
+#[derive(Debug, Clone)]
+pub struct ReplaySensor {
+    /// 記録の先頭からの経過時間と，その時刻の測定値(センサの生の出力値)．
+    records: Vec<(Duration, Wrench)>,
+    /// 次に返す記録の位置．
+    next_index: usize,
+    /// 再生を開始した時刻．
+    start: Option<Instant>,
+    /// 記録時と同じ時間間隔で再生するかどうか．
+    realtime: bool,
+    /// 現在のセンサ出力値．
+    raw_wrench: Wrench,
+    /// センサ出力値から減ずる補正値．
+    offset: Wrench,
+}
+
+impl ReplaySensor {
+    /// 記録の先頭からの経過時間と測定値の組の列から作る．
+    /// 経過時間は昇順に並んでいる必要がある．
+    pub fn from_records(records: impl IntoIterator<Item = (Duration, Wrench)>) -> ReplaySensor {
+        ReplaySensor {
+            records: records.into_iter().collect(),
+            next_index: 0,
+            start: None,
+            realtime: true,
+            raw_wrench: Wrench::zeroed(),
+            offset: Wrench::zeroed(),
+        }
+    }
+
+    /// CSV形式の記録ファイルを読み込む．
+    ///
+    /// 記録ファイルは1行目を見出し行とし，2行目以降の各行に
+    /// 経過時間[s]，fx，fy，fz，tx，ty，tz(SI単位)の7列をカンマ区切りで記述したものとする．
+    /// 8列目以降は無視するため，`CsvLogger`で記録したファイルもそのまま読み込める．
+    ///
+    /// # Returns
+    /// 数値として読めない行や，経過時間が負または有限でない行，経過時間が前の行より小さい行がある場合は，
+    /// `io::ErrorKind::InvalidData`のエラーを返す．
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> io::Result<ReplaySensor> {
+        let file = std::fs::File::open(path)?;
+        Self::from_csv_reader(BufReader::new(file))
+    }
+
+    /// `from_csv`と同じ形式の記録を読み込む．
+    pub fn from_csv_reader<R: BufRead>(reader: R) -> io::Result<ReplaySensor> {
+        let mut records = vec![];
+        // 1行目は見出し行なので読み飛ばす
+        for (i, line) in reader.lines().enumerate().skip(1) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let invalid_line = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid record at line {}: {:?}", i + 1, line),
+                )
+            };
+            let values = line
+                .split(',')
+                .map(|v| v.trim().parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid_line())?;
+            if values.len() < 7 || !values[0].is_finite() || values[0] < 0.0 {
+                return Err(invalid_line());
+            }
+
+            let time = Duration::from_secs_f64(values[0]);
+            // 経過時間が前の行より戻っている記録は再生できない
+            if records.last().is_some_and(|&(last, _)| time < last) {
+                return Err(invalid_line());
+            }
+            let wrench = Wrench::from_array([
+                values[1], values[2], values[3], values[4], values[5], values[6],
+            ]);
+            records.push((time, wrench));
+        }
+
+        Ok(Self::from_records(records))
+    }
+
+    /// `BinaryLogWriter`で記録したバイナリ形式の記録ファイルを読み込む．
+    /// 記録した測定値を，そのままセンサの出力値として再生する．
+    pub fn from_binary_log<P: AsRef<Path>>(path: P) -> io::Result<ReplaySensor> {
+        let file = std::fs::File::open(path)?;
+        Self::from_binary_reader(BufReader::new(file))
+    }
+
+    /// `from_binary_log`と同じ形式の記録を読み込む．
+    pub fn from_binary_reader<R: Read>(reader: R) -> io::Result<ReplaySensor> {
+        let records = BinaryLogReader::new(reader)?
+            .map(|record| record.map(|r| (r.elapsed, r.wrench)))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::from_records(records))
+    }
+
+    /// 記録時と同じ時間間隔で再生するかどうかを設定する．
+    /// `false`を設定した場合，`update`は待機せずに次の測定値を返す．
+    pub fn set_realtime(&mut self, realtime: bool) {
+        self.realtime = realtime;
+    }
+
+    /// まだ返していない測定値の数を返す．
+    pub fn remaining(&self) -> usize {
+        self.records.len() - self.next_index
+    }
+
+    /// 再生位置を記録の先頭に戻す．
+    pub fn rewind(&mut self) {
+        self.next_index = 0;
+        self.start = None;
+    }
+}
+
+impl ForceTorqueSensor for ReplaySensor {
+    fn update(&mut self) -> Result<(), SensorError> {
+        let &(time, wrench) = self.records.get(self.next_index).ok_or_else(|| {
+            SensorError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "All records have been replayed",
+            ))
+        })?;
+
+        if self.realtime {
+            let first_time = self.records[0].0;
+            let start = *self.start.get_or_insert_with(Instant::now);
+            // 記録時の時刻に相当する時刻まで待機する
+            let scheduled = start + time.saturating_sub(first_time);
+            if let Some(remaining) = scheduled.checked_duration_since(Instant::now()) {
+                std::thread::sleep(remaining);
+            }
+        }
+
+        self.raw_wrench = wrench;
+        self.next_index += 1;
+        Ok(())
+    }
+
+    fn last_measurement(&self) -> Wrench {
+        self.raw_wrench - self.offset
+    }
+
+    /// 記録された測定値を順に`measurement_times`個読み，その平均をゼロ点とする．
+    /// 再生の間隔は記録に従うため，`measurement_period`は用いない．
+    fn calibrate(
+        &mut self,
+        _measurement_period: Duration,
+        measurement_times: usize,
+    ) -> Result<CalibrationReport, SensorError> {
+        assert!(measurement_times > 0);
+
+        let mut accumulator = CalibrationAccumulator::new();
+        for _ in 0..measurement_times {
+            match self.update() {
+                Ok(()) => accumulator.push(self.raw_wrench),
+                Err(_) => accumulator.push_failure(),
+            }
+        }
+        let report = accumulator.finish()?;
+        self.offset = report.mean;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "time_s,fx,fy,fz,tx,ty,tz\n";
+
+    fn load(rows: &str) -> io::Result<ReplaySensor> {
+        ReplaySensor::from_csv_reader(format!("{}{}", HEADER, rows).as_bytes())
+    }
+
+    fn assert_invalid_data(result: io::Result<ReplaySensor>) {
+        match result {
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+            Ok(_) => panic!("The record should be rejected"),
+        }
+    }
+
+    #[test]
+    fn csv_with_ascending_time_is_loaded() {
+        let sensor = load("0.0,1,2,3,4,5,6\n0.0,1,2,3,4,5,6\n0.5,1,2,3,4,5,6,7\n").unwrap();
+        assert_eq!(sensor.remaining(), 3);
+    }
+
+    #[test]
+    fn csv_with_non_finite_time_is_rejected() {
+        assert_invalid_data(load("NaN,1,2,3,4,5,6\n"));
+        assert_invalid_data(load("inf,1,2,3,4,5,6\n"));
+        assert_invalid_data(load("-1.0,1,2,3,4,5,6\n"));
+    }
+
+    #[test]
+    fn csv_with_descending_time_is_rejected() {
+        assert_invalid_data(load("1.0,1,2,3,4,5,6\n0.5,1,2,3,4,5,6\n"));
+    }
+
+    #[test]
+    fn binary_log_is_replayed_with_recorded_values() {
+        let path =
+            std::env::temp_dir().join(format!("wacohtech_replay_test_{}.bin", std::process::id()));
+        let wrenches = [
+            Wrench::from_array([1.0, 2.0, 3.0, 0.1, 0.2, 0.3]),
+            Wrench::from_array([-1.0, -2.0, -3.0, -0.1, -0.2, -0.3]),
+        ];
+        {
+            let start = Instant::now();
+            let mut writer =
+                crate::BinaryLogWriter::create(&path, crate::SensorModel::Wdf6m200_3).unwrap();
+            for (i, &wrench) in wrenches.iter().enumerate() {
+                let measurement = crate::Measurement {
+                    wrench,
+                    timestamp: start + Duration::from_millis(i as u64),
+                    system_time: std::time::SystemTime::now(),
+                    sequence: i as u64,
+                    record_number: i as u8,
+                    overload: [false; crate::AXIS_COUNT],
+                };
+                writer
+                    .log(&measurement, [0x2000; crate::AXIS_COUNT])
+                    .unwrap();
+            }
+            writer.flush().unwrap();
+        }
+
+        let result = ReplaySensor::from_binary_log(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mut sensor = result.unwrap();
+        sensor.set_realtime(false);
+        assert_eq!(sensor.remaining(), 2);
+        for wrench in wrenches.iter() {
+            sensor.update().unwrap();
+            assert_eq!(sensor.last_measurement(), *wrench);
+        }
+        assert!(sensor.update().is_err());
+    }
+
+    #[test]
+    fn records_before_first_time_are_replayed_without_waiting() {
+        let records = vec![
+            (Duration::from_millis(1), Wrench::zeroed()),
+            (Duration::from_millis(0), Wrench::zeroed()),
+        ];
+        let mut sensor = ReplaySensor::from_records(records);
+        sensor.update().unwrap();
+        sensor.update().unwrap();
+        assert_eq!(sensor.remaining(), 0);
+    }
+}
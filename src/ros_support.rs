@@ -0,0 +1,76 @@
+//! ROSのメッセージ型との相互変換．
+//! r2rを用いたROS 2ノードから，測定値を変換の手間なく配信できるようにする．
+
+use crate::{Measurement, Wrench};
+use r2r::builtin_interfaces::msg::Time;
+use r2r::geometry_msgs::msg::{Vector3, Wrench as WrenchMsg, WrenchStamped};
+use r2r::std_msgs::msg::Header;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+impl From<Wrench> for WrenchMsg {
+    /// 各成分をSI単位の値として格納する．
+    fn from(wrench: Wrench) -> Self {
+        let [fx, fy, fz, tx, ty, tz] = wrench.to_array();
+        WrenchMsg {
+            force: Vector3 {
+                x: fx,
+                y: fy,
+                z: fz,
+            },
+            torque: Vector3 {
+                x: tx,
+                y: ty,
+                z: tz,
+            },
+        }
+    }
+}
+
+impl From<&WrenchMsg> for Wrench {
+    /// 各成分をSI単位の値として解釈する．
+    fn from(message: &WrenchMsg) -> Self {
+        let WrenchMsg { force, torque } = message;
+        Wrench::from_array([force.x, force.y, force.z, torque.x, torque.y, torque.z])
+    }
+}
+
+impl From<WrenchMsg> for Wrench {
+    /// 各成分をSI単位の値として解釈する．
+    fn from(message: WrenchMsg) -> Self {
+        Wrench::from(&message)
+    }
+}
+
+impl Wrench {
+    /// ヘッダを付けて`geometry_msgs/msg/WrenchStamped`メッセージに変換する．
+    /// # Params
+    /// 1. `frame_id`: レンチを表す座標系の名前．
+    /// 1. `stamp`: レンチを取得した時刻．
+    pub fn to_wrench_stamped(&self, frame_id: &str, stamp: SystemTime) -> WrenchStamped {
+        WrenchStamped {
+            header: Header {
+                stamp: system_time_to_stamp(stamp),
+                frame_id: frame_id.to_string(),
+            },
+            wrench: (*self).into(),
+        }
+    }
+}
+
+impl Measurement {
+    /// 受信時刻をヘッダの時刻として，`geometry_msgs/msg/WrenchStamped`メッセージに変換する．
+    /// # Params
+    /// 1. `frame_id`: レンチを表す座標系の名前．
+    pub fn to_wrench_stamped(&self, frame_id: &str) -> WrenchStamped {
+        self.wrench.to_wrench_stamped(frame_id, self.system_time)
+    }
+}
+
+/// システム時刻をROSの時刻に変換する．UNIX時刻より前の時刻は0とする．
+fn system_time_to_stamp(time: SystemTime) -> Time {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    Time {
+        sec: since_epoch.as_secs() as i32,
+        nanosec: since_epoch.subsec_nanos(),
+    }
+}
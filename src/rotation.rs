@@ -0,0 +1,298 @@
+//! 記録ファイルを一定の大きさや時間ごとに切り替えるための機能．
+
+#[cfg(feature = "json")]
+use crate::JsonLinesLogger;
+use crate::{BinaryLogWriter, CsvLogger, Measurement, MeasurementSink, SensorModel, AXIS_COUNT};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+/// `None`の条件は用いない．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RotationPolicy {
+    /// 1つの記録ファイルの大きさの上限[byte]．これを超えると新しいファイルに切り替える．
+    pub max_file_bytes: Option<u64>,
+    /// 1つの記録ファイルに記録する時間の上限．これを超えると新しいファイルに切り替える．
+    pub max_file_duration: Option<Duration>,
+    /// 記録ファイルの合計の大きさの上限[byte]．これを超えると，古いファイルから順に削除する．
+    pub max_total_bytes: Option<u64>,
+}
+
+/// `RotatingLogger`が作成する記録ファイル．
+/// 書き込んだバイト数を数え，ファイルを切り替える時期の判定に用いる．
+pub struct LogFile {
+    /// 書き込み先のファイル．
+    file: File,
+    /// これまでに書き込んだバイト数．
+    written: Arc<AtomicU64>,
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.file.write(buf)?;
+        self.written.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 記録ファイルから記録器を作る処理．
+type SinkFactory<S> = Box<dyn FnMut(LogFile) -> io::Result<S> + Send>;
+
+/// 現在記録中のファイル．
+struct ActiveFile<S> {
+    /// 記録器．
+    sink: S,
+    /// ファイルのパス．
+    path: PathBuf,
+    /// ファイルに書き込んだバイト数．
+    written: Arc<AtomicU64>,
+    /// ファイルを作成した時刻．
+    opened: Instant,
+}
+
+/// 記録ファイルを一定の大きさや時間ごとに切り替えながら記録する．
+/// 常時稼働する監視装置などで，記録がディスクを使い切ってしまうのを防ぐ．
+///
+/// 記録ファイルは`{directory}/{prefix}-{UNIX時刻[s]}-{通し番号}.{extension}`という名前で作成する．
+/// 書き込みはバッファリングされるため，ファイルの大きさは上限をバッファの大きさ程度超えることがある．
+/// 古いファイルの削除の対象は，このインスタンスが作成したファイルに限る．
+pub struct RotatingLogger<S: MeasurementSink> {
+    /// 記録ファイルを作成するディレクトリ．
+    directory: PathBuf,
+    /// 記録ファイルの名前の先頭部分．
+    prefix: String,
+    /// 記録ファイルの拡張子．
+    extension: String,
+    /// 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+    policy: RotationPolicy,
+    /// 記録ファイルから記録器を作る処理．
+    factory: SinkFactory<S>,
+    /// 現在記録中のファイル．まだ記録していなければ`None`．
+    active: Option<ActiveFile<S>>,
+    /// 記録を終えたファイルのパスと大きさ．古いものから順に並んでいる．
+    closed: VecDeque<(PathBuf, u64)>,
+    /// 次に作成する記録ファイルの通し番号．
+    next_index: u64,
+}
+
+impl RotatingLogger<CsvLogger<LogFile>> {
+    /// `CsvLogger`で記録する．
+    /// # Params
+    /// 1. `directory`: 記録ファイルを作成するディレクトリ．
+    /// 1. `prefix`: 記録ファイルの名前の先頭部分．
+    /// 1. `policy`: 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+    pub fn csv<P: AsRef<Path>>(
+        directory: P,
+        prefix: &str,
+        policy: RotationPolicy,
+    ) -> RotatingLogger<CsvLogger<LogFile>> {
+        Self::new(directory, prefix, "csv", policy, CsvLogger::new)
+    }
+}
+
+#[cfg(feature = "json")]
+impl RotatingLogger<JsonLinesLogger<LogFile>> {
+    /// `JsonLinesLogger`で記録する．
+    /// # Params
+    /// 1. `directory`: 記録ファイルを作成するディレクトリ．
+    /// 1. `prefix`: 記録ファイルの名前の先頭部分．
+    /// 1. `policy`: 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+    pub fn json_lines<P: AsRef<Path>>(
+        directory: P,
+        prefix: &str,
+        policy: RotationPolicy,
+    ) -> RotatingLogger<JsonLinesLogger<LogFile>> {
+        Self::new(directory, prefix, "jsonl", policy, |file| {
+            Ok(JsonLinesLogger::new(file))
+        })
+    }
+}
+
+impl RotatingLogger<BinaryLogWriter<LogFile>> {
+    /// `BinaryLogWriter`で記録する．
+    /// # Params
+    /// 1. `directory`: 記録ファイルを作成するディレクトリ．
+    /// 1. `prefix`: 記録ファイルの名前の先頭部分．
+    /// 1. `model`: 記録するセンサの型式．
+    /// 1. `policy`: 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+    pub fn binary<P: AsRef<Path>>(
+        directory: P,
+        prefix: &str,
+        model: SensorModel,
+        policy: RotationPolicy,
+    ) -> RotatingLogger<BinaryLogWriter<LogFile>> {
+        Self::new(directory, prefix, "bin", policy, move |file| {
+            BinaryLogWriter::new(file, model)
+        })
+    }
+}
+
+impl<S: MeasurementSink> RotatingLogger<S> {
+    /// 任意の記録器で記録する．ファイルは最初の測定値を記録する際に作成する．
+    /// # Params
+    /// 1. `directory`: 記録ファイルを作成するディレクトリ．
+    /// 1. `prefix`: 記録ファイルの名前の先頭部分．
+    /// 1. `extension`: 記録ファイルの拡張子．
+    /// 1. `policy`: 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件．
+    /// 1. `factory`: 作成した記録ファイルから記録器を作る処理．
+    pub fn new<P: AsRef<Path>>(
+        directory: P,
+        prefix: &str,
+        extension: &str,
+        policy: RotationPolicy,
+        factory: impl FnMut(LogFile) -> io::Result<S> + Send + 'static,
+    ) -> RotatingLogger<S> {
+        RotatingLogger {
+            directory: directory.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            extension: extension.to_string(),
+            policy,
+            factory: Box::new(factory),
+            active: None,
+            closed: VecDeque::new(),
+            next_index: 0,
+        }
+    }
+
+    /// 記録ファイルを切り替える条件と，古い記録ファイルを削除する条件を返す．
+    pub fn policy(&self) -> RotationPolicy {
+        self.policy
+    }
+
+    /// 現在記録中のファイルのパスを返す．まだ記録していなければ`None`を返す．
+    pub fn current_path(&self) -> Option<&Path> {
+        self.active.as_ref().map(|active| active.path.as_path())
+    }
+
+    /// 測定値を1件記録する．必要であれば，記録の前に新しいファイルに切り替える．
+    /// # Params
+    /// 1. `measurement`: 記録する測定値．
+    /// 1. `raw_counts`: 測定値の換算前の各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub fn log(
+        &mut self,
+        measurement: &Measurement,
+        raw_counts: [u16; AXIS_COUNT],
+    ) -> io::Result<()> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+        // 切り替えた直後なので，必ず記録中のファイルがある
+        self.active
+            .as_mut()
+            .unwrap()
+            .sink
+            .log(measurement, raw_counts)
+    }
+
+    /// 現在のファイルへの記録を終え，新しいファイルに切り替える．
+    /// 新しいファイルは，この呼び出しの時点で作成する．
+    pub fn rotate(&mut self) -> io::Result<()> {
+        self.close_active()?;
+        self.enforce_retention()?;
+
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let name = format!(
+            "{}-{}-{:04}.{}",
+            self.prefix, seconds, self.next_index, self.extension
+        );
+        let path = self.directory.join(name);
+        self.next_index += 1;
+
+        let written = Arc::new(AtomicU64::new(0));
+        let file = LogFile {
+            file: File::create(&path)?,
+            written: Arc::clone(&written),
+        };
+        let sink = (self.factory)(file)?;
+        self.active = Some(ActiveFile {
+            sink,
+            path,
+            written,
+            opened: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// バッファリングしている内容を書き込み先に書き出す．
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.active {
+            Some(active) => active.sink.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// 新しいファイルに切り替えるべきであれば`true`を返す．
+    fn should_rotate(&self) -> bool {
+        let active = match &self.active {
+            Some(active) => active,
+            None => return true,
+        };
+        let too_large = self
+            .policy
+            .max_file_bytes
+            .is_some_and(|max| active.written.load(Ordering::Relaxed) >= max);
+        let too_old = self
+            .policy
+            .max_file_duration
+            .is_some_and(|max| active.opened.elapsed() >= max);
+        too_large || too_old
+    }
+
+    /// 現在のファイルへの記録を終える．
+    fn close_active(&mut self) -> io::Result<()> {
+        if let Some(mut active) = self.active.take() {
+            active.sink.flush()?;
+            // 書き出しが完了した後の大きさを記録するため，記録器を破棄してから読み出す
+            drop(active.sink);
+            let size = active.written.load(Ordering::Relaxed);
+            self.closed.push_back((active.path, size));
+        }
+        Ok(())
+    }
+
+    /// 記録を終えたファイルの合計の大きさが上限を超えていれば，古いファイルから順に削除する．
+    fn enforce_retention(&mut self) -> io::Result<()> {
+        let max = match self.policy.max_total_bytes {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+
+        let mut total = self.closed.iter().map(|(_, size)| size).sum::<u64>();
+        while total > max {
+            let (path, size) = match self.closed.pop_front() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                // 利用者が既に削除したファイルは無視する
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+impl<S: MeasurementSink> MeasurementSink for RotatingLogger<S> {
+    fn log(&mut self, measurement: &Measurement, raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        RotatingLogger::log(self, measurement, raw_counts)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        RotatingLogger::flush(self)
+    }
+}
@@ -0,0 +1,113 @@
+//! 測定値が許容範囲を超えたことを，受信した時点で通知するための機能．
+
+use crate::{Measurement, AXIS_COUNT};
+use std::sync::mpsc::Sender;
+
+/// `SafetyMonitor`が検出した，許容範囲の超過．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SafetyViolation {
+    /// 許容範囲を超えた測定値．
+    pub measurement: Measurement,
+    /// 各軸が許容範囲を超えているかどうか．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    pub exceeded: [bool; AXIS_COUNT],
+}
+
+/// 許容範囲を超えた際に呼び出される処理．
+type ViolationHandler = Box<dyn FnMut(&SafetyViolation) + Send>;
+
+/// 測定値の各軸の大きさを監視し，いずれかの軸が上限を超えたときに利用者の処理を呼び出す．
+///
+/// `DynPick::set_safety_monitor`で設定すると，測定値を受信するたびにドライバの内部で判定を行う．
+/// 制御ループが測定値を読み出すのを待たないため，上限の超過から1サンプルの遅れで反応できる．
+///
+/// 通知は許容範囲内から範囲外に移ったときにだけ行い，範囲外の状態が続く間は繰り返さない．
+/// すべての軸が上限以下に戻ると，次の超過で再び通知する．
+pub struct SafetyMonitor {
+    /// 各軸の大きさの上限．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で並ぶ．
+    limits: [f64; AXIS_COUNT],
+    /// 許容範囲を超えた際に呼び出される処理．
+    handler: ViolationHandler,
+    /// 直前の測定値が許容範囲を超えていれば`true`．
+    violated: bool,
+}
+
+impl SafetyMonitor {
+    /// 許容範囲を超えたときに`callback`を呼び出す監視器を作る．
+    /// `callback`は受信処理の中で呼び出されるため，時間のかかる処理は行わないこと．
+    /// # Params
+    /// 1. `limits`: 各軸の大きさの上限．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    /// 1. `callback`: 許容範囲を超えたときに呼び出される処理．
+    ///
+    /// # Panics
+    /// `limits`のいずれかの要素が負の場合．
+    pub fn with_callback(
+        limits: [f64; AXIS_COUNT],
+        callback: impl FnMut(&SafetyViolation) + Send + 'static,
+    ) -> SafetyMonitor {
+        assert!(limits.iter().all(|&l| l >= 0.0));
+
+        SafetyMonitor {
+            limits,
+            handler: Box::new(callback),
+            violated: false,
+        }
+    }
+
+    /// 許容範囲を超えたときに`sender`へ`SafetyViolation`を送信する監視器を作る．
+    /// 受信側が既に破棄されている場合，送信の失敗は無視する．
+    /// # Params
+    /// 1. `limits`: 各軸の大きさの上限．`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で指定する．
+    /// 1. `sender`: 超過を通知するチャネル．
+    ///
+    /// # Panics
+    /// `limits`のいずれかの要素が負の場合．
+    pub fn with_channel(
+        limits: [f64; AXIS_COUNT],
+        sender: Sender<SafetyViolation>,
+    ) -> SafetyMonitor {
+        Self::with_callback(limits, move |violation| {
+            let _ = sender.send(*violation);
+        })
+    }
+
+    /// 各軸の大きさの上限を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn limits(&self) -> [f64; AXIS_COUNT] {
+        self.limits
+    }
+
+    /// 直前に判定した測定値が許容範囲を超えていれば`true`を返す．
+    pub fn is_violated(&self) -> bool {
+        self.violated
+    }
+
+    /// 測定値を判定し，許容範囲外に移った場合は通知する．
+    ///
+    /// # Returns
+    /// 測定値が許容範囲を超えていれば`true`を返す．
+    pub fn check(&mut self, measurement: &Measurement) -> bool {
+        let values = measurement.wrench.to_array();
+        let mut exceeded = [false; AXIS_COUNT];
+        for ((e, v), l) in exceeded
+            .iter_mut()
+            .zip(values.iter())
+            .zip(self.limits.iter())
+        {
+            *e = v.abs() > *l;
+        }
+
+        let was_violated = self.violated;
+        self.violated = exceeded.contains(&true);
+        if self.violated && !was_violated {
+            (self.handler)(&SafetyViolation {
+                measurement: *measurement,
+                exceeded,
+            });
+        }
+        self.violated
+    }
+
+    /// 超過の状態を捨てる．次に許容範囲を超えた測定値で改めて通知する．
+    pub fn reset(&mut self) {
+        self.violated = false;
+    }
+}
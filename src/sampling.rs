@@ -0,0 +1,198 @@
+//! 別スレッドでの測定値の取得．
+
+use crate::{DynPick, SensorError, SensorSpec, Wdf6m200Spec, Wrench, WrenchSamplingScheduler};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvError, RecvTimeoutError, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// `spawn`で起動した場合に，受け取られずにチャネルに溜めておける測定値の個数．
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// 専用のスレッドで一定周期でセンサの測定値を取得し，チャネルを通じて受け渡す．
+/// アプリケーションのスレッドがシリアル通信で待たされることがなくなる．
+///
+/// 測定値の更新に失敗した周期では，チャネルに測定値は送られず，そのエラーを`take_error`で受け取れる．
+/// チャネルに溜まった測定値が上限に達している間に取得した測定値は，チャネルに送られずに捨てられる．
+pub struct SampledSensor<S: SensorSpec = Wdf6m200Spec> {
+    /// 取得した測定値を受け取るチャネル．
+    receiver: Receiver<Wrench>,
+    /// 最後に取得した測定値．
+    latest: Arc<Mutex<Option<Wrench>>>,
+    /// 最後に発生した，まだ受け取っていない測定値の更新のエラー．
+    last_error: Arc<Mutex<Option<SensorError>>>,
+    /// チャネルが一杯であったために捨てた測定値の数．
+    dropped: Arc<AtomicU64>,
+    /// 取得スレッドに停止を指示するためのフラグ．
+    stop_requested: Arc<AtomicBool>,
+    /// 取得スレッド．停止時にセンサを返す．
+    handle: Option<JoinHandle<DynPick<S>>>,
+}
+
+impl<S: SensorSpec> SampledSensor<S> {
+    /// 取得スレッドを起動する．チャネルには最大1024個の測定値を溜めておける．
+    /// # Params
+    /// 1. `sensor`: 測定値を取得するセンサ．
+    /// 1. `hz`: 測定値を取得する周波数[Hz]．
+    ///
+    /// # Panics
+    /// `hz`が正でない場合．
+    pub fn spawn(sensor: DynPick<S>, hz: f64) -> SampledSensor<S> {
+        Self::spawn_with_capacity(sensor, hz, DEFAULT_CAPACITY)
+    }
+
+    /// チャネルに溜めておける測定値の個数を指定して，取得スレッドを起動する．
+    /// # Params
+    /// 1. `sensor`: 測定値を取得するセンサ．
+    /// 1. `hz`: 測定値を取得する周波数[Hz]．
+    /// 1. `capacity`: 受け取られずにチャネルに溜めておける測定値の個数．
+    ///
+    /// # Panics
+    /// `hz`が正でない場合，または`capacity`が0の場合．
+    pub fn spawn_with_capacity(
+        mut sensor: DynPick<S>,
+        hz: f64,
+        capacity: usize,
+    ) -> SampledSensor<S> {
+        assert!(capacity > 0);
+
+        let scheduler = WrenchSamplingScheduler::new(hz);
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let latest = Arc::new(Mutex::new(None));
+        let last_error = Arc::new(Mutex::new(None));
+        let dropped = Arc::new(AtomicU64::new(0));
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let latest = Arc::clone(&latest);
+            let last_error = Arc::clone(&last_error);
+            let dropped = Arc::clone(&dropped);
+            let stop_requested = Arc::clone(&stop_requested);
+            std::thread::spawn(move || {
+                for _ in scheduler {
+                    if stop_requested.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Err(e) = sensor.update() {
+                        *last_error.lock().unwrap() = Some(e);
+                        continue;
+                    }
+
+                    let measurement = sensor.last_measurement();
+                    *latest.lock().unwrap() = Some(measurement);
+                    match sender.try_send(measurement) {
+                        Ok(()) => {}
+                        // 受け取りが追いつかない場合は，取得の周期を保つため待たずに捨てる
+                        Err(TrySendError::Full(_)) => {
+                            dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        // 受信側が破棄されていても取得は続ける
+                        Err(TrySendError::Disconnected(_)) => {}
+                    }
+                }
+                sensor
+            })
+        };
+
+        SampledSensor {
+            receiver,
+            latest,
+            last_error,
+            dropped,
+            stop_requested,
+            handle: Some(handle),
+        }
+    }
+
+    /// 最後に取得した測定値を返す．まだ測定値を取得していない場合は`None`を返す．
+    pub fn latest(&self) -> Option<Wrench> {
+        *self.latest.lock().unwrap()
+    }
+
+    /// 最後に発生した測定値の更新のエラーを受け取る．
+    /// 前回の呼び出し以降にエラーが発生していなければ`None`を返す．
+    pub fn take_error(&self) -> Option<SensorError> {
+        self.last_error.lock().unwrap().take()
+    }
+
+    /// チャネルが一杯であったために捨てた測定値の数を返す．
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 次の測定値を受け取るまで待機し，受け取った測定値を返す．
+    /// 取得スレッドが停止している場合はエラーを返す．
+    pub fn recv(&self) -> Result<Wrench, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// 次の測定値を最大で`timeout`だけ待機して受け取る．
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Wrench, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// 待機せずに，既に取得済みの測定値を1つ受け取る．
+    pub fn try_recv(&self) -> Result<Wrench, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// 取得スレッドを停止し，センサを返す．
+    /// # Panics
+    /// 取得スレッドがパニックしていた場合．
+    pub fn stop(mut self) -> DynPick<S> {
+        self.join().unwrap()
+    }
+
+    /// 取得スレッドに停止を指示し，その終了を待つ．
+    fn join(&mut self) -> Option<DynPick<S>> {
+        self.stop_requested.store(true, Ordering::Relaxed);
+        self.handle.take().map(|handle| {
+            handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))
+        })
+    }
+}
+
+impl<S: SensorSpec> Drop for SampledSensor<S> {
+    /// 取得スレッドを停止する．
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_port::FakeSerialPort;
+    use crate::Wdf6m200;
+
+    #[test]
+    fn samples_are_dropped_when_channel_is_full() {
+        let port = FakeSerialPort::new(Duration::from_millis(10));
+        for _ in 0..5 {
+            port.push_incoming(b"0200020002000200020002000\r\n");
+        }
+        let sensor = Wdf6m200::from_serial_port(Box::new(port.clone()), None).unwrap();
+
+        let sampled = SampledSensor::spawn_with_capacity(sensor, 1000.0, 2);
+        // 5つのフレームを受信し終え，その後の更新でタイムアウトするまで待つ
+        let error = loop {
+            if let Some(e) = sampled.take_error() {
+                break e;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        match error {
+            SensorError::Io(e) => assert_eq!(e.kind(), std::io::ErrorKind::TimedOut),
+            other => panic!("Unexpected error: {:?}", other),
+        }
+
+        assert!(port.state().incoming.is_empty());
+        assert_eq!(sampled.dropped_count(), 3);
+        assert!(sampled.try_recv().is_ok());
+        assert!(sampled.try_recv().is_ok());
+        assert!(sampled.try_recv().is_err());
+    }
+}
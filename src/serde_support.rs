@@ -0,0 +1,59 @@
+//! serdeによるシリアライズとデシリアライズへの対応．
+//!
+//! `dimensioned`の型はserdeに対応していないため，レンチはSI単位の値を`[fx, fy, fz, tx, ty, tz]`の順に並べた
+//! 6要素の配列として表す．
+
+use crate::{Measurement, PlainWrench, Wrench, WrenchF32, AXIS_COUNT};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Wrench {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_array().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Wrench {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[f64; AXIS_COUNT]>::deserialize(deserializer).map(Wrench::from_array)
+    }
+}
+
+impl Serialize for WrenchF32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_array().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WrenchF32 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[f32; AXIS_COUNT]>::deserialize(deserializer).map(WrenchF32::from_array)
+    }
+}
+
+impl Serialize for PlainWrench {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_array().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PlainWrench {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[f64; AXIS_COUNT]>::deserialize(deserializer).map(PlainWrench::from_array)
+    }
+}
+
+/// `timestamp`はプロセス内でのみ意味をもつ`Instant`であるため出力しない．
+/// 絶対時刻は`system_time`として出力される．
+/// 同じ理由で，`Measurement`のデシリアライズには対応しない．
+impl Serialize for Measurement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Measurement", 5)?;
+        state.serialize_field("wrench", &self.wrench)?;
+        state.serialize_field("system_time", &self.system_time)?;
+        state.serialize_field("sequence", &self.sequence)?;
+        state.serialize_field("record_number", &self.record_number)?;
+        state.serialize_field("overload", &self.overload)?;
+        state.end()
+    }
+}
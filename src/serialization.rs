@@ -0,0 +1,144 @@
+//! レンチを他のシステムと受け渡すためのテキスト形式への変換．
+
+use crate::Wrench;
+use std::fmt::{self, Display, Formatter};
+
+/// 各成分のタグ名．`[fx, fy, fz, tx, ty, tz]`の順に並んでいる．
+const COMPONENT_NAMES: [&str; 6] = ["fx", "fy", "fz", "tx", "ty", "tz"];
+
+/// レンチとROSのパラメータサーバ形式のXMLとを相互に変換する．
+///
+/// XMLは次のような形式となる．各成分はSI単位で記述される．
+/// ```xml
+/// <wrench><fx>1.23</fx><fy>0</fy><fz>0</fz><tx>0</tx><ty>0</ty><tz>0</tz></wrench>
+/// ```
+pub struct WrenchXmlSerializer;
+
+impl WrenchXmlSerializer {
+    /// レンチをXML文字列に変換する．
+    pub fn to_xml(wrench: &Wrench) -> String {
+        let mut xml = String::from("<wrench>");
+        for (name, value) in COMPONENT_NAMES.iter().zip(wrench.to_array().iter()) {
+            xml.push_str(&format!("<{0}>{1}</{0}>", name, value));
+        }
+        xml.push_str("</wrench>");
+        xml
+    }
+
+    /// XML文字列からレンチを読み取る．
+    /// 要素間の空白や改行は無視される．
+    pub fn from_xml(s: &str) -> Result<Wrench, ParseError> {
+        let content = element_content(s, "wrench").ok_or(ParseError::MissingElement("wrench"))?;
+
+        let mut array = [0.0; 6];
+        for (&name, e) in COMPONENT_NAMES.iter().zip(array.iter_mut()) {
+            let text = element_content(content, name).ok_or(ParseError::MissingElement(name))?;
+            *e = text
+                .trim()
+                .parse()
+                .map_err(|_| ParseError::InvalidNumber(name, text.trim().to_owned()))?;
+        }
+
+        Ok(Wrench::from_array(array))
+    }
+}
+
+/// `<tag>...</tag>`で囲まれた部分を探し，その中身を返す．
+fn element_content<'a>(s: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = s.find(&open)? + open.len();
+    let end = start + s[start..].find(&close)?;
+    Some(&s[start..end])
+}
+
+/// テキスト形式からレンチを読み取る際に発生したエラーを表す．
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// 必要な要素が見つからない．
+    MissingElement(&'static str),
+    /// 要素の値を数値として解釈できない．
+    InvalidNumber(&'static str, String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingElement(name) => write!(f, "Element <{}> not found", name),
+            ParseError::InvalidNumber(name, text) => {
+                write!(f, "The value of <{}> is not a number: {:?}", name, text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrench_round_trips_through_xml() {
+        let wrench = Wrench::from_array([1.23, -4.5, 0.0, 1e-3, -2.5e-7, 6.0]);
+        let xml = WrenchXmlSerializer::to_xml(&wrench);
+        assert_eq!(
+            xml,
+            "<wrench><fx>1.23</fx><fy>-4.5</fy><fz>0</fz><tx>0.001</tx><ty>-0.00000025</ty><tz>6</tz></wrench>"
+        );
+        assert_eq!(WrenchXmlSerializer::from_xml(&xml), Ok(wrench));
+    }
+
+    #[test]
+    fn whitespace_between_and_inside_elements_is_ignored() {
+        let xml = "
+            <wrench>
+                <fx> 1 </fx>
+                <fy>2</fy>
+                <fz>
+                    3
+                </fz>
+                <tx>4</tx> <ty>5</ty>\t<tz>6</tz>
+            </wrench>
+        ";
+        assert_eq!(
+            WrenchXmlSerializer::from_xml(xml),
+            Ok(Wrench::from_array([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]))
+        );
+    }
+
+    #[test]
+    fn missing_element_is_reported() {
+        assert_eq!(
+            WrenchXmlSerializer::from_xml("<fx>1</fx>"),
+            Err(ParseError::MissingElement("wrench"))
+        );
+        assert_eq!(
+            WrenchXmlSerializer::from_xml(
+                "<wrench><fx>1</fx><fy>2</fy><fz>3</fz><tx>4</tx><tz>6</tz></wrench>"
+            ),
+            Err(ParseError::MissingElement("ty"))
+        );
+        // 閉じタグのない要素は見つからないものとする
+        assert_eq!(
+            WrenchXmlSerializer::from_xml("<wrench><fx>1</wrench>"),
+            Err(ParseError::MissingElement("fx"))
+        );
+    }
+
+    #[test]
+    fn invalid_number_is_reported() {
+        assert_eq!(
+            WrenchXmlSerializer::from_xml(
+                "<wrench><fx>1</fx><fy>2</fy><fz> 3N </fz><tx>4</tx><ty>5</ty><tz>6</tz></wrench>"
+            ),
+            Err(ParseError::InvalidNumber("fz", "3N".to_owned()))
+        );
+        assert_eq!(
+            WrenchXmlSerializer::from_xml(
+                "<wrench><fx></fx><fy>2</fy><fz>3</fz><tx>4</tx><ty>5</ty><tz>6</tz></wrench>"
+            ),
+            Err(ParseError::InvalidNumber("fx", String::new()))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! レンチの周波数解析．
+
+use crate::{
+    DynPick, Measurement, SensorError, SensorSpec, Wrench, WrenchSamplingScheduler, AXIS_COUNT,
+};
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+/// 周波数解析の結果．
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectrumResult {
+    /// 各周波数ビンの周波数[Hz]．
+    pub frequencies: Vec<f64>,
+    /// 各軸の振幅スペクトル．`[fx, fy, fz, tx, ty, tz]`の順に並んでおり，
+    /// 各要素は`frequencies`の各周波数ビンにおける振幅をSI単位で表す．
+    pub magnitudes: [Vec<f64>; AXIS_COUNT],
+}
+
+impl SpectrumResult {
+    /// 指定した軸について，直流成分を除いて振幅が最大となる周波数[Hz]を返す．
+    /// 周波数ビンが直流成分しかない場合は`None`を返す．
+    /// # Panics
+    /// `axis`が6以上の場合．
+    pub fn dominant_frequency(&self, axis: usize) -> Option<f64> {
+        self.magnitudes[axis]
+            .iter()
+            .zip(self.frequencies.iter())
+            .skip(1)
+            .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(_, &frequency)| frequency)
+    }
+}
+
+/// 力の振動の周波数成分を高速フーリエ変換(FFT)によって解析する．
+/// 機械的な共振周波数の特定などに利用できる．
+pub struct WrenchFrequencyAnalyzer {
+    /// FFTの計画．同じサンプル数の解析を繰り返す場合に計画を使い回す．
+    planner: FftPlanner<f64>,
+}
+
+impl WrenchFrequencyAnalyzer {
+    pub fn new() -> WrenchFrequencyAnalyzer {
+        WrenchFrequencyAnalyzer {
+            planner: FftPlanner::new(),
+        }
+    }
+
+    /// センサから一定周期で測定値を収集し，その周波数解析を行う．
+    /// # Params
+    /// 1. `sensor`: 測定値を収集するセンサ．
+    /// 1. `n_samples`: 収集するサンプル数．
+    /// 1. `sample_hz`: サンプリング周波数[Hz]．
+    ///
+    /// # Panics
+    /// `n_samples`が0の場合，または`sample_hz`が正でない場合．
+    pub fn analyze<S: SensorSpec>(
+        &mut self,
+        sensor: &mut DynPick<S>,
+        n_samples: usize,
+        sample_hz: f64,
+    ) -> Result<SpectrumResult, SensorError> {
+        assert!(n_samples > 0);
+        assert!(sample_hz > 0.0);
+
+        // 受信にかかる時間によって周期がずれないよう，一定周期の時刻ごとに測定値を取得する
+        let mut samples = Vec::with_capacity(n_samples);
+        for _ in WrenchSamplingScheduler::new(sample_hz).take(n_samples) {
+            sensor.update()?;
+            samples.push(sensor.last_measurement());
+        }
+
+        Ok(self.analyze_samples(&samples, sample_hz))
+    }
+
+    /// 時刻付きの測定値の周波数解析を行う．
+    /// サンプリング周波数は，最初と最後の測定値の受信時刻の差から平均の受信間隔を求めて推定する．
+    /// 受信間隔のばらつきは考慮しないため，測定値はおおむね一定周期で収集したものを与えること．
+    ///
+    /// # Returns
+    /// 周波数解析の結果．測定値が2つ未満の場合や，受信時刻がすべて同じ場合は`None`を返す．
+    pub fn analyze_measurements(&mut self, measurements: &[Measurement]) -> Option<SpectrumResult> {
+        let (first, last) = (measurements.first()?, measurements.last()?);
+        let elapsed = last
+            .timestamp
+            .saturating_duration_since(first.timestamp)
+            .as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        let sample_hz = (measurements.len() - 1) as f64 / elapsed;
+        let samples = measurements.iter().map(|m| m.wrench).collect::<Vec<_>>();
+        Some(self.analyze_samples(&samples, sample_hz))
+    }
+
+    /// 一定周期で収集済みのレンチの周波数解析を行う．
+    /// # Params
+    /// 1. `samples`: 一定周期で収集したレンチ．
+    /// 1. `sample_hz`: サンプリング周波数[Hz]．
+    ///
+    /// # Panics
+    /// `samples`が空の場合，または`sample_hz`が正でない場合．
+    pub fn analyze_samples(&mut self, samples: &[Wrench], sample_hz: f64) -> SpectrumResult {
+        assert!(!samples.is_empty());
+        assert!(sample_hz > 0.0);
+
+        let n = samples.len();
+        let fft = self.planner.plan_fft_forward(n);
+        // 実数信号のスペクトルは対称なので，ナイキスト周波数までの片側のみを扱う
+        let bins = n / 2 + 1;
+
+        let frequencies = (0..bins).map(|k| k as f64 * sample_hz / n as f64).collect();
+
+        let mut magnitudes: [Vec<f64>; AXIS_COUNT] = Default::default();
+        for (axis, magnitude) in magnitudes.iter_mut().enumerate() {
+            let mut buffer: Vec<_> = samples
+                .iter()
+                .map(|w| Complex::new(w.to_array()[axis], 0.0))
+                .collect();
+            fft.process(&mut buffer);
+
+            // 片側振幅スペクトルに換算する．直流成分とナイキスト周波数の成分は折り返しがない
+            *magnitude = buffer[..bins]
+                .iter()
+                .enumerate()
+                .map(|(k, x)| {
+                    let scale = if k == 0 || 2 * k == n { 1.0 } else { 2.0 };
+                    scale * x.norm() / n as f64
+                })
+                .collect();
+        }
+
+        SpectrumResult {
+            frequencies,
+            magnitudes,
+        }
+    }
+}
+
+impl Default for WrenchFrequencyAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine_wave_peaks_at_its_frequency() {
+        // 64Hzで1秒間収集した，振幅2N，周波数10Hzの正弦波
+        let sample_hz = 64.0;
+        let samples = (0..64)
+            .map(|i| {
+                let t = i as f64 / sample_hz;
+                let fx = 2.0 * (2.0 * std::f64::consts::PI * 10.0 * t).sin();
+                Wrench::from_array([fx, 0.0, 0.0, 0.0, 0.0, 0.0])
+            })
+            .collect::<Vec<_>>();
+
+        let result = WrenchFrequencyAnalyzer::new().analyze_samples(&samples, sample_hz);
+
+        assert_eq!(result.frequencies.len(), 33);
+        assert_eq!(result.frequencies[10], 10.0);
+        assert_eq!(result.dominant_frequency(0), Some(10.0));
+        assert!((result.magnitudes[0][10] - 2.0).abs() < 1e-9);
+        assert!(result.magnitudes[1].iter().all(|&m| m.abs() < 1e-9));
+    }
+}
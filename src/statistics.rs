@@ -0,0 +1,468 @@
+//! レンチの統計量を計算するためのユーティリティ．
+
+use crate::{Wrench, WrenchRingBuffer, AXIS_COUNT};
+
+/// 直近`N`個のレンチについて，各軸の分散を逐次計算する．
+/// センサのノイズレベルの推定に利用できる．
+///
+/// 分散はWelfordのアルゴリズムをスライディングウィンドウ向けに拡張したものを用いて計算するため，
+/// 1回の更新にかかる計算量は`N`によらず一定である．
+#[derive(Debug, Clone)]
+pub struct WrenchWindowedVariance<const N: usize> {
+    /// 直近のレンチ．
+    window: WrenchRingBuffer<N>,
+    /// ウィンドウ内の各軸の平均．
+    mean: [f64; AXIS_COUNT],
+    /// ウィンドウ内の各軸の平均からの偏差の2乗和．
+    m2: [f64; AXIS_COUNT],
+}
+
+impl<const N: usize> WrenchWindowedVariance<N> {
+    /// サンプルを1つも持たない状態で作る．
+    /// # Panics
+    /// `N`が0の場合．
+    pub fn new() -> WrenchWindowedVariance<N> {
+        WrenchWindowedVariance {
+            window: WrenchRingBuffer::new(),
+            mean: [0.0; AXIS_COUNT],
+            m2: [0.0; AXIS_COUNT],
+        }
+    }
+
+    /// 新しいサンプルをウィンドウに追加する．
+    /// ウィンドウが埋まっている場合は，最も古いサンプルがウィンドウから取り除かれる．
+    pub fn update(&mut self, wrench: Wrench) {
+        let new = wrench.to_array();
+
+        match self.window.push(wrench) {
+            // ウィンドウが埋まっていた場合は，古いサンプルを新しいサンプルで置き換える
+            Some(evicted) => {
+                let old = evicted.to_array();
+                let n = N as f64;
+                let stats = self.mean.iter_mut().zip(self.m2.iter_mut());
+                for (i, (mean, m2)) in stats.enumerate() {
+                    let old_mean = *mean;
+                    let new_mean = old_mean + (new[i] - old[i]) / n;
+                    *m2 += (new[i] - old[i]) * (new[i] - new_mean + old[i] - old_mean);
+                    *mean = new_mean;
+                }
+            }
+            // まだウィンドウに空きがある場合は，通常のWelfordのアルゴリズムでサンプルを追加する
+            None => {
+                let n = self.window.len() as f64;
+                let stats = self.mean.iter_mut().zip(self.m2.iter_mut());
+                for (i, (mean, m2)) in stats.enumerate() {
+                    let delta = new[i] - *mean;
+                    *mean += delta / n;
+                    *m2 += delta * (new[i] - *mean);
+                }
+            }
+        }
+    }
+
+    /// ウィンドウ内のサンプル数を返す．
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// ウィンドウ内にサンプルがなければ`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// ウィンドウ内の各軸の平均を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn mean(&self) -> [f64; AXIS_COUNT] {
+        self.mean
+    }
+
+    /// ウィンドウ内の各軸の分散`E[(x - E[x])²]`を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    /// サンプルがない場合はすべて0となる．
+    pub fn variance(&self) -> [f64; AXIS_COUNT] {
+        let n = self.window.len();
+        let mut variance = [0.0; AXIS_COUNT];
+        if n > 0 {
+            for (v, m2) in variance.iter_mut().zip(self.m2.iter()) {
+                // 丸め誤差でわずかに負になることがあるので0で打ち切る
+                *v = (m2 / n as f64).max(0.0);
+            }
+        }
+        variance
+    }
+
+    /// ウィンドウ内の各軸の標準偏差を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn std_dev(&self) -> [f64; AXIS_COUNT] {
+        let mut std_dev = self.variance();
+        for e in std_dev.iter_mut() {
+            *e = e.sqrt();
+        }
+        std_dev
+    }
+
+    /// ウィンドウ内のサンプルをすべて捨てる．
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.mean = [0.0; AXIS_COUNT];
+        self.m2 = [0.0; AXIS_COUNT];
+    }
+}
+
+impl<const N: usize> Default for WrenchWindowedVariance<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// レンチの標本の共分散行列を計算する．
+/// カルマンフィルタなどの推定器で用いる，測定ノイズの共分散行列の同定に利用できる．
+///
+/// 共分散行列は`[fx, fy, fz, tx, ty, tz]`を基底とする6×6行列であり，
+/// 標本数で割った標本共分散`E[(x - E[x])(y - E[y])]`を要素とする．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchCovariance {
+    /// これまでに与えたサンプル数．
+    count: usize,
+    /// 各軸の平均．
+    mean: [f64; AXIS_COUNT],
+    /// 各軸の組についての，平均からの偏差の積の和．
+    comoment: [[f64; AXIS_COUNT]; AXIS_COUNT],
+}
+
+impl WrenchCovariance {
+    /// サンプルを1つも持たない状態で作る．
+    pub fn new() -> WrenchCovariance {
+        WrenchCovariance {
+            count: 0,
+            mean: [0.0; AXIS_COUNT],
+            comoment: [[0.0; AXIS_COUNT]; AXIS_COUNT],
+        }
+    }
+
+    /// 標本全体から共分散行列を計算して返す．
+    /// 標本が空の場合は零行列を返す．
+    pub fn compute(samples: &[Wrench]) -> [[f64; AXIS_COUNT]; AXIS_COUNT] {
+        let mut covariance = WrenchCovariance::new();
+        for &sample in samples {
+            covariance.update_online(sample);
+        }
+        covariance.covariance()
+    }
+
+    /// Welfordのアルゴリズムによって，新しいサンプルを共分散行列の推定に加える．
+    pub fn update_online(&mut self, wrench: Wrench) {
+        let x = wrench.to_array();
+        self.count += 1;
+        let n = self.count as f64;
+
+        // 平均を更新する前の偏差
+        let mut delta = [0.0; AXIS_COUNT];
+        for ((d, mean), x) in delta.iter_mut().zip(self.mean.iter_mut()).zip(x.iter()) {
+            *d = x - *mean;
+            *mean += *d / n;
+        }
+
+        for (row, d) in self.comoment.iter_mut().zip(delta.iter()) {
+            for ((c, x), mean) in row.iter_mut().zip(x.iter()).zip(self.mean.iter()) {
+                *c += d * (x - mean);
+            }
+        }
+    }
+
+    /// これまでに与えたサンプル数を返す．
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// これまでに与えたサンプルの各軸の平均を`[fx, fy, fz, tx, ty, tz]`の順に返す．
+    pub fn mean(&self) -> [f64; AXIS_COUNT] {
+        self.mean
+    }
+
+    /// これまでに与えたサンプルの共分散行列を返す．
+    /// サンプルがない場合は零行列を返す．
+    pub fn covariance(&self) -> [[f64; AXIS_COUNT]; AXIS_COUNT] {
+        let mut covariance = [[0.0; AXIS_COUNT]; AXIS_COUNT];
+        if self.count > 0 {
+            let n = self.count as f64;
+            for (row, comoment_row) in covariance.iter_mut().zip(self.comoment.iter()) {
+                for (c, comoment) in row.iter_mut().zip(comoment_row.iter()) {
+                    *c = comoment / n;
+                }
+            }
+        }
+        covariance
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn clear(&mut self) {
+        *self = WrenchCovariance::new();
+    }
+}
+
+impl Default for WrenchCovariance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `WrenchStats`などで求めた，各軸の統計量の要約．
+/// 各統計量は`[fx, fy, fz, tx, ty, tz]`の順に，SI単位で並ぶ．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrenchStatsSummary {
+    /// サンプル数．
+    pub count: usize,
+    /// 各軸の最小値．
+    pub min: [f64; AXIS_COUNT],
+    /// 各軸の最大値．
+    pub max: [f64; AXIS_COUNT],
+    /// 各軸の平均．
+    pub mean: [f64; AXIS_COUNT],
+    /// 各軸の標準偏差．標本数で割った分散の平方根である．
+    pub std_dev: [f64; AXIS_COUNT],
+    /// 各軸の二乗平均平方根．
+    pub rms: [f64; AXIS_COUNT],
+}
+
+/// 与えたレンチすべてについて，各軸の最小値，最大値，平均，標準偏差，二乗平均平方根を逐次求める．
+/// 実験の報告や，異常検知の基準値の算出に利用できる．
+///
+/// サンプルを保持しないため，長時間の計測でもメモリ使用量は一定である．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrenchStats {
+    /// これまでに与えたサンプル数．
+    count: usize,
+    /// 各軸の最小値．
+    min: [f64; AXIS_COUNT],
+    /// 各軸の最大値．
+    max: [f64; AXIS_COUNT],
+    /// 各軸の平均．
+    mean: [f64; AXIS_COUNT],
+    /// 各軸の平均からの偏差の2乗和．
+    m2: [f64; AXIS_COUNT],
+    /// 各軸の2乗の平均．
+    mean_square: [f64; AXIS_COUNT],
+}
+
+impl WrenchStats {
+    /// サンプルを1つも持たない状態で作る．
+    pub fn new() -> WrenchStats {
+        WrenchStats {
+            count: 0,
+            min: [f64::INFINITY; AXIS_COUNT],
+            max: [f64::NEG_INFINITY; AXIS_COUNT],
+            mean: [0.0; AXIS_COUNT],
+            m2: [0.0; AXIS_COUNT],
+            mean_square: [0.0; AXIS_COUNT],
+        }
+    }
+
+    /// 新しいサンプルを統計量に加える．
+    pub fn update(&mut self, wrench: Wrench) {
+        self.count += 1;
+        let n = self.count as f64;
+        for (i, &x) in wrench.to_array().iter().enumerate() {
+            self.min[i] = self.min[i].min(x);
+            self.max[i] = self.max[i].max(x);
+            let delta = x - self.mean[i];
+            self.mean[i] += delta / n;
+            self.m2[i] += delta * (x - self.mean[i]);
+            self.mean_square[i] += (x * x - self.mean_square[i]) / n;
+        }
+    }
+
+    /// これまでに与えたサンプル数を返す．
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// これまでに与えたサンプルの統計量を返す．
+    /// サンプルがない場合は`None`を返す．
+    pub fn summary(&self) -> Option<WrenchStatsSummary> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let n = self.count as f64;
+        Some(WrenchStatsSummary {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            // 丸め誤差でわずかに負になることがあるので0で打ち切る
+            std_dev: self.m2.map(|m2| (m2 / n).max(0.0).sqrt()),
+            rms: self.mean_square.map(f64::sqrt),
+        })
+    }
+
+    /// これまでに与えたサンプルをすべて捨てる．
+    pub fn clear(&mut self) {
+        *self = WrenchStats::new();
+    }
+}
+
+impl Default for WrenchStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 直近`N`個のレンチについて，`WrenchStats`と同じ統計量を求める．
+///
+/// 最小値と最大値を求めるため，`summary`の計算量は`N`に比例する．
+#[derive(Debug, Clone)]
+pub struct WrenchWindowedStats<const N: usize> {
+    /// 直近のレンチ．
+    window: WrenchRingBuffer<N>,
+}
+
+impl<const N: usize> WrenchWindowedStats<N> {
+    /// サンプルを1つも持たない状態で作る．
+    /// # Panics
+    /// `N`が0の場合．
+    pub fn new() -> WrenchWindowedStats<N> {
+        WrenchWindowedStats {
+            window: WrenchRingBuffer::new(),
+        }
+    }
+
+    /// 新しいサンプルをウィンドウに追加する．
+    /// ウィンドウが埋まっている場合は，最も古いサンプルがウィンドウから取り除かれる．
+    pub fn update(&mut self, wrench: Wrench) {
+        self.window.push(wrench);
+    }
+
+    /// ウィンドウ内のサンプル数を返す．
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// ウィンドウ内にサンプルがなければ`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// ウィンドウ内のサンプルの統計量を返す．
+    /// サンプルがない場合は`None`を返す．
+    pub fn summary(&self) -> Option<WrenchStatsSummary> {
+        let mut stats = WrenchStats::new();
+        for &wrench in self.window.iter() {
+            stats.update(wrench);
+        }
+        stats.summary()
+    }
+
+    /// ウィンドウ内のサンプルをすべて捨てる．
+    pub fn clear(&mut self) {
+        self.window.clear();
+    }
+}
+
+impl<const N: usize> Default for WrenchWindowedStats<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各軸の値が`[x, 2x, 3x, -x, -2x, -3x]`となるレンチを作る．
+    fn scaled(x: f64) -> Wrench {
+        Wrench::from_array([x, 2.0 * x, 3.0 * x, -x, -2.0 * x, -3.0 * x])
+    }
+
+    fn assert_close(actual: [f64; AXIS_COUNT], expected: [f64; AXIS_COUNT]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn windowed_variance_of_alternating_signal() {
+        // ±1を交互にとる信号の分散は1となる
+        let mut variance = WrenchWindowedVariance::<4>::new();
+        for i in 0..10 {
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            variance.update(scaled(sign));
+        }
+
+        assert_eq!(variance.len(), 4);
+        assert_close(variance.mean(), [0.0; AXIS_COUNT]);
+        assert_close(variance.variance(), [1.0, 4.0, 9.0, 1.0, 4.0, 9.0]);
+        assert_close(variance.std_dev(), [1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn windowed_variance_only_covers_latest_samples() {
+        // 0, 1, ..., 9を与えると，ウィンドウには6, 7, 8, 9が残り，その分散は1.25となる
+        let mut variance = WrenchWindowedVariance::<4>::new();
+        for i in 0..10 {
+            variance.update(scaled(i as f64));
+        }
+
+        assert_close(variance.mean(), scaled(7.5).to_array());
+        assert_close(variance.variance(), [1.25, 5.0, 11.25, 1.25, 5.0, 11.25]);
+    }
+
+    #[test]
+    fn covariance_diagonal_equals_variance() {
+        let samples = (0..10)
+            .map(|i| {
+                let x = (i * 3 % 7) as f64;
+                Wrench::from_array([x, -2.0 * x, x * x, 1.0, 0.5 * x + 1.0, (i % 2) as f64])
+            })
+            .collect::<Vec<_>>();
+
+        let computed = WrenchCovariance::compute(&samples);
+        let mut online = WrenchCovariance::new();
+        let mut variance = WrenchWindowedVariance::<10>::new();
+        for &sample in samples.iter() {
+            online.update_online(sample);
+            variance.update(sample);
+        }
+
+        assert_eq!(online.count(), samples.len());
+        assert_close(online.mean(), variance.mean());
+        let diagonal = |c: [[f64; AXIS_COUNT]; AXIS_COUNT]| {
+            let mut d = [0.0; AXIS_COUNT];
+            for (i, e) in d.iter_mut().enumerate() {
+                *e = c[i][i];
+            }
+            d
+        };
+        assert_close(diagonal(computed), variance.variance());
+        // 平均を求めてから偏差の積を平均する，2パスの計算と一致する
+        let mean = online.mean();
+        let n = samples.len() as f64;
+        for (i, online_row) in online.covariance().iter().enumerate() {
+            let mut expected = [0.0; AXIS_COUNT];
+            for (j, e) in expected.iter_mut().enumerate() {
+                *e = samples
+                    .iter()
+                    .map(|w| (w.to_array()[i] - mean[i]) * (w.to_array()[j] - mean[j]))
+                    .sum::<f64>()
+                    / n;
+            }
+            assert_close(*online_row, expected);
+            assert_close(computed[i], expected);
+        }
+        // fyはfxの-2倍なので，共分散は分散の-2倍となり，行列は対称となる
+        assert!((computed[0][1] + 2.0 * computed[0][0]).abs() < 1e-9);
+        assert!((computed[0][1] - computed[1][0]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn windowed_variance_while_filling() {
+        let mut variance = WrenchWindowedVariance::<8>::new();
+        assert_close(variance.variance(), [0.0; AXIS_COUNT]);
+
+        variance.update(scaled(1.0));
+        variance.update(scaled(3.0));
+        assert_eq!(variance.len(), 2);
+        assert_close(variance.variance(), [1.0, 4.0, 9.0, 1.0, 4.0, 9.0]);
+
+        variance.clear();
+        assert!(variance.is_empty());
+        assert_close(variance.variance(), [0.0; AXIS_COUNT]);
+    }
+}
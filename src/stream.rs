@@ -0,0 +1,43 @@
+//! 測定値の非同期ストリーム．
+
+use crate::{AsyncDynPick, SensorError, SensorSpec, Wrench};
+use futures::stream::{self, Stream};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// センサの測定値を順に返す非同期ストリーム．
+///
+/// 要素を要求されるたびにセンサと通信して測定値を更新し，補正後の測定値を返す．
+/// ストリームのコンビネータを利用して，間引きや他のセンサとの組み合わせなどを行える．
+pub struct WrenchStream {
+    inner: Pin<Box<dyn Stream<Item = Result<Wrench, SensorError>> + Send>>,
+}
+
+impl WrenchStream {
+    /// センサの測定値を順に返すストリームを作る．
+    pub fn new<S: SensorSpec + 'static>(sensor: AsyncDynPick<S>) -> WrenchStream {
+        let inner = stream::unfold(sensor, |mut sensor| async move {
+            let measurement = sensor.update().await.map(|_| sensor.last_measurement());
+            Some((measurement, sensor))
+        });
+
+        WrenchStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl Stream for WrenchStream {
+    type Item = Result<Wrench, SensorError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<S: SensorSpec + 'static> AsyncDynPick<S> {
+    /// このセンサの測定値を順に返す非同期ストリームに変換する．
+    pub fn into_stream(self) -> WrenchStream {
+        WrenchStream::new(self)
+    }
+}
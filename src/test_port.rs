@@ -0,0 +1,198 @@
+//! 実機のセンサを接続せずに`DynPick`を試験するための，シリアルポートの代替．
+
+use serialport::{
+    ClearBuffer, DataBits, FlowControl, Parity, SerialPort, SerialPortSettings, StopBits,
+};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// `FakeSerialPort`への操作の記録と，これから読み出されるデータ．
+#[derive(Debug, Default)]
+pub(crate) struct FakePortState {
+    /// これまでに書き込まれたバイト列．
+    pub written: Vec<u8>,
+    /// `flush`が呼ばれた回数．
+    pub flush_count: usize,
+    /// これから読み出されるバイト列．
+    pub incoming: VecDeque<u8>,
+    /// 現在のタイムアウト．
+    pub timeout: Duration,
+    /// これまでに`set_timeout`で設定されたタイムアウト．
+    pub timeout_history: Vec<Duration>,
+}
+
+/// 書き込みを記録し，あらかじめ与えたバイト列を読み出すシリアルポート．
+/// 状態は複製したハンドルと共有するため，`DynPick`に渡した後も操作を確かめられる．
+///
+/// 読み出すデータがない場合は，待機せずにタイムアウトのエラーを返す．
+#[derive(Debug, Clone)]
+pub(crate) struct FakeSerialPort {
+    /// 操作の記録と，これから読み出されるデータ．
+    state: Arc<Mutex<FakePortState>>,
+}
+
+impl FakeSerialPort {
+    /// 指定したタイムアウトを持つシリアルポートを作る．
+    pub fn new(timeout: Duration) -> FakeSerialPort {
+        let state = FakePortState {
+            timeout,
+            ..FakePortState::default()
+        };
+        FakeSerialPort {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// 操作の記録と，これから読み出されるデータを返す．
+    pub fn state(&self) -> std::sync::MutexGuard<'_, FakePortState> {
+        self.state.lock().unwrap()
+    }
+
+    /// 読み出されるバイト列を末尾に追加する．
+    pub fn push_incoming(&self, bytes: &[u8]) {
+        self.state().incoming.extend(bytes.iter().copied());
+    }
+}
+
+impl Read for FakeSerialPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.state();
+        if state.incoming.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let count = buf.len().min(state.incoming.len());
+        for (b, byte) in buf.iter_mut().zip(state.incoming.drain(..count)) {
+            *b = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for FakeSerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.state().written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state().flush_count += 1;
+        Ok(())
+    }
+}
+
+impl SerialPort for FakeSerialPort {
+    fn name(&self) -> Option<String> {
+        Some("fake".to_string())
+    }
+
+    fn settings(&self) -> SerialPortSettings {
+        SerialPortSettings {
+            baud_rate: crate::BAUD_RATE,
+            data_bits: DataBits::Eight,
+            flow_control: FlowControl::None,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            timeout: self.timeout(),
+        }
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(crate::BAUD_RATE)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.state().timeout
+    }
+
+    fn set_all(&mut self, settings: &SerialPortSettings) -> serialport::Result<()> {
+        self.set_timeout(settings.timeout)
+    }
+
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, _data_bits: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, _flow_control: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_parity(&mut self, _parity: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, _stop_bits: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        let mut state = self.state();
+        state.timeout = timeout;
+        state.timeout_history.push(timeout);
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.state().incoming.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if !matches!(buffer_to_clear, ClearBuffer::Output) {
+            self.state().incoming.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(self.clone()))
+    }
+}
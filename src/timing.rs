@@ -0,0 +1,77 @@
+//! 一定周期で測定を行うためのタイミング制御．
+
+use std::time::{Duration, Instant};
+
+/// `std::thread::sleep`のみで待機する場合に，起床時刻の誤差を見込んで早めに起きる時間．
+/// 残りの時間はビジーウェイトで待機する．
+const SPIN_DURATION: Duration = Duration::from_micros(500);
+
+/// 一定周期の時刻ごとに要素を返すイテレータ．
+///
+/// `std::thread::sleep`とビジーウェイトを組み合わせて待機するため，
+/// `std::thread::sleep`のみで待機するよりも精度よく周期を保てる．
+/// 処理が遅れて予定時刻を過ぎてしまった場合，過ぎた分の周期は読み飛ばされる．
+///
+/// ```no_run
+/// use wacohtech_force_torque_sensor::{Wdf6m200, WrenchSamplingScheduler};
+/// # use std::time::Duration;
+/// # let mut sensor = Wdf6m200::open(Duration::from_millis(10)).unwrap();
+///
+/// for _tick in WrenchSamplingScheduler::new(1000.0).take(1000) {
+///     sensor.update().unwrap();
+///     println!("{:?}", sensor.last_measurement());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WrenchSamplingScheduler {
+    /// 周期．
+    period: Duration,
+    /// 次に要素を返す予定時刻．
+    next_tick: Instant,
+}
+
+impl WrenchSamplingScheduler {
+    /// 指定した周波数で要素を返すイテレータを作る．最初の要素はすぐに返される．
+    /// # Panics
+    /// `hz`が正でない場合．
+    pub fn new(hz: f64) -> WrenchSamplingScheduler {
+        assert!(hz > 0.0);
+
+        WrenchSamplingScheduler {
+            period: Duration::from_secs_f64(1.0 / hz),
+            next_tick: Instant::now(),
+        }
+    }
+
+    /// 周期を返す．
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+impl Iterator for WrenchSamplingScheduler {
+    /// 要素を返した予定時刻．
+    type Item = Instant;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let now = Instant::now();
+        // 大幅に遅れた場合は，過ぎてしまった周期を読み飛ばす
+        while self.next_tick + self.period <= now {
+            self.next_tick += self.period;
+        }
+
+        // 予定時刻の少し前まではスリープし，残りはビジーウェイトで待機する
+        if let Some(remaining) = self.next_tick.checked_duration_since(now) {
+            if remaining > SPIN_DURATION {
+                std::thread::sleep(remaining - SPIN_DURATION);
+            }
+        }
+        while Instant::now() < self.next_tick {
+            std::hint::spin_loop();
+        }
+
+        let tick = self.next_tick;
+        self.next_tick += self.period;
+        Some(tick)
+    }
+}
@@ -0,0 +1,177 @@
+//! 測定値をUDPで他の計算機に配信するための機能．
+
+use crate::logging::MeasurementSink;
+use crate::{Measurement, WrenchF32, AXIS_COUNT};
+use std::convert::TryInto;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// `WrenchPacket`をエンコードしたバイト数．
+pub const WRENCH_PACKET_BYTES: usize = 8 + 8 + 4 * AXIS_COUNT;
+
+/// `UdpPublisher`が送信する1件の測定値．
+///
+/// パケットは次の順に並べた固定長のバイト列であり，数値はすべてリトルエンディアンで格納する．
+/// 1. 測定値を受信した時刻のUNIX時刻[ns](`u64`)．
+/// 1. 通し番号(`u64`)．
+/// 1. 測定値`[fx, fy, fz, tx, ty, tz]`(`f32` × 6，SI単位)．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrenchPacket {
+    /// 測定値を受信した時刻のシステム時刻．
+    pub system_time: SystemTime,
+    /// ホスト側で付与した通し番号．
+    pub sequence: u64,
+    /// 測定値．
+    pub wrench: WrenchF32,
+}
+
+impl WrenchPacket {
+    /// 測定値から作る．
+    pub fn from_measurement(measurement: &Measurement) -> WrenchPacket {
+        WrenchPacket {
+            system_time: measurement.system_time,
+            sequence: measurement.sequence,
+            wrench: measurement.wrench.into(),
+        }
+    }
+
+    /// 送信するバイト列にエンコードする．
+    pub fn encode(&self) -> [u8; WRENCH_PACKET_BYTES] {
+        let unix_time = self
+            .system_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+
+        let mut packet = [0; WRENCH_PACKET_BYTES];
+        packet[0..8].copy_from_slice(&unix_time.to_le_bytes());
+        packet[8..16].copy_from_slice(&self.sequence.to_le_bytes());
+        for (i, e) in self.wrench.to_array().iter().enumerate() {
+            packet[16 + 4 * i..20 + 4 * i].copy_from_slice(&e.to_le_bytes());
+        }
+        packet
+    }
+
+    /// 受信したバイト列をデコードする．
+    /// 長さが`WRENCH_PACKET_BYTES`でない場合は`None`を返す．
+    pub fn decode(packet: &[u8]) -> Option<WrenchPacket> {
+        if packet.len() != WRENCH_PACKET_BYTES {
+            return None;
+        }
+
+        let u64_at = |i: usize| u64::from_le_bytes(packet[i..i + 8].try_into().unwrap());
+        let mut wrench = [0.0; AXIS_COUNT];
+        for (i, e) in wrench.iter_mut().enumerate() {
+            *e = f32::from_le_bytes(packet[16 + 4 * i..20 + 4 * i].try_into().unwrap());
+        }
+        Some(WrenchPacket {
+            system_time: UNIX_EPOCH + Duration::from_nanos(u64_at(0)),
+            sequence: u64_at(8),
+            wrench: WrenchF32::from_array(wrench),
+        })
+    }
+}
+
+/// 測定値を1件ずつ小さな固定長のUDPパケットとして送信する．
+/// 別の計算機で動作する実時間制御器に，小さい遅れで測定値を渡すために用いる．
+///
+/// 送信先にはユニキャストアドレスとマルチキャストアドレスのどちらも指定できる．
+/// パケットの形式は`WrenchPacket`を参照．受信側では`WrenchPacket::decode`でデコードできる．
+pub struct UdpPublisher {
+    /// 送信に用いるソケット．
+    socket: UdpSocket,
+    /// 送信先のアドレス．
+    target: SocketAddr,
+}
+
+impl UdpPublisher {
+    /// 送信先を指定して作る．送信元のポートは自動で割り当てる．
+    /// # Params
+    /// 1. `target`: 送信先のアドレス．`"192.168.0.10:50000"`や`"239.0.0.1:50000"`など．
+    pub fn new<A: ToSocketAddrs>(target: A) -> io::Result<UdpPublisher> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No target address resolved")
+        })?;
+        let local: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(target)?;
+
+        Ok(UdpPublisher { socket, target })
+    }
+
+    /// 送信先のアドレスを返す．
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// IPv4のマルチキャストパケットが通過できるルータの数を設定する．既定では1であり，同一のネットワーク内にのみ届く．
+    pub fn set_multicast_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.socket.set_multicast_ttl_v4(ttl)
+    }
+
+    /// 測定値を1件送信する．
+    pub fn publish(&self, measurement: &Measurement) -> io::Result<()> {
+        self.publish_packet(&WrenchPacket::from_measurement(measurement))
+    }
+
+    /// パケットを1件送信する．
+    pub fn publish_packet(&self, packet: &WrenchPacket) -> io::Result<()> {
+        self.socket.send(&packet.encode()).map(|_| ())
+    }
+}
+
+impl MeasurementSink for UdpPublisher {
+    /// 換算前のデジタル出力値はパケットに含まれないため，送信しない．
+    fn log(&mut self, measurement: &Measurement, _raw_counts: [u16; AXIS_COUNT]) -> io::Result<()> {
+        self.publish(measurement)
+    }
+
+    /// パケットは1件ずつ即座に送信するため，何もしない．
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet() -> WrenchPacket {
+        WrenchPacket {
+            system_time: UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+            sequence: 42,
+            wrench: WrenchF32::from_array([1.5, -2.25, 3.0, 0.125, -0.5, 1e-3]),
+        }
+    }
+
+    #[test]
+    fn packet_round_trips_through_encode_and_decode() {
+        let packet = packet();
+        let bytes = packet.encode();
+        assert_eq!(&bytes[0..8], &1_700_000_000_123_456_789u64.to_le_bytes());
+        assert_eq!(&bytes[8..16], &42u64.to_le_bytes());
+        assert_eq!(&bytes[16..20], &1.5f32.to_le_bytes());
+        assert_eq!(&bytes[WRENCH_PACKET_BYTES - 4..], &1e-3f32.to_le_bytes());
+
+        assert_eq!(WrenchPacket::decode(&bytes), Some(packet));
+    }
+
+    #[test]
+    fn packet_with_wrong_length_is_rejected() {
+        let bytes = packet().encode();
+        assert_eq!(
+            WrenchPacket::decode(&bytes[..WRENCH_PACKET_BYTES - 1]),
+            None
+        );
+
+        let mut longer = bytes.to_vec();
+        longer.push(0);
+        assert_eq!(WrenchPacket::decode(&longer), None);
+        assert_eq!(WrenchPacket::decode(&[]), None);
+    }
+}
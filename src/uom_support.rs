@@ -0,0 +1,47 @@
+//! uomの物理量の型との相互変換．
+//! `dimensioned`ではなくuomで物理量を扱っているプロジェクトに，単位を外さずに測定値を渡せるようにする．
+
+use crate::{Newton, NewtonMeter, Triplet, Wrench};
+use uom::si::f64::{Force, Torque};
+use uom::si::force::newton;
+use uom::si::torque::newton_meter;
+
+impl Wrench {
+    /// 力の各成分をuomの力として返す．
+    pub fn force_uom(&self) -> Triplet<Force> {
+        self.force.map(newton_to_force)
+    }
+
+    /// トルクの各成分をuomのトルクとして返す．
+    pub fn torque_uom(&self) -> Triplet<Torque> {
+        self.torque.map(newton_meter_to_torque)
+    }
+
+    /// uomの力とトルクから`Wrench`を作る．
+    pub fn from_uom(force: Triplet<Force>, torque: Triplet<Torque>) -> Wrench {
+        Wrench::new(
+            force.map(force_to_newton),
+            torque.map(torque_to_newton_meter),
+        )
+    }
+}
+
+/// 力をuomの力に変換する．
+pub fn newton_to_force(force: Newton<f64>) -> Force {
+    Force::new::<newton>(force.value_unsafe)
+}
+
+/// uomの力を力に変換する．
+pub fn force_to_newton(force: Force) -> Newton<f64> {
+    Newton::new(force.get::<newton>())
+}
+
+/// トルクをuomのトルクに変換する．
+pub fn newton_meter_to_torque(torque: NewtonMeter<f64>) -> Torque {
+    Torque::new::<newton_meter>(torque.value_unsafe)
+}
+
+/// uomのトルクをトルクに変換する．
+pub fn torque_to_newton_meter(torque: Torque) -> NewtonMeter<f64> {
+    NewtonMeter::<f64>::new(torque.get::<newton_meter>())
+}
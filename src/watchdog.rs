@@ -0,0 +1,91 @@
+//! センサからのデータが途絶えたことを検出するための機能．
+
+use std::time::{Duration, Instant};
+
+/// 途絶を検出した際に呼び出される処理．
+type StallHandler = Box<dyn FnMut(Duration) + Send>;
+
+/// 有効なフレームを最後に受信してからの経過時間を監視し，一定時間を超えたらデータの途絶とみなす．
+///
+/// USBケーブルが抜けた場合などには`last_measurement`が最後の値のまま変化しなくなるため，
+/// 途絶を検出して制御を止めなければ，古い測定値に基づいてロボットを動かし続けてしまう．
+/// `DynPick::set_watchdog`で設定すると，途絶している間の`update`は`SensorError::DataStalled`を返す．
+///
+/// コールバックは途絶が始まったときにだけ呼び出し，有効なフレームを再び受信するまでは繰り返さない．
+pub struct DataStallWatchdog {
+    /// 途絶とみなすまでの時間．
+    timeout: Duration,
+    /// 途絶を検出した際に呼び出される処理．
+    handler: Option<StallHandler>,
+    /// 最初のフレームを受信する前に，経過時間の起点とする時刻．
+    armed_at: Instant,
+    /// 現在途絶していると判定していれば`true`．
+    stalled: bool,
+}
+
+impl DataStallWatchdog {
+    /// # Params
+    /// 1. `timeout`: 有効なフレームをこの時間受信しなければ途絶とみなす．
+    ///
+    /// # Panics
+    /// `timeout`が0の場合．
+    pub fn new(timeout: Duration) -> DataStallWatchdog {
+        assert!(timeout > Duration::from_secs(0));
+
+        DataStallWatchdog {
+            timeout,
+            handler: None,
+            armed_at: Instant::now(),
+            stalled: false,
+        }
+    }
+
+    /// 途絶を検出したときに呼び出される処理を設定する．
+    /// コールバックには，最後に有効なフレームを受信してからの経過時間が渡される．
+    /// コールバックは受信処理の中で呼び出されるため，時間のかかる処理は行わないこと．
+    pub fn with_callback(mut self, callback: impl FnMut(Duration) + Send + 'static) -> Self {
+        self.handler = Some(Box::new(callback));
+        self
+    }
+
+    /// 途絶とみなすまでの時間を返す．
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 直前の判定で途絶していれば`true`を返す．
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// 途絶しているかどうかを判定する．途絶が始まった場合はコールバックを呼び出す．
+    /// # Params
+    /// 1. `last_frame`: 最後に有効なフレームを受信した時刻．まだ受信していなければ`None`．
+    /// 1. `now`: 現在時刻．
+    ///
+    /// # Returns
+    /// 途絶していれば，最後に有効なフレームを受信してからの経過時間`d`を`Some(d)`として返す．
+    /// まだ受信していない場合は，監視を始めてからの経過時間を用いる．
+    pub fn check(&mut self, last_frame: Option<Instant>, now: Instant) -> Option<Duration> {
+        let since = last_frame.unwrap_or(self.armed_at);
+        let elapsed = now.saturating_duration_since(since);
+        if elapsed < self.timeout {
+            self.stalled = false;
+            return None;
+        }
+
+        if !self.stalled {
+            self.stalled = true;
+            if let Some(handler) = &mut self.handler {
+                handler(elapsed);
+            }
+        }
+        Some(elapsed)
+    }
+
+    /// 途絶の判定を捨て，現在時刻から改めて監視を始める．
+    pub fn reset(&mut self) {
+        self.armed_at = Instant::now();
+        self.stalled = false;
+    }
+}
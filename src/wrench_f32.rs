@@ -0,0 +1,202 @@
+//! 単精度浮動小数点数で表したレンチ．
+
+use crate::{Newton, NewtonMeter, PerNewton, PerNewtonMeter, Triplet, Wrench, AXIS_COUNT};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// 単精度浮動小数点数で表したレンチ(力とトルクのペア)．
+/// 高頻度なログの記録など，メモリ使用量を抑えたい場合に`Wrench`の代わりに利用できる．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrenchF32 {
+    /// 力．
+    pub force: Triplet<Newton<f32>>,
+    /// トルク．
+    pub torque: Triplet<NewtonMeter<f32>>,
+}
+
+impl WrenchF32 {
+    pub const fn new(force: Triplet<Newton<f32>>, torque: Triplet<NewtonMeter<f32>>) -> WrenchF32 {
+        WrenchF32 { force, torque }
+    }
+
+    /// 力とトルクが0である`WrenchF32`を返す．
+    pub fn zeroed() -> WrenchF32 {
+        let force = Triplet::from_cloned(0.0).map(Newton::new);
+        let torque = Triplet::from_cloned(0.0).map(NewtonMeter::<f32>::new);
+        WrenchF32 { force, torque }
+    }
+
+    /// 力とトルクの各成分をSI単位の値として`[fx, fy, fz, tx, ty, tz]`の順に並べた配列を返す．
+    pub fn to_array(self) -> [f32; AXIS_COUNT] {
+        [
+            self.force.x.value_unsafe,
+            self.force.y.value_unsafe,
+            self.force.z.value_unsafe,
+            self.torque.x.value_unsafe,
+            self.torque.y.value_unsafe,
+            self.torque.z.value_unsafe,
+        ]
+    }
+
+    /// SI単位の値を`[fx, fy, fz, tx, ty, tz]`の順に並べた配列から`WrenchF32`を作る．
+    pub fn from_array(array: [f32; AXIS_COUNT]) -> WrenchF32 {
+        let force = Triplet::new(array[0], array[1], array[2]).map(Newton::new);
+        let torque = Triplet::new(array[3], array[4], array[5]).map(NewtonMeter::<f32>::new);
+        WrenchF32 { force, torque }
+    }
+
+    /// センサから受信した各軸のデジタル出力値を，倍精度を経由せずに単精度のまま力とトルクに換算する．
+    /// # Params
+    /// 1. `counts`: 各軸のデジタル出力値．`[fx, fy, fz, tx, ty, tz]`の順に並ぶ．
+    /// 1. `center`: 無負荷時のデジタル出力値の中心値．
+    /// 1. `force_sensitivity`: 力の各軸について，1Nあたりデジタル出力値がいくつ変化するか．
+    /// 1. `torque_sensitivity`: トルクの各軸について，1Nmあたりデジタル出力値がいくつ変化するか．
+    pub fn from_counts(
+        counts: [u16; AXIS_COUNT],
+        center: u16,
+        force_sensitivity: Triplet<PerNewton<f32>>,
+        torque_sensitivity: Triplet<PerNewtonMeter<f32>>,
+    ) -> WrenchF32 {
+        let centered = counts.map(|c| c as f32 - center as f32);
+        let force = Triplet::new(centered[0], centered[1], centered[2])
+            .map_entrywise(force_sensitivity, |d, s| d / s);
+        let torque = Triplet::new(centered[3], centered[4], centered[5])
+            .map_entrywise(torque_sensitivity, |d, s| d / s);
+        WrenchF32 { force, torque }
+    }
+}
+
+impl Add for WrenchF32 {
+    type Output = WrenchF32;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let force = self.force + rhs.force;
+        let torque = self.torque + rhs.torque;
+        WrenchF32 { force, torque }
+    }
+}
+
+impl Sub for WrenchF32 {
+    type Output = WrenchF32;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let force = self.force - rhs.force;
+        let torque = self.torque - rhs.torque;
+        WrenchF32 { force, torque }
+    }
+}
+
+impl AddAssign for WrenchF32 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for WrenchF32 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for WrenchF32 {
+    type Output = WrenchF32;
+
+    /// 力とトルクの各成分を`rhs`倍する．
+    fn mul(self, rhs: f32) -> Self::Output {
+        let force = self.force.map(|e| e * rhs);
+        let torque = self.torque.map(|e| e * rhs);
+        WrenchF32 { force, torque }
+    }
+}
+
+impl Div<f32> for WrenchF32 {
+    type Output = WrenchF32;
+
+    /// 力とトルクの各成分を`rhs`で割る．
+    fn div(self, rhs: f32) -> Self::Output {
+        let force = self.force.map(|e| e / rhs);
+        let torque = self.torque.map(|e| e / rhs);
+        WrenchF32 { force, torque }
+    }
+}
+
+impl Neg for WrenchF32 {
+    type Output = WrenchF32;
+
+    fn neg(self) -> Self::Output {
+        self * -1.0
+    }
+}
+
+impl Sum for WrenchF32 {
+    fn sum<I: Iterator<Item = WrenchF32>>(iter: I) -> Self {
+        iter.fold(WrenchF32::zeroed(), |acc, cur| acc + cur)
+    }
+}
+
+impl From<WrenchF32> for [f32; AXIS_COUNT] {
+    /// 各成分をSI単位の値として`[fx, fy, fz, tx, ty, tz]`の順に並べる．
+    fn from(wrench: WrenchF32) -> Self {
+        wrench.to_array()
+    }
+}
+
+impl From<[f32; AXIS_COUNT]> for WrenchF32 {
+    /// `[fx, fy, fz, tx, ty, tz]`の順に並べたSI単位の値から`WrenchF32`を作る．
+    fn from(array: [f32; AXIS_COUNT]) -> Self {
+        WrenchF32::from_array(array)
+    }
+}
+
+impl From<Wrench> for WrenchF32 {
+    /// 各成分を単精度に丸めて変換する．
+    fn from(wrench: Wrench) -> Self {
+        let force = wrench.force.map(|f| Newton::new(f.value_unsafe as f32));
+        let torque = wrench
+            .torque
+            .map(|t| NewtonMeter::<f32>::new(t.value_unsafe as f32));
+        WrenchF32 { force, torque }
+    }
+}
+
+impl From<WrenchF32> for Wrench {
+    fn from(wrench: WrenchF32) -> Self {
+        let force = wrench.force.map(|f| Newton::new(f.value_unsafe as f64));
+        let torque = wrench
+            .torque
+            .map(|t| NewtonMeter::<f64>::new(t.value_unsafe as f64));
+        Wrench { force, torque }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SensorModel;
+
+    #[test]
+    fn counts_are_converted_in_single_precision() {
+        let model = SensorModel::Wdf6m200_3;
+        let force_sensitivity = model
+            .force_sensitivity()
+            .unwrap()
+            .map(|s| PerNewton::<f32>::new(s.value_unsafe as f32));
+        let torque_sensitivity = model
+            .torque_sensitivity()
+            .unwrap()
+            .map(|s| PerNewtonMeter::<f32>::new(s.value_unsafe as f32));
+        let counts = [8192 + 249, 8192 - 246, 8192, 8192 + 1665, 8192, 8192 - 1638];
+
+        let wrench = WrenchF32::from_counts(
+            counts,
+            model.center_count(),
+            force_sensitivity,
+            torque_sensitivity,
+        );
+
+        let expected = [10.0, -10.0, 0.0, 1665.0 / 1664.7, 0.0, -1638.0 / 1638.0];
+        for (a, e) in wrench.to_array().iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-5, "{:?}", wrench);
+        }
+    }
+}